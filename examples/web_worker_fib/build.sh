@@ -5,4 +5,4 @@ then
 	exit
 fi
 
-wasm-pack build --target no-modules --out-name wasm --out-dir ./static --no-typescript
+wasm-pack build --target web --out-name wasm --out-dir ./static --no-typescript
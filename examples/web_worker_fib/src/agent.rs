@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use yew_agent::{Agent, AgentLink, HandlerId, Public};
+use yew_agent::{Agent, AgentLink, HandlerId, Pool};
 
 pub struct Worker {
     link: AgentLink<Self>,
@@ -16,7 +16,8 @@ pub struct WorkerOutput {
 }
 
 impl Agent for Worker {
-    type Reach = Public<Self>;
+    // Calculations submitted before the previous ones finished run in parallel
+    type Reach = Pool<Self, 4>;
     type Message = ();
     type Input = WorkerInput;
     type Output = WorkerOutput;
@@ -52,4 +53,8 @@ impl Agent for Worker {
     fn name_of_resource() -> &'static str {
         "wasm.js"
     }
+
+    fn is_module() -> bool {
+        true
+    }
 }
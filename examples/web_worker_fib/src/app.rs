@@ -2,7 +2,7 @@ use crate::agent::{Worker, WorkerInput, WorkerOutput};
 
 use web_sys::HtmlInputElement;
 use yew::prelude::*;
-use yew_agent::{Bridge, Bridged};
+use yew_agent::{Bridge, Bridged, CallError};
 
 pub struct Model {
     clicker_value: u32,
@@ -15,6 +15,7 @@ pub enum Message {
     Click,
     RunWorker,
     WorkerMsg(WorkerOutput),
+    WorkerFailed(CallError),
 }
 
 impl Component for Model {
@@ -32,23 +33,32 @@ impl Component for Model {
         }
     }
 
-    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
         match msg {
             Self::Message::Click => {
                 self.clicker_value += 1;
             }
             Self::Message::RunWorker => {
                 if let Some(input) = self.input_ref.cast::<HtmlInputElement>() {
-                    // start the worker off!
-                    self.worker.send(WorkerInput {
+                    // start the worker off! The pool hands the call to its least busy worker
+                    let call = self.worker.call(WorkerInput {
                         n: input.value_as_number() as u32,
                     });
+                    ctx.link().send_future(async move {
+                        match call.await {
+                            Ok(output) => Self::Message::WorkerMsg(output),
+                            Err(error) => Self::Message::WorkerFailed(error),
+                        }
+                    });
                 }
             }
             Self::Message::WorkerMsg(output) => {
                 // the worker is done!
                 self.fibonacci_output = format!("Fibonacci value: {}", output.value);
             }
+            Self::Message::WorkerFailed(error) => {
+                self.fibonacci_output = format!("The calculation failed: {}", error);
+            }
         }
 
         true
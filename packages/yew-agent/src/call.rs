@@ -0,0 +1,852 @@
+use super::HandlerId;
+use futures_core::Stream;
+use gloo_timers::callback::Timeout;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::{Rc, Weak};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+use yew::scheduler::Shared;
+
+/// A message from a bridge to the agent about one of its calls or streams
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Control {
+    /// The bridge consumed a number of outputs of a stream
+    Acknowledge(usize),
+    /// The bridge cancelled the call or stream
+    Cancel,
+}
+
+/// Sends the control messages about a call or stream with the given id to the agent handling it
+pub(crate) type Controller = Rc<dyn Fn(HandlerId, Control)>;
+
+/// Calls and streams of a bridge, that are waiting for their responses.
+pub(crate) struct PendingCalls<OUT> {
+    state: Shared<PendingState<OUT>>,
+}
+
+struct PendingState<OUT> {
+    next_request: u64,
+    calls: HashMap<u64, Shared<CallState<OUT>>>,
+    streams: HashMap<u64, Shared<StreamState<OUT>>>,
+    /// Requests, that were cancelled while the agent may still respond to them, and whether they
+    /// are streams
+    cancelled: HashMap<u64, bool>,
+    /// Whether the outstanding calls and streams are cancelled, when the bridge is dropped
+    cancel_on_drop: bool,
+}
+
+impl<OUT> PendingState<OUT> {
+    fn next_request(&mut self) -> u64 {
+        let request = self.next_request;
+        self.next_request += 1;
+        request
+    }
+}
+
+struct CallState<OUT> {
+    id: HandlerId,
+    control: Controller,
+    output: Option<Result<OUT, CallError>>,
+    timed_out: bool,
+    waker: Option<Waker>,
+}
+
+impl<OUT> CallState<OUT> {
+    fn wake(&mut self) {
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+
+    fn fail(&mut self, error: CallError) {
+        self.output = Some(Err(error));
+        self.wake();
+    }
+}
+
+impl<OUT> PendingCalls<OUT> {
+    pub fn new() -> Self {
+        Self {
+            state: Rc::new(RefCell::new(PendingState {
+                next_request: 0,
+                calls: HashMap::new(),
+                streams: HashMap::new(),
+                cancelled: HashMap::new(),
+                cancel_on_drop: false,
+            })),
+        }
+    }
+
+    /// Registers a new call and returns the id of the handler tagged with the call
+    pub fn register(&self, id: HandlerId, control: Controller) -> (HandlerId, AgentCall<OUT>) {
+        let mut state = self.state.borrow_mut();
+        let request = state.next_request();
+        let id = id.with_request(request);
+        let call = Rc::new(RefCell::new(CallState {
+            id,
+            control,
+            output: None,
+            timed_out: false,
+            waker: None,
+        }));
+        state.calls.insert(request, call.clone());
+        let call = AgentCall {
+            state: call,
+            pending: Rc::downgrade(&self.state),
+            id,
+        };
+        (id, call)
+    }
+
+    /// Registers a new stream and returns the id of the handler tagged with the stream
+    pub fn register_stream(
+        &self,
+        id: HandlerId,
+        control: Controller,
+        window: usize,
+    ) -> (HandlerId, AgentStream<OUT>) {
+        let mut state = self.state.borrow_mut();
+        let request = state.next_request();
+        let id = id.with_request(request);
+        let stream = Rc::new(RefCell::new(StreamState {
+            id,
+            control: control.clone(),
+            outputs: VecDeque::new(),
+            error: None,
+            ended: false,
+            waker: None,
+        }));
+        state.streams.insert(request, stream.clone());
+        let stream = AgentStream {
+            state: stream,
+            pending: Rc::downgrade(&self.state),
+            id,
+            control,
+            window,
+            consumed: 0,
+        };
+        (id, stream)
+    }
+
+    /// Resolves the call, that `id` is tagged with, or adds the output to its stream.
+    ///
+    /// Returns the output, if neither a call nor a stream is waiting for it. Outputs of cancelled
+    /// calls and streams are dropped.
+    pub fn resolve(&self, id: HandlerId, output: OUT) -> Option<OUT> {
+        let request = match id.request() {
+            Some(request) => request,
+            None => return Some(output),
+        };
+        let (call, stream, cancelled) = {
+            let mut state = self.state.borrow_mut();
+            let call = state.calls.remove(&request);
+            let stream = state.streams.get(&request).cloned();
+            // The response to a cancelled call is dropped once, the outputs of a cancelled stream
+            // until it ends
+            let cancelled = match state.cancelled.get(&request) {
+                Some(true) => true,
+                Some(false) => state.cancelled.remove(&request).is_some(),
+                None => false,
+            };
+            (call, stream, cancelled)
+        };
+        if let Some(call) = call {
+            let mut call = call.borrow_mut();
+            call.output = Some(Ok(output));
+            call.wake();
+            None
+        } else if let Some(stream) = stream {
+            let mut stream = stream.borrow_mut();
+            stream.outputs.push_back(output);
+            stream.wake();
+            None
+        } else if cancelled {
+            None
+        } else {
+            Some(output)
+        }
+    }
+
+    /// Ends the stream, that `id` is tagged with
+    pub fn end(&self, id: HandlerId) {
+        let stream = id.request().and_then(|request| {
+            let mut state = self.state.borrow_mut();
+            state.cancelled.remove(&request);
+            state.streams.remove(&request)
+        });
+        if let Some(stream) = stream {
+            let mut stream = stream.borrow_mut();
+            stream.ended = true;
+            stream.wake();
+        }
+    }
+
+    /// Fails the call or stream, that `id` is tagged with
+    pub fn fail(&self, id: HandlerId, error: CallError) {
+        let (call, stream) = match id.request() {
+            Some(request) => {
+                let mut state = self.state.borrow_mut();
+                state.cancelled.remove(&request);
+                (state.calls.remove(&request), state.streams.remove(&request))
+            }
+            None => return,
+        };
+        if let Some(call) = call {
+            call.borrow_mut().fail(error);
+        } else if let Some(stream) = stream {
+            stream.borrow_mut().fail(error);
+        }
+    }
+
+    /// Fails all calls and streams of the bridge, that are waiting for responses
+    pub fn fail_all(&self, error: CallError) {
+        let (calls, streams) = {
+            let mut state = self.state.borrow_mut();
+            state.cancelled.clear();
+            let calls: Vec<_> = state.calls.drain().map(|(_, call)| call).collect();
+            let streams: Vec<_> = state.streams.drain().map(|(_, stream)| stream).collect();
+            (calls, streams)
+        };
+        for call in calls {
+            call.borrow_mut().fail(error.clone());
+        }
+        for stream in streams {
+            stream.borrow_mut().fail(error.clone());
+        }
+    }
+}
+
+impl<OUT> PendingCalls<OUT> {
+    /// Cancel the outstanding calls and streams, when the bridge is dropped.
+    pub fn cancel_on_drop(&self) {
+        self.state.borrow_mut().cancel_on_drop = true;
+    }
+
+    /// Cancels the outstanding calls and streams of a bridge, that is dropped, if the bridge
+    /// cancels them on drop.
+    pub fn close(&self) {
+        let (calls, streams) = {
+            let mut state = self.state.borrow_mut();
+            if !state.cancel_on_drop {
+                return;
+            }
+            let calls: Vec<_> = state.calls.drain().collect();
+            let streams: Vec<_> = state.streams.drain().collect();
+            let cancelled = calls.iter().map(|(request, _)| (*request, false));
+            let cancelled = cancelled.chain(streams.iter().map(|(request, _)| (*request, true)));
+            let cancelled: Vec<_> = cancelled.collect();
+            state.cancelled.extend(cancelled);
+            (calls, streams)
+        };
+        for (_, call) in calls {
+            let call = call.borrow();
+            (call.control)(call.id, Control::Cancel);
+        }
+        for (_, stream) in streams {
+            let stream = stream.borrow();
+            (stream.control)(stream.id, Control::Cancel);
+        }
+    }
+}
+
+impl<OUT> Clone for PendingCalls<OUT> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+        }
+    }
+}
+
+/// A future resolving to the response of an agent to one input.
+///
+/// Created by [`Bridge::call`](crate::Bridge::call). If the call is dropped before the agent
+/// responds, the response is passed to the callback of the bridge instead, unless the call is
+/// cancelled with [`AgentCall::cancel`].
+///
+/// The call fails with [`CallError::Crashed`], if the worker running the agent crashes before it
+/// responds.
+pub struct AgentCall<OUT> {
+    state: Shared<CallState<OUT>>,
+    pending: Weak<RefCell<PendingState<OUT>>>,
+    id: HandlerId,
+}
+
+impl<OUT> AgentCall<OUT> {
+    /// Returns a call, that failed with `error` right away
+    pub(crate) fn failed(error: CallError) -> Self {
+        let id = HandlerId::new(0, false);
+        AgentCall {
+            state: Rc::new(RefCell::new(CallState {
+                id,
+                control: Rc::new(|_, _| {}),
+                output: Some(Err(error)),
+                timed_out: false,
+                waker: None,
+            })),
+            pending: Weak::new(),
+            id,
+        }
+    }
+
+    /// Cancel the call.
+    ///
+    /// The agent can learn about it through the [`CancellationToken`](crate::CancellationToken)
+    /// of the call. Its response is dropped.
+    pub fn cancel(self) {
+        cancel_call(&self.state, &self.pending);
+    }
+}
+
+/// Cancels a call, unless the agent responded to it already
+fn cancel_call<OUT>(call: &Shared<CallState<OUT>>, pending: &Weak<RefCell<PendingState<OUT>>>) {
+    let (id, control) = {
+        let call = call.borrow();
+        (call.id, call.control.clone())
+    };
+    let (pending, request) = match (pending.upgrade(), id.request()) {
+        (Some(pending), Some(request)) => (pending, request),
+        _ => return,
+    };
+    {
+        let mut pending = pending.borrow_mut();
+        if pending.calls.remove(&request).is_none() {
+            return;
+        }
+        pending.cancelled.insert(request, false);
+    }
+    control(id, Control::Cancel);
+}
+
+/// Fails a call with [`CallError::TimedOut`] and cancels it, so that a late response is dropped
+fn time_out<OUT>(call: &Weak<RefCell<CallState<OUT>>>, pending: &Weak<RefCell<PendingState<OUT>>>) {
+    let call = match call.upgrade() {
+        Some(call) => call,
+        None => return,
+    };
+    {
+        let mut call = call.borrow_mut();
+        if call.output.is_some() {
+            return;
+        }
+        call.timed_out = true;
+        call.wake();
+    }
+    cancel_call(&call, pending);
+}
+
+impl<OUT: 'static> AgentCall<OUT> {
+    /// Wait for the response at most for the duration of `timeout`.
+    ///
+    /// The call is cancelled, when it times out. Timeouts longer than the maximum delay of browser
+    /// timers, about 24 days, are shortened to it.
+    pub fn timeout(self, timeout: Duration) -> TimeoutCall<OUT> {
+        let state = Rc::downgrade(&self.state);
+        let pending = self.pending.clone();
+        let timer = Timeout::new(timeout_millis(timeout), move || time_out(&state, &pending));
+        TimeoutCall {
+            call: self,
+            _timer: timer,
+        }
+    }
+}
+
+/// Returns the delay of a timer in milliseconds, clamped to the maximum delay of browser timers,
+/// which fire right away, if the delay is longer
+fn timeout_millis(timeout: Duration) -> u32 {
+    timeout.as_millis().min(i32::MAX as u128) as u32
+}
+
+impl<OUT> Future for AgentCall<OUT> {
+    type Output = Result<OUT, CallError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.state.borrow_mut();
+        match state.output.take() {
+            Some(output) => Poll::Ready(output),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<OUT> Drop for AgentCall<OUT> {
+    fn drop(&mut self) {
+        if let (Some(pending), Some(request)) = (self.pending.upgrade(), self.id.request()) {
+            pending.borrow_mut().calls.remove(&request);
+        }
+    }
+}
+
+impl<OUT> fmt::Debug for AgentCall<OUT> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("AgentCall<_>")
+    }
+}
+
+/// A future resolving to the response of an agent to one input, or to
+/// [`CallError::TimedOut`], if the agent does not respond in time. The call is cancelled then.
+///
+/// Created by [`AgentCall::timeout`].
+pub struct TimeoutCall<OUT> {
+    call: AgentCall<OUT>,
+    _timer: Timeout,
+}
+
+impl<OUT> TimeoutCall<OUT> {
+    /// Cancel the call.
+    ///
+    /// See [`AgentCall::cancel`].
+    pub fn cancel(self) {
+        self.call.cancel();
+    }
+}
+
+impl<OUT> Future for TimeoutCall<OUT> {
+    type Output = Result<OUT, CallError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.call.state.borrow_mut();
+        match state.output.take() {
+            Some(output) => Poll::Ready(output),
+            None if state.timed_out => Poll::Ready(Err(CallError::TimedOut)),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<OUT> fmt::Debug for TimeoutCall<OUT> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TimeoutCall<_>")
+    }
+}
+
+/// Error of a call or stream, that the agent did not finish.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CallError {
+    /// The agent did not respond to the call in time.
+    TimedOut,
+    /// The worker running the agent crashed with an error message.
+    Crashed(String),
+    /// The bridge does not support calls and streams.
+    Unsupported,
+}
+
+impl fmt::Display for CallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CallError::TimedOut => f.write_str("the agent did not respond in time"),
+            CallError::Crashed(message) => {
+                write!(f, "the worker of the agent crashed: {}", message)
+            }
+            CallError::Unsupported => f.write_str("the bridge does not support calls and streams"),
+        }
+    }
+}
+
+impl std::error::Error for CallError {}
+
+struct StreamState<OUT> {
+    id: HandlerId,
+    control: Controller,
+    outputs: VecDeque<OUT>,
+    /// Error, that the stream fails with after its remaining outputs
+    error: Option<CallError>,
+    ended: bool,
+    waker: Option<Waker>,
+}
+
+impl<OUT> StreamState<OUT> {
+    fn wake(&mut self) {
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+
+    fn fail(&mut self, error: CallError) {
+        self.error = Some(error);
+        self.ended = true;
+        self.wake();
+    }
+}
+
+/// A stream of the outputs, that an agent sends in response to one input.
+///
+/// Created by [`Bridge::stream`](crate::Bridge::stream). The agent sends the outputs through an
+/// [`OutputStream`](crate::OutputStream). It sends only a limited number of outputs ahead of the
+/// ones consumed from this stream, so a slow consumer slows down the agent instead of queuing up
+/// its outputs.
+///
+/// If the stream is dropped before it ends, the remaining outputs are passed to the callback of
+/// the bridge instead, and are no longer held back by the agent. They are dropped, if the stream
+/// is cancelled with [`AgentStream::cancel`].
+///
+/// If the worker running the agent crashes, the stream yields [`CallError::Crashed`] after the
+/// outputs it received, and ends.
+pub struct AgentStream<OUT> {
+    state: Shared<StreamState<OUT>>,
+    pending: Weak<RefCell<PendingState<OUT>>>,
+    id: HandlerId,
+    control: Controller,
+    /// Number of outputs, that the agent sends ahead of the consumed ones
+    window: usize,
+    /// Number of consumed outputs, that have not been acknowledged yet
+    consumed: usize,
+}
+
+impl<OUT> AgentStream<OUT> {
+    /// Returns a stream, that failed with `error` right away
+    pub(crate) fn failed(error: CallError) -> Self {
+        let id = HandlerId::new(0, false);
+        let control: Controller = Rc::new(|_, _| {});
+        AgentStream {
+            state: Rc::new(RefCell::new(StreamState {
+                id,
+                control: control.clone(),
+                outputs: VecDeque::new(),
+                error: Some(error),
+                ended: true,
+                waker: None,
+            })),
+            pending: Weak::new(),
+            id,
+            control,
+            window: 1,
+            consumed: 0,
+        }
+    }
+
+    /// Cancel the stream.
+    ///
+    /// The agent can learn about it through the [`CancellationToken`](crate::CancellationToken)
+    /// of the stream. Its remaining outputs are dropped.
+    pub fn cancel(self) {
+        if self.state.borrow().ended {
+            return;
+        }
+        if let (Some(pending), Some(request)) = (self.pending.upgrade(), self.id.request()) {
+            pending.borrow_mut().cancelled.insert(request, true);
+        }
+        (self.control)(self.id, Control::Cancel);
+    }
+}
+
+impl<OUT> Stream for AgentStream<OUT> {
+    type Item = Result<OUT, CallError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let output = {
+            let mut state = this.state.borrow_mut();
+            match state.outputs.pop_front() {
+                Some(output) => output,
+                None if state.ended => return Poll::Ready(state.error.take().map(Err)),
+                None => {
+                    state.waker = Some(cx.waker().clone());
+                    return Poll::Pending;
+                }
+            }
+        };
+        // Acknowledges the outputs in batches to save messages to workers
+        this.consumed += 1;
+        if this.consumed >= (this.window / 2).max(1) {
+            (this.control)(this.id, Control::Acknowledge(this.consumed));
+            this.consumed = 0;
+        }
+        Poll::Ready(Some(Ok(output)))
+    }
+}
+
+impl<OUT> Drop for AgentStream<OUT> {
+    fn drop(&mut self) {
+        let pending = match (self.pending.upgrade(), self.id.request()) {
+            (Some(pending), Some(request)) => pending.borrow_mut().streams.remove(&request),
+            _ => None,
+        };
+        // The agent must not wait for outputs to be consumed, that go to the callback now
+        if pending.is_some() && !self.state.borrow().ended {
+            (self.control)(self.id, Control::Acknowledge(usize::MAX));
+        }
+    }
+}
+
+impl<OUT> fmt::Debug for AgentStream<OUT> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("AgentStream<_>")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Agent, AgentLifecycleEvent, AgentLink, AgentScope, CancellationToken, Responder};
+    use std::sync::Arc;
+    use std::task::Wake;
+
+    const WINDOW: usize = 8;
+
+    struct NoopWaker;
+
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    fn poll<F: Future + Unpin>(future: &mut F) -> Poll<F::Output> {
+        let waker = Waker::from(Arc::new(NoopWaker));
+        Pin::new(future).poll(&mut Context::from_waker(&waker))
+    }
+
+    fn poll_next<S: Stream + Unpin>(stream: &mut S) -> Poll<Option<S::Item>> {
+        let waker = Waker::from(Arc::new(NoopWaker));
+        Pin::new(stream).poll_next(&mut Context::from_waker(&waker))
+    }
+
+    /// Returns a controller, that records the control messages sent through it
+    fn recorder() -> (Controller, Shared<Vec<(HandlerId, Control)>>) {
+        let controls = Rc::new(RefCell::new(Vec::new()));
+        let controller = {
+            let controls = controls.clone();
+            Rc::new(move |id, control| controls.borrow_mut().push((id, control)))
+        };
+        (controller, controls)
+    }
+
+    #[test]
+    fn routes_outputs_to_calls() {
+        let calls = PendingCalls::new();
+        let (controller, _) = recorder();
+        let handler = HandlerId::new(1, true);
+        let (first_id, mut first) = calls.register(handler, controller.clone());
+        let (second_id, mut second) = calls.register(handler, controller);
+        assert_eq!(first_id.request(), Some(0));
+        assert_eq!(second_id.request(), Some(1));
+        assert_eq!(poll(&mut first), Poll::Pending);
+
+        assert_eq!(calls.resolve(second_id, "b"), None);
+        assert_eq!(calls.resolve(handler, "untagged"), Some("untagged"));
+        assert_eq!(calls.resolve(first_id, "a"), None);
+        assert_eq!(poll(&mut first), Poll::Ready(Ok("a")));
+        assert_eq!(poll(&mut second), Poll::Ready(Ok("b")));
+
+        // Further outputs to a resolved call go to the callback
+        assert_eq!(calls.resolve(first_id, "c"), Some("c"));
+    }
+
+    #[test]
+    fn passes_outputs_of_dropped_calls_to_callback() {
+        let calls = PendingCalls::new();
+        let (controller, controls) = recorder();
+        let (id, call) = calls.register(HandlerId::new(1, true), controller);
+        drop(call);
+        assert_eq!(calls.resolve(id, 1), Some(1));
+        assert!(controls.borrow().is_empty());
+    }
+
+    #[test]
+    fn drops_outputs_of_cancelled_calls() {
+        let calls = PendingCalls::new();
+        let (controller, controls) = recorder();
+        let (id, call) = calls.register(HandlerId::new(1, true), controller);
+        call.cancel();
+        assert_eq!(*controls.borrow(), vec![(id, Control::Cancel)]);
+        assert_eq!(calls.resolve(id, 1), None);
+        assert_eq!(calls.resolve(id, 2), Some(2));
+    }
+
+    /// Holds the cancellation tokens of its calls, until it gets a message
+    struct Holder {
+        link: AgentLink<Self>,
+        tokens: Vec<CancellationToken>,
+    }
+
+    impl Agent for Holder {
+        type Reach = crate::Context<Self>;
+        type Message = ();
+        type Input = ();
+        type Output = ();
+
+        fn create(link: AgentLink<Self>) -> Self {
+            Self {
+                link,
+                tokens: Vec::new(),
+            }
+        }
+
+        fn update(&mut self, _msg: Self::Message) {
+            self.tokens.clear();
+        }
+
+        fn handle_input(&mut self, _msg: Self::Input, id: HandlerId) {
+            self.tokens.push(self.link.cancellation(id));
+        }
+    }
+
+    /// Passes the responses of an agent to the calls of a bridge
+    struct Forward(PendingCalls<()>);
+
+    impl Responder<Holder> for Forward {
+        fn respond(&self, id: HandlerId, output: ()) {
+            self.0.resolve(id, output);
+        }
+
+        fn end_stream(&self, id: HandlerId) {
+            self.0.end(id);
+        }
+    }
+
+    #[test]
+    fn forgets_cancelled_calls_released_by_agents() {
+        let scope = AgentScope::<Holder>::new();
+        let calls = PendingCalls::new();
+        let link = AgentLink::connect(&scope, Forward(calls.clone()));
+        scope.send(AgentLifecycleEvent::Create(link));
+        let controller: Controller = {
+            let scope = scope.clone();
+            Rc::new(move |id, control| scope.control(id, control))
+        };
+
+        let (id, call) = calls.register(HandlerId::new(1, true), controller);
+        scope.send(AgentLifecycleEvent::Input((), id));
+        call.cancel();
+        assert_eq!(calls.state.borrow().cancelled.len(), 1);
+        assert_eq!(scope.cancellations.borrow().len(), 1);
+
+        // The agent drops the tokens of the call instead of responding to it
+        scope.send(AgentLifecycleEvent::Message(()));
+        assert!(calls.state.borrow().cancelled.is_empty());
+        assert!(scope.cancellations.borrow().is_empty());
+    }
+
+    #[test]
+    fn cancels_timed_out_calls() {
+        let calls = PendingCalls::new();
+        let (controller, controls) = recorder();
+        let (id, call) = calls.register(HandlerId::new(1, true), controller);
+        time_out(&Rc::downgrade(&call.state), &call.pending);
+        assert!(call.state.borrow().timed_out);
+        assert_eq!(*controls.borrow(), vec![(id, Control::Cancel)]);
+
+        // The late response goes nowhere
+        assert_eq!(calls.resolve(id, 1), None);
+        assert!(call.state.borrow().output.is_none());
+        assert!(calls.state.borrow().cancelled.is_empty());
+    }
+
+    #[test]
+    fn routes_outputs_to_streams() {
+        let calls = PendingCalls::new();
+        let (controller, controls) = recorder();
+        let (id, mut stream) = calls.register_stream(HandlerId::new(1, true), controller, WINDOW);
+        assert_eq!(poll_next(&mut stream), Poll::Pending);
+
+        for i in 0..WINDOW {
+            assert_eq!(calls.resolve(id, i), None);
+        }
+        for i in 0..WINDOW {
+            assert_eq!(poll_next(&mut stream), Poll::Ready(Some(Ok(i))));
+        }
+        // Consumed outputs are acknowledged in batches
+        let half = Control::Acknowledge(WINDOW / 2);
+        assert_eq!(*controls.borrow(), vec![(id, half), (id, half)]);
+
+        calls.end(id);
+        assert_eq!(poll_next(&mut stream), Poll::Ready(None));
+        assert_eq!(calls.resolve(id, 0), Some(0));
+    }
+
+    #[test]
+    fn passes_outputs_of_dropped_streams_to_callback() {
+        let calls = PendingCalls::new();
+        let (controller, controls) = recorder();
+        let (id, stream) = calls.register_stream(HandlerId::new(1, true), controller, WINDOW);
+        drop(stream);
+        // The agent no longer waits for the outputs to be consumed
+        assert_eq!(
+            *controls.borrow(),
+            vec![(id, Control::Acknowledge(usize::MAX))]
+        );
+        assert_eq!(calls.resolve(id, 1), Some(1));
+    }
+
+    #[test]
+    fn cancels_on_close() {
+        let calls = PendingCalls::<u32>::new();
+        let (controller, controls) = recorder();
+        let handler = HandlerId::new(1, true);
+        let (call_id, _call) = calls.register(handler, controller.clone());
+        calls.close();
+        assert!(controls.borrow().is_empty());
+
+        calls.cancel_on_drop();
+        let (stream_id, _stream) = calls.register_stream(handler, controller, WINDOW);
+        calls.close();
+        let mut cancelled = controls.borrow().clone();
+        cancelled.sort_by_key(|(id, _)| id.request());
+        assert_eq!(
+            cancelled,
+            vec![(call_id, Control::Cancel), (stream_id, Control::Cancel)]
+        );
+        assert_eq!(calls.resolve(call_id, 1), None);
+        assert_eq!(calls.resolve(stream_id, 2), None);
+        assert_eq!(calls.resolve(stream_id, 3), None);
+    }
+
+    #[test]
+    fn fails_calls_and_streams_of_crashed_workers() {
+        let calls = PendingCalls::new();
+        let (controller, _) = recorder();
+        let handler = HandlerId::new(1, true);
+        let (call_id, mut call) = calls.register(handler, controller.clone());
+        let (stream_id, mut stream) = calls.register_stream(handler, controller.clone(), WINDOW);
+        let crashed = CallError::Crashed("panic".to_string());
+
+        calls.fail(call_id, crashed.clone());
+        assert_eq!(poll(&mut call), Poll::Ready(Err(crashed.clone())));
+
+        // The stream yields its outputs before the error
+        assert_eq!(calls.resolve(stream_id, 1), None);
+        calls.fail(stream_id, crashed.clone());
+        assert_eq!(poll_next(&mut stream), Poll::Ready(Some(Ok(1))));
+        assert_eq!(
+            poll_next(&mut stream),
+            Poll::Ready(Some(Err(crashed.clone())))
+        );
+        assert_eq!(poll_next(&mut stream), Poll::Ready(None));
+
+        let (_, mut call) = calls.register(handler, controller.clone());
+        let (_, mut stream) = calls.register_stream(handler, controller, WINDOW);
+        calls.fail_all(crashed.clone());
+        assert_eq!(poll(&mut call), Poll::Ready(Err(crashed.clone())));
+        assert_eq!(poll_next(&mut stream), Poll::Ready(Some(Err(crashed))));
+        assert_eq!(poll_next(&mut stream), Poll::Ready(None));
+    }
+
+    #[test]
+    fn fails_right_away() {
+        let mut call = AgentCall::<u32>::failed(CallError::Unsupported);
+        assert_eq!(poll(&mut call), Poll::Ready(Err(CallError::Unsupported)));
+        let mut stream = AgentStream::<u32>::failed(CallError::Unsupported);
+        assert_eq!(
+            poll_next(&mut stream),
+            Poll::Ready(Some(Err(CallError::Unsupported)))
+        );
+        assert_eq!(poll_next(&mut stream), Poll::Ready(None));
+    }
+
+    #[test]
+    fn clamps_timeouts() {
+        assert_eq!(timeout_millis(Duration::from_millis(1500)), 1500);
+        assert_eq!(
+            timeout_millis(Duration::from_secs(u64::MAX)),
+            i32::MAX as u32
+        );
+    }
+}
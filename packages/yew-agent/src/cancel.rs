@@ -0,0 +1,448 @@
+use super::*;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll, Waker};
+use yew::scheduler::Shared;
+
+/// Cancellations of the calls and streams of an agent by the raw id of their handler and their
+/// request
+pub(crate) type SharedCancellations = Shared<HashMap<(usize, u64), Shared<CancellationState>>>;
+
+#[derive(Default)]
+pub(crate) struct CancellationState {
+    cancelled: bool,
+    /// Number of live tokens of the call or stream
+    tokens: usize,
+    /// Whether the agent handled the input of the call or stream
+    handled: bool,
+    /// Whether the agent responded to the call or ended the stream
+    finished: bool,
+    wakers: Vec<Waker>,
+}
+
+/// Registers the call or stream, that `id` is tagged with, when the agent receives its input.
+///
+/// The registration keeps a cancellation, that arrives before the agent asks for its token, until
+/// the agent finishes the call or stream.
+pub(crate) fn start_request(cancellations: &SharedCancellations, id: HandlerId) {
+    if let Some(request) = id.request() {
+        cancellations
+            .borrow_mut()
+            .entry((id.raw_id(), request))
+            .or_default();
+    }
+}
+
+/// Records, that the agent handled the input of the call or stream, that `id` is tagged with
+pub(crate) fn handle_request(cancellations: &SharedCancellations, id: HandlerId) {
+    if let Some(request) = id.request() {
+        if let Some(state) = cancellations.borrow().get(&(id.raw_id(), request)) {
+            state.borrow_mut().handled = true;
+        }
+    }
+}
+
+/// Removes the call or stream, that `id` is tagged with, once the agent finished it and dropped
+/// all of its tokens.
+///
+/// Returns `false`, if the call or stream was released before, see [`release_request`].
+pub(crate) fn finish_request(cancellations: &SharedCancellations, id: HandlerId) -> bool {
+    let key = match id.request() {
+        Some(request) => (id.raw_id(), request),
+        None => return true,
+    };
+    let mut cancellations = cancellations.borrow_mut();
+    let unused = match cancellations.get(&key) {
+        Some(state) => {
+            let mut state = state.borrow_mut();
+            state.finished = true;
+            state.tokens == 0
+        }
+        None => return false,
+    };
+    if unused {
+        cancellations.remove(&key);
+    }
+    true
+}
+
+/// Removes the call or stream, that `id` is tagged with, if it was cancelled after the agent
+/// handled its input and the agent dropped all of its tokens. The agent does not respond to it
+/// anymore then.
+///
+/// Returns whether the call or stream was removed.
+pub(crate) fn release_request(cancellations: &SharedCancellations, id: HandlerId) -> bool {
+    let key = match id.request() {
+        Some(request) => (id.raw_id(), request),
+        None => return false,
+    };
+    let mut cancellations = cancellations.borrow_mut();
+    let released = matches!(
+        cancellations.get(&key),
+        Some(state) if {
+            let state = state.borrow();
+            state.cancelled && state.handled && state.tokens == 0 && !state.finished
+        }
+    );
+    if released {
+        cancellations.remove(&key);
+    }
+    released
+}
+
+/// Cancels the call or stream, that `id` is tagged with, unless the agent finished it already.
+///
+/// The call or stream must be released with [`release_request`] afterwards.
+pub(crate) fn cancel_request(cancellations: &SharedCancellations, id: HandlerId) {
+    let request = match id.request() {
+        Some(request) => request,
+        None => return,
+    };
+    let state = match cancellations.borrow().get(&(id.raw_id(), request)) {
+        Some(state) => state.clone(),
+        None => return,
+    };
+    let wakers = {
+        let mut state = state.borrow_mut();
+        state.cancelled = true;
+        std::mem::take(&mut state.wakers)
+    };
+    for waker in wakers {
+        waker.wake();
+    }
+}
+
+/// Removes the cancellations of a handler, that disconnected
+pub(crate) fn remove_cancellations(cancellations: &SharedCancellations, id: HandlerId) {
+    cancellations
+        .borrow_mut()
+        .retain(|(raw_id, _), _| *raw_id != id.raw_id());
+}
+
+/// Tells an agent, whether a bridge cancelled a call or stream.
+///
+/// Created by [`AgentLink::cancellation`]. A bridge cancels its calls and streams with
+/// [`AgentCall::cancel`] and [`AgentStream::cancel`], or when it is dropped, if it was told so
+/// with [`Bridge::cancel_on_drop`]. Ids of inputs, that were sent with [`Bridge::send`], are never
+/// cancelled, and cancellations, that arrive after the agent responded to the call or ended the
+/// stream, are ignored.
+///
+/// Once a call or stream is cancelled and the agent holds none of its tokens, it is released: the
+/// bridge is told, that the agent does not respond to it, and responses to it are dropped.
+///
+/// # Example
+///
+/// ```
+/// use yew_agent::testing::TestAgent;
+/// use yew_agent::{Agent, AgentLink, CancellationToken, Context, HandlerId};
+///
+/// struct Search {
+///     link: AgentLink<Self>,
+///     pending: Vec<(HandlerId, CancellationToken)>,
+/// }
+///
+/// impl Agent for Search {
+///     type Reach = Context<Self>;
+///     type Message = ();
+///     type Input = String;
+///     type Output = usize;
+///
+///     fn create(link: AgentLink<Self>) -> Self {
+///         Self {
+///             link,
+///             pending: Vec::new(),
+///         }
+///     }
+///
+///     // Answers the searches, that were not cancelled in the meantime
+///     fn update(&mut self, _msg: Self::Message) {
+///         for (id, token) in self.pending.drain(..) {
+///             if !token.is_cancelled() {
+///                 self.link.respond(id, 42);
+///             }
+///         }
+///     }
+///
+///     fn handle_input(&mut self, _msg: Self::Input, id: HandlerId) {
+///         let token = self.link.cancellation(id);
+///         self.pending.push((id, token));
+///     }
+/// }
+///
+/// let mut agent = TestAgent::<Search>::new();
+/// let bridge = agent.connect();
+/// let first = agent.send_call(bridge, "yew".to_string());
+/// agent.send_call(bridge, "agent".to_string());
+/// agent.cancel(first);
+/// agent.send_message(());
+/// assert_eq!(agent.take_responses(bridge), vec![42]);
+/// ```
+pub struct CancellationToken {
+    state: Shared<CancellationState>,
+    /// Where the token is registered, unless it is never cancelled
+    registration: Option<Rc<Registration>>,
+}
+
+struct Registration {
+    cancellations: SharedCancellations,
+    key: (usize, u64),
+    /// Releases the call or stream, once it is cancelled and the last token is dropped
+    release: Box<dyn Fn()>,
+}
+
+impl CancellationToken {
+    pub(crate) fn new(
+        cancellations: &SharedCancellations,
+        id: HandlerId,
+        release: Box<dyn Fn()>,
+    ) -> Self {
+        match id.request() {
+            Some(request) => {
+                let key = (id.raw_id(), request);
+                let state = cancellations.borrow_mut().entry(key).or_default().clone();
+                state.borrow_mut().tokens += 1;
+                CancellationToken {
+                    state,
+                    registration: Some(Rc::new(Registration {
+                        cancellations: cancellations.clone(),
+                        key,
+                        release,
+                    })),
+                }
+            }
+            None => CancellationToken {
+                state: Rc::new(RefCell::new(CancellationState {
+                    tokens: 1,
+                    ..CancellationState::default()
+                })),
+                registration: None,
+            },
+        }
+    }
+
+    /// Indicates if the call or stream was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.state.borrow().cancelled
+    }
+
+    /// Returns a future, that resolves when the call or stream is cancelled.
+    pub fn cancelled(&self) -> Cancelled {
+        Cancelled {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl Clone for CancellationToken {
+    fn clone(&self) -> Self {
+        self.state.borrow_mut().tokens += 1;
+        CancellationToken {
+            state: self.state.clone(),
+            registration: self.registration.clone(),
+        }
+    }
+}
+
+impl Drop for CancellationToken {
+    fn drop(&mut self) {
+        let (unused, cancelled) = {
+            let mut state = self.state.borrow_mut();
+            state.tokens -= 1;
+            (state.tokens == 0 && state.finished, state.cancelled)
+        };
+        let registration = match &self.registration {
+            Some(registration) if unused || cancelled => registration,
+            _ => return,
+        };
+        if unused {
+            // The last token of a finished call or stream removes it from the cancellations of the
+            // agent, unless the handler disconnected and its id was reused in the meantime
+            let mut cancellations = registration.cancellations.borrow_mut();
+            let registered = matches!(
+                cancellations.get(&registration.key),
+                Some(state) if Rc::ptr_eq(state, &self.state)
+            );
+            if registered {
+                cancellations.remove(&registration.key);
+            }
+        } else if self.state.borrow().tokens == 0 {
+            (registration.release)();
+        }
+    }
+}
+
+impl fmt::Debug for CancellationToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CancellationToken")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+/// A future resolving, when a call or stream is cancelled.
+///
+/// Created by [`CancellationToken::cancelled`].
+pub struct Cancelled {
+    state: Shared<CancellationState>,
+}
+
+impl Future for Cancelled {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.state.borrow_mut();
+        if state.cancelled {
+            Poll::Ready(())
+        } else {
+            state.wakers.push(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+impl fmt::Debug for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Cancelled")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(raw_id: usize, request: u64) -> HandlerId {
+        HandlerId::new(raw_id, true).with_request(request)
+    }
+
+    fn new_token(cancellations: &SharedCancellations, id: HandlerId) -> CancellationToken {
+        CancellationToken::new(cancellations, id, Box::new(|| {}))
+    }
+
+    #[test]
+    fn cancels_tokens_of_requests() {
+        let cancellations = SharedCancellations::default();
+        let first = new_token(&cancellations, request(1, 0));
+        let second = new_token(&cancellations, request(1, 1));
+        cancel_request(&cancellations, request(1, 0));
+        assert!(first.is_cancelled());
+        assert!(first.clone().is_cancelled());
+        assert!(!second.is_cancelled());
+    }
+
+    #[test]
+    fn keeps_cancellations_until_requests_finish() {
+        let cancellations = SharedCancellations::default();
+        start_request(&cancellations, request(1, 0));
+        cancel_request(&cancellations, request(1, 0));
+        let token = new_token(&cancellations, request(1, 0));
+        assert!(token.is_cancelled());
+
+        start_request(&cancellations, request(2, 0));
+        cancel_request(&cancellations, request(2, 0));
+        remove_cancellations(&cancellations, HandlerId::new(2, true));
+        let token = new_token(&cancellations, request(2, 0));
+        assert!(!token.is_cancelled());
+    }
+
+    #[test]
+    fn ignores_cancellations_of_finished_requests() {
+        let cancellations = SharedCancellations::default();
+        start_request(&cancellations, request(1, 0));
+        finish_request(&cancellations, request(1, 0));
+        assert!(cancellations.borrow().is_empty());
+        cancel_request(&cancellations, request(1, 0));
+        assert!(cancellations.borrow().is_empty());
+    }
+
+    #[test]
+    fn never_cancels_untagged_ids() {
+        let cancellations = SharedCancellations::default();
+        let id = HandlerId::new(1, true);
+        start_request(&cancellations, id);
+        let token = new_token(&cancellations, id);
+        cancel_request(&cancellations, id);
+        assert!(!token.is_cancelled());
+        assert!(!token.clone().is_cancelled());
+        assert!(cancellations.borrow().is_empty());
+    }
+
+    #[test]
+    fn removes_finished_requests_with_last_token() {
+        let cancellations = SharedCancellations::default();
+        start_request(&cancellations, request(1, 0));
+        let token = new_token(&cancellations, request(1, 0));
+        let clone = token.clone();
+        let cancelled = token.cancelled();
+        drop(token);
+        drop(clone);
+        // The request goes on without tokens, until the agent finishes it
+        assert_eq!(cancellations.borrow().len(), 1);
+
+        let token = new_token(&cancellations, request(1, 0));
+        finish_request(&cancellations, request(1, 0));
+        assert_eq!(cancellations.borrow().len(), 1);
+        drop(token);
+        assert!(cancellations.borrow().is_empty());
+        drop(cancelled);
+    }
+
+    #[test]
+    fn keeps_requests_reusing_ids_of_disconnected_handlers() {
+        let cancellations = SharedCancellations::default();
+        start_request(&cancellations, request(1, 0));
+        let token = new_token(&cancellations, request(1, 0));
+        finish_request(&cancellations, request(1, 0));
+        remove_cancellations(&cancellations, HandlerId::new(1, true));
+
+        start_request(&cancellations, request(1, 0));
+        drop(token);
+        assert_eq!(cancellations.borrow().len(), 1);
+    }
+
+    #[test]
+    fn releases_cancelled_requests_without_tokens() {
+        let cancellations = SharedCancellations::default();
+        let released = Rc::new(RefCell::new(Vec::new()));
+        let id = request(1, 0);
+        start_request(&cancellations, id);
+        let release = {
+            let (cancellations, released) = (cancellations.clone(), released.clone());
+            move || {
+                released
+                    .borrow_mut()
+                    .push(release_request(&cancellations, id))
+            }
+        };
+        let token = CancellationToken::new(&cancellations, id, Box::new(release));
+
+        // Cancellations, that arrive before the agent handled the input, are kept
+        cancel_request(&cancellations, id);
+        assert!(!release_request(&cancellations, id));
+        handle_request(&cancellations, id);
+        assert!(!release_request(&cancellations, id));
+
+        drop(token);
+        assert_eq!(*released.borrow(), vec![true]);
+        assert!(cancellations.borrow().is_empty());
+        assert!(!finish_request(&cancellations, id));
+    }
+
+    #[test]
+    fn releases_cancelled_requests_after_their_input_was_handled() {
+        let cancellations = SharedCancellations::default();
+        start_request(&cancellations, request(1, 0));
+        handle_request(&cancellations, request(1, 0));
+        cancel_request(&cancellations, request(1, 0));
+        assert!(release_request(&cancellations, request(1, 0)));
+        assert!(cancellations.borrow().is_empty());
+
+        start_request(&cancellations, request(1, 1));
+        handle_request(&cancellations, request(1, 1));
+        assert!(finish_request(&cancellations, request(1, 1)));
+        assert!(!release_request(&cancellations, request(1, 1)));
+    }
+}
@@ -21,6 +21,22 @@ where
         let mut bridge = self.inner.borrow_mut();
         bridge.send(msg);
     }
+
+    /// Send a message to an agent and wait for the response to it.
+    ///
+    /// See [`Bridge::call`].
+    pub fn call(&self, msg: T::Input) -> AgentCall<T::Output> {
+        let mut bridge = self.inner.borrow_mut();
+        bridge.call(msg)
+    }
+
+    /// Send a message to an agent and receive the stream of responses to it.
+    ///
+    /// See [`Bridge::stream`].
+    pub fn stream(&self, msg: T::Input) -> AgentStream<T::Output> {
+        let mut bridge = self.inner.borrow_mut();
+        bridge.stream(msg)
+    }
 }
 
 /// A hook to bridge to an Agent.
@@ -55,6 +71,52 @@ where
     UseBridgeHandle { inner: bridge }
 }
 
+/// A hook to call an Agent and await its responses.
+///
+/// This hook bridges the agent once over the entire component lifecycle, like [`use_bridge`], but
+/// ignores all outputs, that are not responses to [`UseBridgeHandle::call`].
+///
+/// # Example
+/// ```
+/// # use yew::prelude::*;
+/// # use yew_agent::{use_agent_call, Agent, AgentLink, HandlerId, Job};
+/// # use std::time::Duration;
+/// # struct Fib;
+/// # impl Agent for Fib {
+/// #     type Reach = Job<Self>;
+/// #     type Message = ();
+/// #     type Input = u32;
+/// #     type Output = u32;
+/// #     fn create(_: AgentLink<Self>) -> Self { Fib }
+/// #     fn update(&mut self, _: ()) {}
+/// #     fn handle_input(&mut self, _: u32, _: HandlerId) {}
+/// # }
+/// #[function_component(Calculator)]
+/// fn calculator() -> Html {
+///     let fib = use_agent_call::<Fib>();
+///     let result = use_state(|| None);
+///     let onclick = {
+///         let result = result.clone();
+///         Callback::from(move |_| {
+///             let call = fib.call(30).timeout(Duration::from_secs(5));
+///             let result = result.clone();
+///             wasm_bindgen_futures::spawn_local(async move {
+///                 result.set(call.await.ok());
+///             });
+///         })
+///     };
+///     html! {
+///         <button {onclick}>{ format!("{:?}", *result) }</button>
+///     }
+/// }
+/// ```
+pub fn use_agent_call<T>() -> UseBridgeHandle<T>
+where
+    T: Bridged,
+{
+    use_bridge(|_| {})
+}
+
 impl<T: Agent> Clone for UseBridgeHandle<T> {
     fn clone(&self) -> Self {
         Self {
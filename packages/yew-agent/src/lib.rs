@@ -1,22 +1,38 @@
 //! This module contains types to support multi-threading and state management.
 
+mod call;
+mod cancel;
 mod hooks;
 mod link;
 mod local;
 mod pool;
+mod stream;
+pub mod testing;
 pub mod utils;
 mod worker;
 
-pub use hooks::{use_bridge, UseBridgeHandle};
+pub use call::{AgentCall, AgentStream, CallError, TimeoutCall};
+pub(crate) use call::{Control, Controller, PendingCalls};
+pub(crate) use cancel::*;
+pub use cancel::{CancellationToken, Cancelled};
+pub use hooks::{use_agent_call, use_bridge, UseBridgeHandle};
 pub use link::AgentLink;
 pub(crate) use link::*;
 pub use local::{Context, Job};
 pub(crate) use pool::*;
 pub use pool::{Dispatched, Dispatcher};
-pub use worker::{Private, Public, Threaded};
+pub(crate) use stream::*;
+pub use stream::{OutputStream, StreamReady};
+#[cfg(feature = "json")]
+pub use worker::Json;
+pub use worker::{
+    Bincode, Broadcast, Codec, CodecError, Pool, PoolWithCodec, Private, Public, RestartPolicy,
+    SharedWorker, Threaded, Transfer, Transferable, WorkerStatus,
+};
 
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::ops::{Deref, DerefMut};
 use yew::callback::Callback;
 
@@ -64,27 +80,88 @@ pub trait Agent: Sized + 'static {
         false
     }
 
-    /// Signifies if resource is a module.
-    /// This has pending browser support.
+    /// Signifies if resource is an ES module.
+    ///
+    /// A module is loaded in a module worker, which imports its default export and calls it with
+    /// the URL of the wasm file. Build it with wasm-bindgen's `--target web`, for example with
+    /// `wasm-pack build --target web`, so that the default export initializes the wasm module.
+    /// The page loads the same module with a script of `type="module"`:
+    ///
+    /// ```html
+    /// <script type="module">import init from "./wasm.js"; init();</script>
+    /// ```
+    ///
+    /// The default implementation returns `false`, which loads [`Self::name_of_resource`] as a
+    /// classic script built with `--target no-modules`.
     fn is_module() -> bool {
         false
     }
+
+    /// Policy for restarting the worker of the agent, after it crashed.
+    ///
+    /// The default implementation returns [`RestartPolicy::Never`].
+    fn restart_policy() -> RestartPolicy {
+        RestartPolicy::Never
+    }
+
+    /// Number of outputs, that the agent sends ahead of the ones consumed from a stream.
+    ///
+    /// A larger window lets the agent run further ahead of a slow consumer, at the cost of more
+    /// outputs waiting to be consumed. See [`OutputStream`].
+    ///
+    /// The default implementation returns 16. A window of zero is treated as a window of one.
+    fn stream_window() -> usize {
+        16
+    }
 }
 
 /// Id of responses handler.
-#[derive(Debug, Serialize, Deserialize, Eq, PartialEq, Hash, Clone, Copy)]
-pub struct HandlerId(usize, bool);
+///
+/// The ids, that an agent receives with the inputs of [`Bridge::call`] and [`Bridge::stream`], are
+/// tagged with the call or stream, so that the responses can be routed to it.
+///
+/// Equality and hashing ignore the tag, so ids of the same handler are equal, even if they are
+/// tagged with different calls. A map keyed by handler ids keeps a single entry per handler, with
+/// the tag of the id it was inserted with.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct HandlerId(usize, bool, Option<u64>);
 
 impl HandlerId {
     fn new(id: usize, respondable: bool) -> Self {
-        HandlerId(id, respondable)
+        HandlerId(id, respondable, None)
     }
     fn raw_id(self) -> usize {
         self.0
     }
+    fn with_raw_id(self, id: usize) -> Self {
+        HandlerId(id, self.1, self.2)
+    }
+    fn with_request(self, request: u64) -> Self {
+        HandlerId(self.0, self.1, Some(request))
+    }
+    fn request(self) -> Option<u64> {
+        self.2
+    }
     /// Indicates if a handler id corresponds to callback in the Agent runtime.
+    ///
+    /// Ids of calls and streams are always respondable, even if they were made through a dispatcher.
     pub fn is_respondable(self) -> bool {
-        self.1
+        self.1 || self.2.is_some()
+    }
+}
+
+impl PartialEq for HandlerId {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0 && self.1 == other.1
+    }
+}
+
+impl Eq for HandlerId {}
+
+impl Hash for HandlerId {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+        self.1.hash(state);
     }
 }
 
@@ -97,18 +174,64 @@ pub trait Discoverer {
     fn spawn_or_join(
         _callback: Option<Callback<<Self::Agent as Agent>::Output>>,
     ) -> Box<dyn Bridge<Self::Agent>>;
+
+    /// Spawns an agent and returns `Bridge` implementation with the given options.
+    ///
+    /// Agents in the same thread have no worker and never encode their messages, so the default
+    /// implementation ignores the options.
+    fn spawn_or_join_with_options(
+        callback: Option<Callback<<Self::Agent as Agent>::Output>>,
+        _options: BridgeOptions,
+    ) -> Box<dyn Bridge<Self::Agent>> {
+        Self::spawn_or_join(callback)
+    }
 }
 
 /// Bridge to a specific kind of worker.
 pub trait Bridge<AGN: Agent> {
     /// Send a message to an agent.
     fn send(&mut self, msg: AGN::Input);
+
+    /// Send a message to an agent and wait for the response to it.
+    ///
+    /// The returned future resolves to the first output, that the agent sends to the
+    /// [`HandlerId`] it received with the message. All other outputs are passed to the callback
+    /// of the bridge as usual.
+    ///
+    /// The default implementation does not send the message and fails with
+    /// [`CallError::Unsupported`].
+    fn call(&mut self, _msg: AGN::Input) -> AgentCall<AGN::Output> {
+        AgentCall::failed(CallError::Unsupported)
+    }
+
+    /// Send a message to an agent and receive the stream of responses to it.
+    ///
+    /// The agent sends the responses through the [`OutputStream`] returned by
+    /// [`AgentLink::respond_stream`] for the [`HandlerId`] it received with the message.
+    ///
+    /// The default implementation does not send the message and fails with
+    /// [`CallError::Unsupported`].
+    fn stream(&mut self, _msg: AGN::Input) -> AgentStream<AGN::Output> {
+        AgentStream::failed(CallError::Unsupported)
+    }
+
+    /// Cancel the calls and streams of the bridge, that are still waiting for responses, when the
+    /// bridge is dropped.
+    ///
+    /// See [`CancellationToken`]. The default implementation does nothing.
+    fn cancel_on_drop(&mut self) {}
 }
 
 /// This trait allows registering or getting the address of a worker.
 pub trait Bridged: Agent + Sized + 'static {
     /// Creates a messaging bridge between a worker and the component.
     fn bridge(callback: Callback<Self::Output>) -> Box<dyn Bridge<Self>>;
+
+    /// Creates a messaging bridge between a worker and the component with the given options.
+    fn bridge_with_options(
+        callback: Callback<Self::Output>,
+        options: BridgeOptions,
+    ) -> Box<dyn Bridge<Self>>;
 }
 
 impl<T> Bridged for T
@@ -119,4 +242,61 @@ where
     fn bridge(callback: Callback<Self::Output>) -> Box<dyn Bridge<Self>> {
         Self::Reach::spawn_or_join(Some(callback))
     }
+
+    fn bridge_with_options(
+        callback: Callback<Self::Output>,
+        options: BridgeOptions,
+    ) -> Box<dyn Bridge<Self>> {
+        Self::Reach::spawn_or_join_with_options(Some(callback), options)
+    }
+}
+
+/// Options of a bridge created with [`Bridged::bridge_with_options`].
+///
+/// The options concern agents running in workers, and are ignored by agents in the same thread.
+#[derive(Debug, Clone, Default)]
+pub struct BridgeOptions {
+    on_error: Option<Callback<CodecError>>,
+    on_status: Option<Callback<WorkerStatus>>,
+}
+
+impl BridgeOptions {
+    /// Creates the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reports messages, which can't be encoded or decoded, to `on_error` instead of the console.
+    pub fn on_error(mut self, on_error: Callback<CodecError>) -> Self {
+        self.on_error = Some(on_error);
+        self
+    }
+
+    /// Reports crashes, restarts and errors of the workers of the agent to `on_status`.
+    pub fn on_status(mut self, on_status: Callback<WorkerStatus>) -> Self {
+        self.on_status = Some(on_status);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn handler_ids_ignore_requests() {
+        let id = HandlerId::new(1, true);
+        let call = id.with_request(0);
+        let stream = id.with_request(1);
+        assert_eq!(id, call);
+        assert_eq!(call, stream);
+        assert_ne!(id, HandlerId::new(2, true));
+        assert_ne!(id, HandlerId::new(1, false));
+
+        let ids: HashSet<_> = vec![id, call, stream].into_iter().collect();
+        assert_eq!(ids.len(), 1);
+        assert!(ids.contains(&stream));
+        assert_eq!(ids.get(&call).and_then(|id| id.request()), None);
+    }
 }
@@ -1,5 +1,6 @@
 use super::*;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fmt;
 use std::future::Future;
 use std::rc::Rc;
@@ -12,6 +13,10 @@ use yew::scheduler::{self, Runnable, Shared};
 pub(crate) trait Responder<AGN: Agent> {
     /// Implementation for communication channel from Worker to Consumers
     fn respond(&self, id: HandlerId, output: AGN::Output);
+
+    /// Ends the stream of outputs, that `id` is tagged with, or tells the bridge, that the agent
+    /// does not respond to the cancelled call, that `id` is tagged with
+    fn end_stream(&self, id: HandlerId);
 }
 
 /// Link to agent's scope for creating callbacks.
@@ -26,17 +31,46 @@ impl<AGN: Agent> AgentLink<AGN> {
     where
         T: Responder<AGN> + 'static,
     {
+        let responder: Rc<dyn Responder<AGN>> = Rc::new(responder);
+        *scope.responder.borrow_mut() = Some(responder.clone());
         AgentLink {
             scope: scope.clone(),
-            responder: Rc::new(responder),
+            responder,
         }
     }
 
     /// Send response to an agent.
     pub fn respond(&self, id: HandlerId, output: AGN::Output) {
+        // A response finishes a call, while a stream goes on until it ends. Calls, that were
+        // released after they were cancelled, are not responded to anymore.
+        if !self.scope.has_stream(id) && !finish_request(&self.scope.cancellations, id) {
+            return;
+        }
         self.responder.respond(id, output);
     }
 
+    /// Start a stream of responses to an input, that was sent with [`Bridge::stream`].
+    ///
+    /// See [`OutputStream`].
+    pub fn respond_stream(&self, id: HandlerId) -> OutputStream<AGN> {
+        OutputStream::new(
+            id,
+            self.responder.clone(),
+            self.scope.streams.clone(),
+            self.scope.cancellations.clone(),
+        )
+    }
+
+    /// Get the token, that tells whether the bridge cancelled the call or stream, that `id` is
+    /// tagged with.
+    ///
+    /// See [`CancellationToken`].
+    pub fn cancellation(&self, id: HandlerId) -> CancellationToken {
+        let scope = self.scope.clone();
+        let release = Box::new(move || scope.release(id));
+        CancellationToken::new(&self.scope.cancellations, id, release)
+    }
+
     /// Send a message to the agent
     pub fn send_message<T>(&self, msg: T)
     where
@@ -149,6 +183,10 @@ impl<AGN: Agent> Clone for AgentLink<AGN> {
 /// This struct holds a reference to a component and to a global scheduler.
 pub(crate) struct AgentScope<AGN: Agent> {
     state: Shared<AgentState<AGN>>,
+    streams: SharedOutputStreams<AGN>,
+    pub(crate) cancellations: SharedCancellations,
+    /// The responder of the link of the agent, once it is connected
+    responder: Shared<Option<Rc<dyn Responder<AGN>>>>,
 }
 
 impl<AGN: Agent> fmt::Debug for AgentScope<AGN> {
@@ -161,6 +199,9 @@ impl<AGN: Agent> Clone for AgentScope<AGN> {
     fn clone(&self) -> Self {
         AgentScope {
             state: self.state.clone(),
+            streams: self.streams.clone(),
+            cancellations: self.cancellations.clone(),
+            responder: self.responder.clone(),
         }
     }
 }
@@ -169,16 +210,79 @@ impl<AGN: Agent> AgentScope<AGN> {
     /// Create agent scope
     pub fn new() -> Self {
         let state = Rc::new(RefCell::new(AgentState::new()));
-        AgentScope { state }
+        let streams = Rc::new(RefCell::new(HashMap::new()));
+        let cancellations = Rc::new(RefCell::new(HashMap::new()));
+        AgentScope {
+            state,
+            streams,
+            cancellations,
+            responder: Rc::new(RefCell::new(None)),
+        }
     }
 
     /// Schedule message for sending to agent
     pub fn send(&self, event: AgentLifecycleEvent<AGN>) {
+        match event {
+            AgentLifecycleEvent::Input(_, id) => {
+                start_request(&self.cancellations, id);
+            }
+            AgentLifecycleEvent::Disconnected(id) => {
+                remove_streams(&self.streams, id);
+                remove_cancellations(&self.cancellations, id);
+            }
+            _ => {}
+        }
         scheduler::push(Box::new(AgentRunnable {
-            state: self.state.clone(),
+            scope: self.clone(),
             event,
         }));
     }
+
+    /// Acknowledges, that the bridge consumed `count` outputs of the stream, that `id` is tagged
+    /// with
+    pub fn ack_stream(&self, id: HandlerId, count: usize) {
+        acknowledge_stream(&self.streams, &self.cancellations, id, count);
+    }
+
+    /// Indicates if the agent streams its outputs to `id`
+    fn has_stream(&self, id: HandlerId) -> bool {
+        match id.request() {
+            Some(request) => self.streams.borrow().contains_key(&(id.raw_id(), request)),
+            None => false,
+        }
+    }
+
+    /// Cancels the call or stream, that `id` is tagged with
+    pub fn cancel(&self, id: HandlerId) {
+        cancel_request(&self.cancellations, id);
+        self.release(id);
+    }
+
+    /// Tells the bridge, that the agent does not respond to the call, that `id` is tagged with,
+    /// once it was cancelled and the agent dropped all of its tokens
+    fn release(&self, id: HandlerId) {
+        if self.has_stream(id) || !release_request(&self.cancellations, id) {
+            return;
+        }
+        let responder = self.responder.borrow().clone();
+        if let Some(responder) = responder {
+            responder.end_stream(id);
+        }
+    }
+
+    /// Handles a control message of a bridge about the call or stream, that `id` is tagged with
+    pub fn control(&self, id: HandlerId, control: Control) {
+        match control {
+            Control::Acknowledge(count) => self.ack_stream(id, count),
+            Control::Cancel => self.cancel(id),
+        }
+    }
+
+    /// Runs a function with the agent, which must have been created
+    pub fn with_agent<R>(&self, f: impl FnOnce(&AGN) -> R) -> R {
+        let state = self.state.borrow();
+        f(state.agent.as_ref().expect("agent was not created"))
+    }
 }
 
 impl<AGN: Agent> Default for AgentScope<AGN> {
@@ -222,7 +326,7 @@ pub(crate) enum AgentLifecycleEvent<AGN: Agent> {
 }
 
 struct AgentRunnable<AGN: Agent> {
-    state: Shared<AgentState<AGN>>,
+    scope: AgentScope<AGN>,
     event: AgentLifecycleEvent<AGN>,
 }
 
@@ -231,7 +335,7 @@ where
     AGN: Agent,
 {
     fn run(self: Box<Self>) {
-        let mut state = self.state.borrow_mut();
+        let mut state = self.scope.state.borrow_mut();
         if state.destroyed {
             return;
         }
@@ -259,6 +363,9 @@ where
                     .as_mut()
                     .expect("agent was not created to process inputs")
                     .handle_input(inp, id);
+                drop(state);
+                handle_request(&self.scope.cancellations, id);
+                self.scope.release(id);
             }
             AgentLifecycleEvent::Disconnected(id) => {
                 state
@@ -5,7 +5,6 @@ use std::cell::RefCell;
 use std::marker::PhantomData;
 use std::rc::Rc;
 use yew::callback::Callback;
-use yew::scheduler::Shared;
 
 thread_local! {
     static LOCAL_AGENTS_POOL: RefCell<AnyMap> = RefCell::new(AnyMap::new());
@@ -52,13 +51,17 @@ where
 }
 
 struct SlabResponder<AGN: Agent> {
-    slab: Shared<Slab<Option<Callback<AGN::Output>>>>,
+    slab: SharedOutputSlab<AGN>,
 }
 
 impl<AGN: Agent> Responder<AGN> for SlabResponder<AGN> {
     fn respond(&self, id: HandlerId, output: AGN::Output) {
         locate_callback_and_respond::<AGN>(&self.slab, id, output);
     }
+
+    fn end_stream(&self, id: HandlerId) {
+        locate_stream_and_end::<AGN>(&self.slab, id);
+    }
 }
 
 impl<AGN: Agent> Dispatchable for Context<AGN> {}
@@ -66,6 +69,15 @@ impl<AGN: Agent> Dispatchable for Context<AGN> {}
 struct ContextBridge<AGN: Agent> {
     scope: AgentScope<AGN>,
     id: HandlerId,
+    calls: PendingCalls<AGN::Output>,
+}
+
+impl<AGN: Agent> ContextBridge<AGN> {
+    /// Returns the controller of a call or stream to the agent
+    fn controller(&self) -> Controller {
+        let scope = self.scope.clone();
+        Rc::new(move |id, control| scope.control(id, control))
+    }
 }
 
 impl<AGN: Agent> Bridge<AGN> for ContextBridge<AGN> {
@@ -73,10 +85,32 @@ impl<AGN: Agent> Bridge<AGN> for ContextBridge<AGN> {
         let upd = AgentLifecycleEvent::Input(msg, self.id);
         self.scope.send(upd);
     }
+
+    fn call(&mut self, msg: AGN::Input) -> AgentCall<AGN::Output> {
+        let (id, call) = self.calls.register(self.id, self.controller());
+        let upd = AgentLifecycleEvent::Input(msg, id);
+        self.scope.send(upd);
+        call
+    }
+
+    fn stream(&mut self, msg: AGN::Input) -> AgentStream<AGN::Output> {
+        let (id, stream) =
+            self.calls
+                .register_stream(self.id, self.controller(), stream_window::<AGN>());
+        let upd = AgentLifecycleEvent::Input(msg, id);
+        self.scope.send(upd);
+        stream
+    }
+
+    fn cancel_on_drop(&mut self) {
+        self.calls.cancel_on_drop();
+    }
 }
 
 impl<AGN: Agent> Drop for ContextBridge<AGN> {
     fn drop(&mut self) {
+        self.calls.close();
+
         let terminate_worker = LOCAL_AGENTS_POOL.with(|pool| {
             let mut pool = pool.borrow_mut();
             let terminate_worker = {
@@ -124,12 +158,17 @@ impl<AGN: Agent> LocalAgent<AGN> {
 
     fn create_bridge(&mut self, callback: Option<Callback<AGN::Output>>) -> ContextBridge<AGN> {
         let respondable = callback.is_some();
+        let calls = PendingCalls::new();
         let mut slab = self.slab.borrow_mut();
-        let id: usize = slab.insert(callback);
+        let id: usize = slab.insert(BridgeHandler {
+            callback,
+            calls: calls.clone(),
+        });
         let id = HandlerId::new(id, respondable);
         ContextBridge {
             scope: self.scope.clone(),
             id,
+            calls,
         }
     }
 
@@ -1,8 +1,9 @@
 use super::*;
 use std::marker::PhantomData;
+use std::rc::Rc;
 use yew::callback::Callback;
 
-const SINGLETON_ID: HandlerId = HandlerId(0, true);
+const SINGLETON_ID: HandlerId = HandlerId(0, true, None);
 
 /// Create an instance in the current thread.
 #[allow(missing_debug_implementations)]
@@ -19,19 +20,32 @@ where
     fn spawn_or_join(callback: Option<Callback<AGN::Output>>) -> Box<dyn Bridge<AGN>> {
         let callback = callback.expect("Callback required for Job");
         let scope = AgentScope::<AGN>::new();
-        let responder = CallbackResponder { callback };
+        let calls = PendingCalls::new();
+        let responder = CallbackResponder {
+            callback,
+            calls: calls.clone(),
+        };
         let agent_link = AgentLink::connect(&scope, responder);
         let upd = AgentLifecycleEvent::Create(agent_link);
         scope.send(upd);
         let upd = AgentLifecycleEvent::Connected(SINGLETON_ID);
         scope.send(upd);
-        let bridge = JobBridge { scope };
+        let bridge = JobBridge { scope, calls };
         Box::new(bridge)
     }
 }
 
 struct JobBridge<AGN: Agent> {
     scope: AgentScope<AGN>,
+    calls: PendingCalls<AGN::Output>,
+}
+
+impl<AGN: Agent> JobBridge<AGN> {
+    /// Returns the controller of a call or stream to the agent
+    fn controller(&self) -> Controller {
+        let scope = self.scope.clone();
+        Rc::new(move |id, control| scope.control(id, control))
+    }
 }
 
 impl<AGN: Agent> Bridge<AGN> for JobBridge<AGN> {
@@ -39,10 +53,32 @@ impl<AGN: Agent> Bridge<AGN> for JobBridge<AGN> {
         let upd = AgentLifecycleEvent::Input(msg, SINGLETON_ID);
         self.scope.send(upd);
     }
+
+    fn call(&mut self, msg: AGN::Input) -> AgentCall<AGN::Output> {
+        let (id, call) = self.calls.register(SINGLETON_ID, self.controller());
+        let upd = AgentLifecycleEvent::Input(msg, id);
+        self.scope.send(upd);
+        call
+    }
+
+    fn stream(&mut self, msg: AGN::Input) -> AgentStream<AGN::Output> {
+        let (id, stream) =
+            self.calls
+                .register_stream(SINGLETON_ID, self.controller(), stream_window::<AGN>());
+        let upd = AgentLifecycleEvent::Input(msg, id);
+        self.scope.send(upd);
+        stream
+    }
+
+    fn cancel_on_drop(&mut self) {
+        self.calls.cancel_on_drop();
+    }
 }
 
 impl<AGN: Agent> Drop for JobBridge<AGN> {
     fn drop(&mut self) {
+        self.calls.close();
+
         let upd = AgentLifecycleEvent::Disconnected(SINGLETON_ID);
         self.scope.send(upd);
         let upd = AgentLifecycleEvent::Destroy;
@@ -52,11 +88,18 @@ impl<AGN: Agent> Drop for JobBridge<AGN> {
 
 struct CallbackResponder<AGN: Agent> {
     callback: Callback<AGN::Output>,
+    calls: PendingCalls<AGN::Output>,
 }
 
 impl<AGN: Agent> Responder<AGN> for CallbackResponder<AGN> {
     fn respond(&self, id: HandlerId, output: AGN::Output) {
         assert_eq!(id.raw_id(), SINGLETON_ID.raw_id());
-        self.callback.emit(output);
+        if let Some(output) = self.calls.resolve(id, output) {
+            self.callback.emit(output);
+        }
+    }
+
+    fn end_stream(&self, id: HandlerId) {
+        self.calls.end(id);
     }
 }
@@ -5,20 +5,27 @@ use yew::scheduler::Shared;
 
 pub(crate) type Last = bool;
 
-/// Type alias to a sharable Slab that owns optional callbacks that emit messages of the type of the specified Agent.
-pub(crate) type SharedOutputSlab<AGN> = Shared<Slab<Option<Callback<<AGN as Agent>::Output>>>>;
+/// The optional callback of a bridge and its calls, that are waiting for a response.
+pub(crate) struct BridgeHandler<OUT> {
+    pub callback: Option<Callback<OUT>>,
+    pub calls: PendingCalls<OUT>,
+}
+
+/// Type alias to a sharable Slab that owns the handlers of the bridges to the specified Agent.
+pub(crate) type SharedOutputSlab<AGN> = Shared<Slab<BridgeHandler<<AGN as Agent>::Output>>>;
 
 /// The slab contains the callback, the id is used to look up the callback,
-/// and the output is the message that will be sent via the callback.
+/// and the output is the message that will be sent via the callback, unless a call of the bridge
+/// is waiting for it.
 pub(crate) fn locate_callback_and_respond<AGN: Agent>(
     slab: &SharedOutputSlab<AGN>,
     id: HandlerId,
     output: AGN::Output,
 ) {
-    let callback = {
+    let (callback, calls) = {
         let slab = slab.borrow();
-        match slab.get(id.raw_id()).cloned() {
-            Some(callback) => callback,
+        match slab.get(id.raw_id()) {
+            Some(handler) => (handler.callback.clone(), handler.calls.clone()),
             None => {
                 console::warn!(format!(
                     "Id of handler does not exist in the slab: {}.",
@@ -28,12 +35,42 @@ pub(crate) fn locate_callback_and_respond<AGN: Agent>(
             }
         }
     };
+    let output = match calls.resolve(id, output) {
+        Some(output) => output,
+        None => return,
+    };
     match callback {
         Some(callback) => callback.emit(output),
         None => console::warn!(format!("The Id of the handler: {}, while present in the slab, is not associated with a callback.", id.raw_id())),
     }
 }
 
+/// Ends the stream of a handler in the slab, that the id is tagged with
+pub(crate) fn locate_stream_and_end<AGN: Agent>(slab: &SharedOutputSlab<AGN>, id: HandlerId) {
+    let calls = slab
+        .borrow()
+        .get(id.raw_id())
+        .map(|handler| handler.calls.clone());
+    if let Some(calls) = calls {
+        calls.end(id);
+    }
+}
+
+/// Fails the call or stream of a handler in the slab, that the id is tagged with
+pub(crate) fn locate_call_and_fail<AGN: Agent>(
+    slab: &SharedOutputSlab<AGN>,
+    id: HandlerId,
+    error: CallError,
+) {
+    let calls = slab
+        .borrow()
+        .get(id.raw_id())
+        .map(|handler| handler.calls.clone());
+    if let Some(calls) = calls {
+        calls.fail(id, error);
+    }
+}
+
 /// A newtype around a bridge to indicate that it is distinct from a normal bridge
 pub struct Dispatcher<T>(pub(crate) Box<dyn Bridge<T>>);
 
@@ -0,0 +1,360 @@
+use super::*;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll, Waker};
+use yew::scheduler::Shared;
+
+/// Number of outputs, that an agent sends ahead of the ones consumed from a stream
+pub(crate) fn stream_window<AGN: Agent>() -> usize {
+    AGN::stream_window().max(1)
+}
+
+/// Streams of an agent by the raw id of their handler and their request
+pub(crate) type SharedOutputStreams<AGN> = Shared<HashMap<(usize, u64), Shared<StreamSender<AGN>>>>;
+
+/// The outputs of a stream, that wait for the consumer to acknowledge the ones sent before
+pub(crate) struct StreamSender<AGN: Agent> {
+    responder: Rc<dyn Responder<AGN>>,
+    /// Number of outputs, that can be sent without waiting for an acknowledgement
+    credit: usize,
+    outputs: VecDeque<AGN::Output>,
+    /// Whether the agent dropped its [`OutputStream`]
+    closed: bool,
+    waker: Option<Waker>,
+}
+
+impl<AGN: Agent> StreamSender<AGN> {
+    /// Takes the buffered outputs, that can be sent now
+    fn take_sendable(&mut self) -> Vec<AGN::Output> {
+        let count = self.credit.min(self.outputs.len());
+        self.credit -= count;
+        self.outputs.drain(..count).collect()
+    }
+
+    fn is_ready(&self) -> bool {
+        self.credit > 0 && self.outputs.is_empty()
+    }
+}
+
+/// Adds `count` acknowledged outputs to the credit of the stream, that `id` is tagged with, and
+/// sends the outputs buffered by the agent.
+pub(crate) fn acknowledge_stream<AGN: Agent>(
+    streams: &SharedOutputStreams<AGN>,
+    cancellations: &SharedCancellations,
+    id: HandlerId,
+    count: usize,
+) {
+    let key = match id.request() {
+        Some(request) => (id.raw_id(), request),
+        None => return,
+    };
+    let sender = match streams.borrow().get(&key) {
+        Some(sender) => sender.clone(),
+        None => return,
+    };
+    let (responder, outputs, ended) = {
+        let mut sender = sender.borrow_mut();
+        sender.credit = sender.credit.saturating_add(count);
+        let outputs = sender.take_sendable();
+        if let Some(waker) = sender.waker.take() {
+            waker.wake();
+        }
+        let ended = sender.closed && sender.outputs.is_empty();
+        (sender.responder.clone(), outputs, ended)
+    };
+    for output in outputs {
+        responder.respond(id, output);
+    }
+    if ended {
+        streams.borrow_mut().remove(&key);
+        finish_request(cancellations, id);
+        responder.end_stream(id);
+    }
+}
+
+/// Removes the streams of a handler, that disconnected
+pub(crate) fn remove_streams<AGN: Agent>(streams: &SharedOutputStreams<AGN>, id: HandlerId) {
+    streams
+        .borrow_mut()
+        .retain(|(raw_id, _), _| *raw_id != id.raw_id());
+}
+
+/// The sending half of a stream of outputs in response to one input.
+///
+/// Created by [`AgentLink::respond_stream`]. The outputs are sent to the [`AgentStream`] of the
+/// bridge, that sent the input with [`Bridge::stream`]. At most [`Agent::stream_window`] outputs
+/// are sent ahead of the ones, which the bridge has consumed. Further outputs are buffered, until
+/// the bridge acknowledges that it consumed the ones sent before, so an agent producing outputs
+/// faster than they are consumed should wait for [`OutputStream::ready`] before sending the next
+/// one.
+///
+/// The stream ends, when the `OutputStream` is dropped and its buffered outputs have been sent.
+///
+/// If the input was not sent with [`Bridge::stream`], the outputs are sent right away and passed
+/// to the callback of the bridge.
+///
+/// # Example
+///
+/// ```
+/// use yew_agent::testing::TestAgent;
+/// use yew_agent::{Agent, AgentLink, Context, HandlerId};
+///
+/// struct Range {
+///     link: AgentLink<Self>,
+/// }
+///
+/// impl Agent for Range {
+///     type Reach = Context<Self>;
+///     type Message = ();
+///     type Input = u32;
+///     type Output = u32;
+///
+///     fn create(link: AgentLink<Self>) -> Self {
+///         Self { link }
+///     }
+///
+///     fn update(&mut self, _msg: Self::Message) {}
+///
+///     fn handle_input(&mut self, msg: Self::Input, id: HandlerId) {
+///         let stream = self.link.respond_stream(id);
+///         for i in 0..msg {
+///             stream.send(i);
+///         }
+///     }
+/// }
+///
+/// let mut agent = TestAgent::<Range>::new();
+/// let bridge = agent.connect();
+/// let id = agent.send_stream(bridge, 20);
+/// assert_eq!(agent.take_responses(bridge), (0..16).collect::<Vec<_>>());
+/// assert!(!agent.is_stream_ended(id));
+/// agent.ack_stream(id, 16);
+/// assert_eq!(agent.take_responses(bridge), (16..20).collect::<Vec<_>>());
+/// assert!(agent.is_stream_ended(id));
+/// ```
+pub struct OutputStream<AGN: Agent> {
+    id: HandlerId,
+    sender: Shared<StreamSender<AGN>>,
+    streams: SharedOutputStreams<AGN>,
+    cancellations: SharedCancellations,
+}
+
+impl<AGN: Agent> OutputStream<AGN> {
+    pub(crate) fn new(
+        id: HandlerId,
+        responder: Rc<dyn Responder<AGN>>,
+        streams: SharedOutputStreams<AGN>,
+        cancellations: SharedCancellations,
+    ) -> Self {
+        // Outputs to ids, that are not tagged with a stream, are never acknowledged
+        let credit = match id.request() {
+            Some(_) => stream_window::<AGN>(),
+            None => usize::MAX,
+        };
+        let sender = Rc::new(RefCell::new(StreamSender {
+            responder,
+            credit,
+            outputs: VecDeque::new(),
+            closed: false,
+            waker: None,
+        }));
+        if let Some(request) = id.request() {
+            streams
+                .borrow_mut()
+                .insert((id.raw_id(), request), sender.clone());
+        }
+        OutputStream {
+            id,
+            sender,
+            streams,
+            cancellations,
+        }
+    }
+
+    /// Send an output of the stream, or buffer it, if the bridge has not consumed enough of the
+    /// outputs sent before.
+    pub fn send(&self, output: AGN::Output) {
+        let (responder, outputs) = {
+            let mut sender = self.sender.borrow_mut();
+            sender.outputs.push_back(output);
+            (sender.responder.clone(), sender.take_sendable())
+        };
+        for output in outputs {
+            responder.respond(self.id, output);
+        }
+    }
+
+    /// Indicates if the next output would be sent right away instead of being buffered.
+    pub fn is_ready(&self) -> bool {
+        self.sender.borrow().is_ready()
+    }
+
+    /// Returns a future, that resolves when the next output would be sent right away.
+    pub fn ready(&self) -> StreamReady<AGN> {
+        StreamReady {
+            sender: self.sender.clone(),
+        }
+    }
+}
+
+impl<AGN: Agent> Drop for OutputStream<AGN> {
+    fn drop(&mut self) {
+        let (responder, ended) = {
+            let mut sender = self.sender.borrow_mut();
+            sender.closed = true;
+            (sender.responder.clone(), sender.outputs.is_empty())
+        };
+        // Otherwise the stream ends, once the bridge acknowledged the buffered outputs
+        if ended {
+            if let Some(request) = self.id.request() {
+                self.streams
+                    .borrow_mut()
+                    .remove(&(self.id.raw_id(), request));
+            }
+            finish_request(&self.cancellations, self.id);
+            responder.end_stream(self.id);
+        }
+    }
+}
+
+impl<AGN: Agent> fmt::Debug for OutputStream<AGN> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("OutputStream<_>")
+    }
+}
+
+/// A future resolving, when the next output of an [`OutputStream`] would be sent right away.
+///
+/// Created by [`OutputStream::ready`].
+pub struct StreamReady<AGN: Agent> {
+    sender: Shared<StreamSender<AGN>>,
+}
+
+impl<AGN: Agent> Future for StreamReady<AGN> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut sender = self.sender.borrow_mut();
+        if sender.is_ready() {
+            Poll::Ready(())
+        } else {
+            sender.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+impl<AGN: Agent> fmt::Debug for StreamReady<AGN> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("StreamReady<_>")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestAgent;
+
+    const WINDOW: usize = 4;
+
+    /// Streams as many outputs as it is asked for, and drops its streams on a message
+    struct Streamer {
+        link: AgentLink<Self>,
+        streams: Vec<OutputStream<Self>>,
+    }
+
+    impl Agent for Streamer {
+        type Reach = crate::Context<Self>;
+        type Message = ();
+        type Input = usize;
+        type Output = usize;
+
+        fn create(link: AgentLink<Self>) -> Self {
+            Self {
+                link,
+                streams: Vec::new(),
+            }
+        }
+
+        fn update(&mut self, _msg: Self::Message) {
+            self.streams.clear();
+        }
+
+        fn handle_input(&mut self, msg: Self::Input, id: HandlerId) {
+            let stream = self.link.respond_stream(id);
+            for i in 0..msg {
+                stream.send(i);
+            }
+            self.streams.push(stream);
+        }
+
+        fn stream_window() -> usize {
+            WINDOW
+        }
+    }
+
+    fn is_ready(agent: &TestAgent<Streamer>) -> bool {
+        agent.with_agent(|streamer| streamer.streams[0].is_ready())
+    }
+
+    #[test]
+    fn holds_back_outputs_until_acknowledged() {
+        let mut agent = TestAgent::<Streamer>::new();
+        let bridge = agent.connect();
+        let id = agent.send_stream(bridge, WINDOW + 4);
+        let window: Vec<_> = (0..WINDOW).collect();
+        assert_eq!(agent.take_responses(bridge), window);
+        assert!(!is_ready(&agent));
+
+        agent.ack_stream(id, 2);
+        assert_eq!(agent.take_responses(bridge), vec![WINDOW, WINDOW + 1]);
+        assert!(!is_ready(&agent));
+        agent.ack_stream(id, 3);
+        assert_eq!(agent.take_responses(bridge), vec![WINDOW + 2, WINDOW + 3]);
+        assert!(is_ready(&agent));
+
+        assert!(!agent.is_stream_ended(id));
+        agent.send_message(());
+        assert!(agent.is_stream_ended(id));
+    }
+
+    #[test]
+    fn ends_streams_once_buffered_outputs_are_sent() {
+        let mut agent = TestAgent::<Streamer>::new();
+        let bridge = agent.connect();
+        let id = agent.send_stream(bridge, WINDOW + 2);
+        agent.take_responses(bridge);
+        agent.send_message(());
+        assert!(!agent.is_stream_ended(id));
+
+        // Dropped streams on the bridge acknowledge all outputs
+        agent.ack_stream(id, usize::MAX);
+        assert_eq!(agent.take_responses(bridge), vec![WINDOW, WINDOW + 1]);
+        assert!(agent.is_stream_ended(id));
+    }
+
+    #[test]
+    fn sends_outputs_to_untagged_ids_right_away() {
+        let mut agent = TestAgent::<Streamer>::new();
+        let bridge = agent.connect();
+        agent.send(bridge, WINDOW + 2);
+        let outputs: Vec<_> = (0..WINDOW + 2).collect();
+        assert_eq!(agent.take_responses(bridge), outputs);
+        assert!(is_ready(&agent));
+    }
+
+    #[test]
+    fn forgets_streams_of_disconnected_handlers() {
+        let mut agent = TestAgent::<Streamer>::new();
+        let bridge = agent.connect();
+        let id = agent.send_stream(bridge, WINDOW + 2);
+        agent.take_responses(bridge);
+        agent.disconnect(bridge);
+        agent.ack_stream(id, WINDOW);
+        assert!(agent.take_responses(bridge).is_empty());
+    }
+}
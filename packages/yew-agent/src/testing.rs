@@ -0,0 +1,205 @@
+//! Utilities for testing agents without a browser.
+
+use super::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+use yew::scheduler::Shared;
+
+/// An instance of an agent, that is driven by a test instead of bridges.
+///
+/// The agent is created with a link, that collects its responses, so tests can run natively with
+/// `cargo test`, whatever the reach of the agent is. Every call runs the agent synchronously, so
+/// its responses can be inspected right away. The agent is destroyed, when the `TestAgent` is
+/// dropped.
+///
+/// Agents, that use [`AgentLink::send_future`] or other browser APIs, still need a browser.
+///
+/// # Example
+///
+/// ```
+/// use yew_agent::testing::TestAgent;
+/// use yew_agent::{Agent, AgentLink, Context, HandlerId};
+///
+/// struct Counter {
+///     link: AgentLink<Self>,
+///     count: u32,
+/// }
+///
+/// impl Agent for Counter {
+///     type Reach = Context<Self>;
+///     type Message = ();
+///     type Input = u32;
+///     type Output = u32;
+///
+///     fn create(link: AgentLink<Self>) -> Self {
+///         Self { link, count: 0 }
+///     }
+///
+///     fn update(&mut self, _msg: Self::Message) {}
+///
+///     fn handle_input(&mut self, msg: Self::Input, id: HandlerId) {
+///         self.count += msg;
+///         self.link.respond(id, self.count);
+///     }
+/// }
+///
+/// let mut agent = TestAgent::<Counter>::new();
+/// let first = agent.connect();
+/// let second = agent.connect();
+/// agent.send(first, 2);
+/// agent.send(second, 3);
+/// assert_eq!(agent.take_responses(first), vec![2]);
+/// assert_eq!(agent.take_responses(second), vec![5]);
+/// assert_eq!(agent.with_agent(|counter| counter.count), 5);
+/// ```
+pub struct TestAgent<AGN: Agent> {
+    scope: AgentScope<AGN>,
+    responses: Shared<Vec<(HandlerId, AGN::Output)>>,
+    ended_streams: Shared<Vec<HandlerId>>,
+    next_id: usize,
+    next_request: u64,
+}
+
+impl<AGN: Agent> TestAgent<AGN> {
+    /// Creates the agent.
+    pub fn new() -> Self {
+        let scope = AgentScope::<AGN>::new();
+        let responses = Rc::new(RefCell::new(Vec::new()));
+        let ended_streams = Rc::new(RefCell::new(Vec::new()));
+        let responder = TestResponder {
+            responses: responses.clone(),
+            ended_streams: ended_streams.clone(),
+        };
+        let link = AgentLink::connect(&scope, responder);
+        let upd = AgentLifecycleEvent::Create(link);
+        scope.send(upd);
+        TestAgent {
+            scope,
+            responses,
+            ended_streams,
+            next_id: 0,
+            next_request: 0,
+        }
+    }
+
+    /// Connects a new handler to the agent and returns its id.
+    pub fn connect(&mut self) -> HandlerId {
+        let id = HandlerId::new(self.next_id, true);
+        self.next_id += 1;
+        let upd = AgentLifecycleEvent::Connected(id);
+        self.scope.send(upd);
+        id
+    }
+
+    /// Sends an input to the agent from a handler.
+    pub fn send(&self, id: HandlerId, input: AGN::Input) {
+        let upd = AgentLifecycleEvent::Input(input, id);
+        self.scope.send(upd);
+    }
+
+    /// Sends an input to the agent from a handler, as if it was sent with [`Bridge::call`], and
+    /// returns the id of the call.
+    pub fn send_call(&mut self, id: HandlerId, input: AGN::Input) -> HandlerId {
+        let id = id.with_request(self.next_request);
+        self.next_request += 1;
+        let upd = AgentLifecycleEvent::Input(input, id);
+        self.scope.send(upd);
+        id
+    }
+
+    /// Sends an input to the agent from a handler, as if it was sent with [`Bridge::stream`], and
+    /// returns the id of the stream.
+    ///
+    /// The agent sends only a limited number of outputs, until they are acknowledged with
+    /// [`TestAgent::ack_stream`].
+    pub fn send_stream(&mut self, id: HandlerId, input: AGN::Input) -> HandlerId {
+        self.send_call(id, input)
+    }
+
+    /// Cancels a call or stream.
+    pub fn cancel(&self, id: HandlerId) {
+        self.scope.cancel(id);
+    }
+
+    /// Acknowledges, that `count` outputs of a stream have been consumed.
+    pub fn ack_stream(&self, id: HandlerId, count: usize) {
+        self.scope.ack_stream(id, count);
+    }
+
+    /// Indicates if the agent has ended a stream, or released a call, that was cancelled.
+    ///
+    /// See [`CancellationToken`].
+    pub fn is_stream_ended(&self, id: HandlerId) -> bool {
+        self.ended_streams
+            .borrow()
+            .iter()
+            .any(|ended| ended.request() == id.request() && *ended == id)
+    }
+
+    /// Sends a message to the agent, as if it was sent through its [`AgentLink`].
+    pub fn send_message(&self, msg: AGN::Message) {
+        let upd = AgentLifecycleEvent::Message(msg);
+        self.scope.send(upd);
+    }
+
+    /// Disconnects a handler from the agent.
+    pub fn disconnect(&self, id: HandlerId) {
+        let upd = AgentLifecycleEvent::Disconnected(id);
+        self.scope.send(upd);
+    }
+
+    /// Removes the responses to a handler and returns them in the order they were sent.
+    pub fn take_responses(&self, id: HandlerId) -> Vec<AGN::Output> {
+        let mut responses = self.responses.borrow_mut();
+        let (taken, kept) = responses
+            .drain(..)
+            .partition::<Vec<_>, _>(|(response_id, _)| *response_id == id);
+        *responses = kept;
+        taken.into_iter().map(|(_, output)| output).collect()
+    }
+
+    /// Removes all responses and returns them with the ids of their handlers in the order they
+    /// were sent.
+    pub fn take_all_responses(&self) -> Vec<(HandlerId, AGN::Output)> {
+        self.responses.borrow_mut().drain(..).collect()
+    }
+
+    /// Inspects the state of the agent.
+    pub fn with_agent<R>(&self, f: impl FnOnce(&AGN) -> R) -> R {
+        self.scope.with_agent(f)
+    }
+}
+
+impl<AGN: Agent> Default for TestAgent<AGN> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<AGN: Agent> fmt::Debug for TestAgent<AGN> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TestAgent<_>")
+    }
+}
+
+impl<AGN: Agent> Drop for TestAgent<AGN> {
+    fn drop(&mut self) {
+        let upd = AgentLifecycleEvent::Destroy;
+        self.scope.send(upd);
+    }
+}
+
+struct TestResponder<AGN: Agent> {
+    responses: Shared<Vec<(HandlerId, AGN::Output)>>,
+    ended_streams: Shared<Vec<HandlerId>>,
+}
+
+impl<AGN: Agent> Responder<AGN> for TestResponder<AGN> {
+    fn respond(&self, id: HandlerId, output: AGN::Output) {
+        self.responses.borrow_mut().push((id, output));
+    }
+
+    fn end_stream(&self, id: HandlerId) {
+        self.ended_streams.borrow_mut().push(id);
+    }
+}
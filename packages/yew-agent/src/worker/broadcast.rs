@@ -0,0 +1,259 @@
+use super::*;
+use anymap2::{self, AnyMap};
+use slab::Slab;
+use std::any::type_name;
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+use web_sys::BroadcastChannel;
+use yew::scheduler::Shared;
+
+thread_local! {
+    static TAB_CHANNELS: RefCell<AnyMap> = RefCell::new(AnyMap::new());
+}
+
+/// Error callbacks of the bridges in the current tab
+type SharedErrorCallbacks = Shared<Slab<Option<Callback<CodecError>>>>;
+
+/// Create a single instance in every tab, that also handles the inputs sent in other tabs.
+///
+/// The agent runs in the current thread like one with the [`Context`] reach. Every input sent
+/// through a bridge is handled by the agent in the tab of the bridge and posted to the agents in
+/// all other tabs of the app over a `BroadcastChannel`. These receive it with the id of a
+/// dispatcher, so they can't respond to it. The agent of a tab only receives inputs from other
+/// tabs, while the tab has a bridge to it.
+///
+/// Inputs containing [`Transferable`] payloads can't be posted to other tabs, as their payloads
+/// are moved rather than copied. They are only handled by the agent in the current tab, and the
+/// error is reported to the error callback of the bridge.
+///
+/// Messages are encoded with `CODEC`.
+#[allow(missing_debug_implementations)]
+pub struct Broadcast<AGN, CODEC = Bincode> {
+    _agent: PhantomData<AGN>,
+    _codec: PhantomData<CODEC>,
+}
+
+impl<AGN, CODEC> Broadcast<AGN, CODEC>
+where
+    AGN: Agent,
+    CODEC: Codec + 'static,
+    <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
+{
+    fn join(
+        callback: Option<Callback<AGN::Output>>,
+        on_error: Option<Callback<CodecError>>,
+    ) -> Box<dyn Bridge<AGN>> {
+        let local = Context::<AGN>::spawn_or_join(callback);
+        let (channel, key) = TAB_CHANNELS.with(|channels| {
+            let mut channels = channels.borrow_mut();
+            let tab_channel = channels
+                .entry::<TabChannel<AGN, CODEC>>()
+                .or_insert_with(TabChannel::open);
+            let key = tab_channel.errors.borrow_mut().insert(on_error.clone());
+            (tab_channel.channel.clone(), key)
+        });
+        Box::new(BroadcastBridge::<AGN, CODEC> {
+            local,
+            channel,
+            key,
+            on_error,
+            _codec: PhantomData,
+        })
+    }
+}
+
+impl<AGN, CODEC> Discoverer for Broadcast<AGN, CODEC>
+where
+    AGN: Agent,
+    CODEC: Codec + 'static,
+    <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
+{
+    type Agent = AGN;
+
+    fn spawn_or_join(callback: Option<Callback<AGN::Output>>) -> Box<dyn Bridge<AGN>> {
+        Self::join(callback, None)
+    }
+
+    fn spawn_or_join_with_options(
+        callback: Option<Callback<AGN::Output>>,
+        options: BridgeOptions,
+    ) -> Box<dyn Bridge<AGN>> {
+        Self::join(callback, options.on_error)
+    }
+}
+
+impl<AGN, CODEC> Dispatchable for Broadcast<AGN, CODEC>
+where
+    AGN: Agent,
+    CODEC: Codec + 'static,
+    <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
+{
+}
+
+/// The channel of an agent to the other tabs, open while the current tab has bridges to it
+struct TabChannel<AGN, CODEC> {
+    channel: BroadcastChannel,
+    errors: SharedErrorCallbacks,
+    /// Handles the inputs from other tabs with a dispatcher to the agent of this tab
+    _onmessage: Closure<dyn Fn(MessageEvent)>,
+    _agent: PhantomData<AGN>,
+    _codec: PhantomData<CODEC>,
+}
+
+impl<AGN, CODEC> TabChannel<AGN, CODEC>
+where
+    AGN: Agent,
+    CODEC: Codec + 'static,
+    <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
+{
+    fn open() -> Self {
+        let name = format!("yew-agent:{}", type_name::<AGN>());
+        let channel = BroadcastChannel::new(&name).expect("failed to open broadcast channel");
+        let errors: SharedErrorCallbacks = Rc::new(RefCell::new(Slab::new()));
+        let dispatcher = RefCell::new(Context::<AGN>::spawn_or_join(None));
+        let onmessage = {
+            let errors = errors.clone();
+            move |event: MessageEvent| {
+                let packet = Packet::from_message(event.data());
+                match packet.decode::<CODEC, ToWorker<AGN::Input>>() {
+                    Ok(ToWorker::ProcessInput(_, input)) => dispatcher.borrow_mut().send(input),
+                    Ok(_) => {}
+                    Err(error) => report_error_to_tab(&errors, error),
+                }
+            }
+        };
+        let onmessage = Closure::wrap(Box::new(onmessage) as Box<dyn Fn(MessageEvent)>);
+        channel.set_onmessage(Some(onmessage.as_ref().unchecked_ref()));
+        TabChannel {
+            channel,
+            errors,
+            _onmessage: onmessage,
+            _agent: PhantomData,
+            _codec: PhantomData,
+        }
+    }
+}
+
+impl<AGN, CODEC> Drop for TabChannel<AGN, CODEC> {
+    fn drop(&mut self) {
+        self.channel.set_onmessage(None);
+        self.channel.close();
+    }
+}
+
+/// Reports a message from another tab, that could not be decoded, to all bridges of the tab
+fn report_error_to_tab(errors: &SharedErrorCallbacks, error: CodecError) {
+    let callbacks: Vec<_> = errors
+        .borrow()
+        .iter()
+        .filter_map(|(_, cb)| cb.clone())
+        .collect();
+    if callbacks.is_empty() {
+        report_error(None, error);
+    } else {
+        for callback in callbacks {
+            report_error(Some(&callback), error.clone());
+        }
+    }
+}
+
+struct BroadcastBridge<AGN, CODEC>
+where
+    AGN: Agent,
+    CODEC: Codec + 'static,
+    <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
+{
+    /// Bridge to the agent in this tab
+    local: Box<dyn Bridge<AGN>>,
+    channel: BroadcastChannel,
+    /// Key of the error callback of the bridge
+    key: usize,
+    on_error: Option<Callback<CodecError>>,
+    _codec: PhantomData<CODEC>,
+}
+
+impl<AGN, CODEC> BroadcastBridge<AGN, CODEC>
+where
+    AGN: Agent,
+    CODEC: Codec + 'static,
+    <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
+{
+    /// Post an input to the agents in the other tabs
+    fn broadcast(&self, msg: &AGN::Input) {
+        let msg = ToWorker::ProcessInput(HandlerId::new(0, false), msg);
+        let packet = match Packet::encode_without_transfer::<CODEC, _>(&msg) {
+            Ok(packet) => packet,
+            Err(error) => return report_error(self.on_error.as_ref(), error),
+        };
+        let (message, _) = packet.to_message();
+        if self.channel.post_message(&message).is_err() {
+            gloo_console::error!("failed to post an agent input to other tabs");
+        }
+    }
+}
+
+impl<AGN, CODEC> fmt::Debug for BroadcastBridge<AGN, CODEC>
+where
+    AGN: Agent,
+    CODEC: Codec + 'static,
+    <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("BroadcastBridge<_>")
+    }
+}
+
+impl<AGN, CODEC> Bridge<AGN> for BroadcastBridge<AGN, CODEC>
+where
+    AGN: Agent,
+    CODEC: Codec + 'static,
+    <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
+{
+    fn send(&mut self, msg: AGN::Input) {
+        self.broadcast(&msg);
+        self.local.send(msg);
+    }
+
+    fn call(&mut self, msg: AGN::Input) -> AgentCall<AGN::Output> {
+        self.broadcast(&msg);
+        self.local.call(msg)
+    }
+
+    fn stream(&mut self, msg: AGN::Input) -> AgentStream<AGN::Output> {
+        self.broadcast(&msg);
+        self.local.stream(msg)
+    }
+
+    fn cancel_on_drop(&mut self) {
+        self.local.cancel_on_drop();
+    }
+}
+
+impl<AGN, CODEC> Drop for BroadcastBridge<AGN, CODEC>
+where
+    AGN: Agent,
+    CODEC: Codec + 'static,
+    <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
+{
+    fn drop(&mut self) {
+        let closed = TAB_CHANNELS.with(|channels| {
+            let mut channels = channels.borrow_mut();
+            let last = match channels.get_mut::<TabChannel<AGN, CODEC>>() {
+                Some(tab_channel) => {
+                    let mut errors = tab_channel.errors.borrow_mut();
+                    errors.remove(self.key);
+                    errors.is_empty()
+                }
+                None => false,
+            };
+            if last {
+                channels.remove::<TabChannel<AGN, CODEC>>()
+            } else {
+                None
+            }
+        });
+        // Closes the channel and drops its dispatcher outside of the borrow of the channels
+        drop(closed);
+    }
+}
@@ -0,0 +1,191 @@
+use js_sys::Uint8Array;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use wasm_bindgen::{JsCast, JsValue};
+
+/// Encodes the messages sent between a worker agent and its bridges.
+///
+/// The codec of an agent is chosen by the second parameter of its reach, like
+/// `type Reach = Public<Self, Json>`. It defaults to [`Bincode`].
+///
+/// Encoded messages must not be plain JavaScript arrays, which are used to post messages along with
+/// their [`Transferable`](crate::Transferable) payloads.
+pub trait Codec {
+    /// Encode a message into a value, that can be posted to or from a worker
+    fn encode<T: Serialize>(message: &T) -> Result<JsValue, CodecError>;
+
+    /// Decode a message received from a worker or one of its bridges
+    fn decode<T: for<'de> Deserialize<'de>>(message: JsValue) -> Result<T, CodecError>;
+}
+
+/// Error reported, when a message between a worker agent and its bridges can't be encoded or
+/// decoded.
+///
+/// Messages, that can't be decoded, usually mean, that the worker was built from a different
+/// version of the agent than the bridge. Messages can't be encoded, if their `Serialize`
+/// implementation fails.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CodecError {
+    encoding: bool,
+    message: String,
+}
+
+impl CodecError {
+    /// Creates an error for a message, that can't be encoded, with a message describing the reason
+    pub fn encode(message: impl fmt::Display) -> Self {
+        Self {
+            encoding: true,
+            message: message.to_string(),
+        }
+    }
+
+    /// Creates an error for a message, that can't be decoded, with a message describing the reason
+    pub fn decode(message: impl fmt::Display) -> Self {
+        Self {
+            encoding: false,
+            message: message.to_string(),
+        }
+    }
+
+    /// Indicates if the message could not be encoded, rather than decoded
+    pub fn is_encode(&self) -> bool {
+        self.encoding
+    }
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let action = if self.encoding { "encode" } else { "decode" };
+        write!(f, "can't {} an agent message: {}", action, self.message)
+    }
+}
+
+impl std::error::Error for CodecError {}
+
+/// A compact binary codec, that posts messages as byte arrays.
+#[derive(Debug)]
+pub struct Bincode;
+
+impl Bincode {
+    fn serialize<T: Serialize>(message: &T) -> Result<Vec<u8>, CodecError> {
+        bincode::serialize(message).map_err(CodecError::encode)
+    }
+
+    fn deserialize<T: for<'de> Deserialize<'de>>(data: &[u8]) -> Result<T, CodecError> {
+        bincode::deserialize(data).map_err(CodecError::decode)
+    }
+}
+
+impl Codec for Bincode {
+    fn encode<T: Serialize>(message: &T) -> Result<JsValue, CodecError> {
+        let data = Self::serialize(message)?;
+        Ok(Uint8Array::from(data.as_slice()).into())
+    }
+
+    fn decode<T: for<'de> Deserialize<'de>>(message: JsValue) -> Result<T, CodecError> {
+        let data = message
+            .dyn_into::<Uint8Array>()
+            .map_err(|_| CodecError::decode("expected a byte array"))?
+            .to_vec();
+        Self::deserialize(&data)
+    }
+}
+
+/// A codec, that posts messages as JSON strings, which can be read in the developer tools of the
+/// browser.
+#[cfg(feature = "json")]
+#[derive(Debug)]
+pub struct Json;
+
+#[cfg(feature = "json")]
+impl Json {
+    fn serialize<T: Serialize>(message: &T) -> Result<String, CodecError> {
+        serde_json::to_string(message).map_err(CodecError::encode)
+    }
+
+    fn deserialize<T: for<'de> Deserialize<'de>>(data: &str) -> Result<T, CodecError> {
+        serde_json::from_str(data).map_err(CodecError::decode)
+    }
+}
+
+#[cfg(feature = "json")]
+impl Codec for Json {
+    fn encode<T: Serialize>(message: &T) -> Result<JsValue, CodecError> {
+        Ok(JsValue::from_str(&Self::serialize(message)?))
+    }
+
+    fn decode<T: for<'de> Deserialize<'de>>(message: JsValue) -> Result<T, CodecError> {
+        let data = message
+            .as_string()
+            .ok_or_else(|| CodecError::decode("expected a string"))?;
+        Self::deserialize(&data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::ser::{self, Serializer};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Message {
+        Input(u32, String),
+        Output(Vec<Option<bool>>),
+    }
+
+    /// A value, that always fails to serialize
+    struct Unserializable;
+
+    impl Serialize for Unserializable {
+        fn serialize<S: Serializer>(&self, _serializer: S) -> Result<S::Ok, S::Error> {
+            Err(ser::Error::custom("unserializable"))
+        }
+    }
+
+    fn messages() -> Vec<Message> {
+        vec![
+            Message::Input(7, "yew".to_string()),
+            Message::Output(vec![Some(true), None]),
+        ]
+    }
+
+    #[test]
+    fn bincode_round_trip() {
+        for message in messages() {
+            let data = Bincode::serialize(&message).unwrap();
+            assert_eq!(Bincode::deserialize::<Message>(&data).unwrap(), message);
+        }
+    }
+
+    #[test]
+    fn bincode_errors() {
+        let error = Bincode::deserialize::<Message>(&[9, 0, 0, 0]).unwrap_err();
+        assert!(!error.is_encode());
+
+        let error = Bincode::serialize(&Unserializable).unwrap_err();
+        assert!(error.is_encode());
+        assert_eq!(
+            error.to_string(),
+            "can't encode an agent message: unserializable"
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_round_trip() {
+        for message in messages() {
+            let data = Json::serialize(&message).unwrap();
+            assert_eq!(Json::deserialize::<Message>(&data).unwrap(), message);
+        }
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_errors() {
+        let error = Json::deserialize::<Message>("{\"Unknown\":1}").unwrap_err();
+        assert!(!error.is_encode());
+
+        let error = Json::serialize(&Unserializable).unwrap_err();
+        assert!(error.is_encode());
+    }
+}
@@ -1,16 +1,33 @@
+mod broadcast;
+mod codec;
 mod private;
 mod public;
 mod queue;
+mod restart;
+mod shared;
+mod transfer;
 
+pub use broadcast::Broadcast;
+
+#[cfg(feature = "json")]
+pub use codec::Json;
+pub use codec::{Bincode, Codec, CodecError};
 pub use private::Private;
-pub use public::Public;
+pub use public::{Pool, PoolWithCodec, Public};
+pub use restart::{RestartPolicy, WorkerStatus};
+pub use shared::SharedWorker;
+pub use transfer::{Transfer, Transferable};
 
 use super::*;
-use js_sys::{Array, Reflect, Uint8Array};
+use js_sys::{Array, Reflect};
+use restart::WorkerSet;
 use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+use transfer::Packet;
 use wasm_bindgen::{closure::Closure, JsCast, JsValue, UnwrapThrowExt};
 use web_sys::{
-    Blob, BlobPropertyBag, DedicatedWorkerGlobalScope, MessageEvent, Url, Worker, WorkerOptions,
+    Blob, BlobPropertyBag, DedicatedWorkerGlobalScope, ErrorEvent, Event, MessageEvent,
+    MessagePort, SharedWorkerGlobalScope, Url, Worker, WorkerOptions,
 };
 
 /// Implements rules to register a worker in a separate thread.
@@ -20,23 +37,15 @@ pub trait Threaded {
     fn register();
 }
 
-/// Message packager, based on serde::Serialize/Deserialize
-pub trait Packed {
-    /// Pack serializable message into Vec<u8>
-    fn pack(&self) -> Vec<u8>;
-    /// Unpack deserializable message of byte slice
-    fn unpack(data: &[u8]) -> Self;
+/// A reach, that runs its agent in a worker.
+#[doc(hidden)]
+pub trait WorkerReach {
+    /// Codec of the messages sent between the worker and its bridges
+    type Codec: Codec;
 }
 
-impl<T: Serialize + for<'de> Deserialize<'de>> Packed for T {
-    fn pack(&self) -> Vec<u8> {
-        bincode::serialize(&self).expect("can't serialize an agent message")
-    }
-
-    fn unpack(data: &[u8]) -> Self {
-        bincode::deserialize(data).expect("can't deserialize an agent message")
-    }
-}
+/// Codec of the messages of an agent running in a worker
+type CodecOf<AGN> = <<AGN as Agent>::Reach as WorkerReach>::Codec;
 
 /// Serializable messages to worker
 #[derive(Serialize, Deserialize, Debug)]
@@ -45,31 +54,130 @@ enum ToWorker<T> {
     Connected(HandlerId),
     /// Incoming message to Worker
     ProcessInput(HandlerId, T),
+    /// Client consumed a number of outputs of a stream
+    StreamAck(HandlerId, usize),
+    /// Client cancelled a call or stream
+    Cancel(HandlerId),
     /// Client is disconnected
     Disconnected(HandlerId),
     /// Worker should be terminated
     Destroy,
 }
 
+impl<T> ToWorker<T> {
+    /// Message to the worker handling a call or stream about it
+    fn control(id: HandlerId, control: Control) -> Self {
+        match control {
+            Control::Acknowledge(count) => ToWorker::StreamAck(id, count),
+            Control::Cancel => ToWorker::Cancel(id),
+        }
+    }
+}
+
 /// Serializable messages sent by worker to consumer
 #[derive(Serialize, Deserialize, Debug)]
 enum FromWorker<T> {
-    /// Worker sends this message when `wasm` bundle has loaded.
-    WorkerLoaded,
+    /// Worker sends this message when `wasm` bundle has loaded, with the name of the lock it
+    /// holds until it terminates, if the browser supports locks.
+    WorkerLoaded(Option<String>),
     /// Outgoing message to consumer
     ProcessOutput(HandlerId, T),
+    /// Worker sends this message when a stream of outputs has ended, or when the agent released a
+    /// call, that was cancelled.
+    StreamEnd(HandlerId),
+    /// Worker sends this message when it can't decode a message from a bridge, or encode an
+    /// output to the handler with the id.
+    Error(Option<HandlerId>, CodecError),
+}
+
+impl<T> FromWorker<T> {
+    /// Id of the handler, that the message is sent to
+    fn handler_id(&self) -> Option<HandlerId> {
+        match self {
+            FromWorker::ProcessOutput(id, _) | FromWorker::StreamEnd(id) => Some(*id),
+            FromWorker::Error(id, _) => *id,
+            FromWorker::WorkerLoaded(_) => None,
+        }
+    }
 }
 
-fn send_to_remote<AGN>(worker: &Worker, msg: ToWorker<AGN::Input>)
+/// Encodes a message to a worker, reporting the error to `on_error`, if it can't be encoded
+fn encode_to_worker<AGN, CODEC>(
+    msg: &ToWorker<AGN::Input>,
+    on_error: Option<&Callback<CodecError>>,
+) -> Option<Packet>
 where
     AGN: Agent,
+    CODEC: Codec,
+    <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
+{
+    match Packet::encode::<CODEC, _>(msg) {
+        Ok(packet) => Some(packet),
+        Err(error) => {
+            report_error(on_error, error);
+            None
+        }
+    }
+}
+
+fn send_to_remote<AGN, CODEC>(
+    worker: &RemoteWorker,
+    msg: ToWorker<AGN::Input>,
+    on_error: Option<&Callback<CodecError>>,
+) where
+    AGN: Agent,
+    CODEC: Codec,
     <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
-    <AGN as Agent>::Output: Serialize + for<'de> Deserialize<'de>,
 {
-    let msg = msg.pack();
-    worker.post_message_vec(msg);
+    if let Some(packet) = encode_to_worker::<AGN, CODEC>(&msg, on_error) {
+        worker.post_packet(packet);
+    }
 }
-fn worker_new(name_of_resource: &str, resource_is_relative: bool, is_module: bool) -> Worker {
+
+/// Posts a message from a worker to its bridges.
+///
+/// If the message can't be encoded, the error is posted instead.
+fn post_from_worker<CODEC, T>(target: &impl WorkerExt, msg: &FromWorker<T>)
+where
+    CODEC: Codec,
+    T: Serialize,
+{
+    let packet = Packet::encode::<CODEC, _>(msg).or_else(|error| {
+        let msg: FromWorker<T> = FromWorker::Error(msg.handler_id(), error);
+        Packet::encode::<CODEC, _>(&msg)
+    });
+    match packet {
+        Ok(packet) => target.post_packet(packet),
+        Err(error) => gloo_console::error!(error.to_string()),
+    }
+}
+
+/// Reports a status of a worker to the status callbacks of its bridges
+fn report_status<'a>(
+    callbacks: impl IntoIterator<Item = &'a Callback<WorkerStatus>>,
+    status: WorkerStatus,
+) {
+    for callback in callbacks {
+        callback.emit(status.clone());
+    }
+}
+
+/// Reports a message, that could not be encoded or decoded, to the error callback of a bridge or
+/// to the console, if the bridge has none.
+fn report_error(on_error: Option<&Callback<CodecError>>, error: CodecError) {
+    match on_error {
+        Some(on_error) => on_error.emit(error),
+        None => gloo_console::error!(error.to_string()),
+    }
+}
+
+/// Error of the calls and streams of an agent, whose workers all crashed and were not restarted
+fn no_worker_left() -> CallError {
+    CallError::Crashed("no worker is running the agent".to_string())
+}
+
+/// Returns the absolute URL of a resource of an agent
+fn resource_url(name_of_resource: &str, resource_is_relative: bool) -> String {
     let origin = gloo_utils::document()
         .location()
         .unwrap_throw()
@@ -85,21 +193,27 @@ fn worker_new(name_of_resource: &str, resource_is_relative: bool, is_module: boo
     } else {
         ""
     };
-    let script_url = format!("{}{}/{}", origin, prefix, name_of_resource);
-    let wasm_url = format!(
-        "{}{}/{}",
-        origin,
-        prefix,
-        name_of_resource.replace(".js", "_bg.wasm")
+    format!("{}{}/{}", origin, prefix, name_of_resource)
+}
+
+fn worker_new(name_of_resource: &str, resource_is_relative: bool, is_module: bool) -> Worker {
+    let script_url = resource_url(name_of_resource, resource_is_relative);
+    let wasm_url = resource_url(
+        &name_of_resource.replace(".js", "_bg.wasm"),
+        resource_is_relative,
     );
-    let array = Array::new();
-    array.push(
-        &format!(
+    // Scripts built with `--target no-modules` define a global `wasm_bindgen` function, while
+    // modules built with `--target web` export it as their default
+    let loader = if is_module {
+        format!(r#"import init from "{}";init("{}");"#, script_url, wasm_url)
+    } else {
+        format!(
             r#"importScripts("{}");wasm_bindgen("{}");"#,
             script_url, wasm_url
         )
-        .into(),
-    );
+    };
+    let array = Array::new();
+    array.push(&loader.into());
     let blob = Blob::new_with_str_sequence_and_options(
         &array,
         BlobPropertyBag::new().type_("application/javascript"),
@@ -108,44 +222,248 @@ fn worker_new(name_of_resource: &str, resource_is_relative: bool, is_module: boo
     let url = Url::create_object_url_with_blob(&blob).unwrap();
 
     if is_module {
-        let options = WorkerOptions::new();
-        Reflect::set(
-            options.as_ref(),
-            &JsValue::from_str("type"),
-            &JsValue::from_str("module"),
-        )
-        .unwrap();
-        Worker::new_with_options(&url, &options).expect("failed to spawn worker")
+        Worker::new_with_options(&url, &module_options()).expect("failed to spawn worker")
     } else {
         Worker::new(&url).expect("failed to spawn worker")
     }
 }
 
+/// Options of a worker running an ES module
+fn module_options() -> WorkerOptions {
+    let options = WorkerOptions::new();
+    Reflect::set(
+        options.as_ref(),
+        &JsValue::from_str("type"),
+        &JsValue::from_str("module"),
+    )
+    .unwrap();
+    options
+}
+
+/// A worker, that the bridges of an agent post their messages to
+#[derive(Clone)]
+pub(crate) enum RemoteWorker {
+    /// A worker dedicated to the page
+    Dedicated(Worker),
+    /// A worker shared by all pages and the port of the page to it
+    Shared(web_sys::SharedWorker, MessagePort),
+}
+
+impl RemoteWorker {
+    /// Spawns a dedicated worker running the agent
+    fn dedicated<AGN: Agent>() -> Self {
+        let name_of_resource = AGN::name_of_resource();
+        let is_relative = AGN::resource_path_is_relative();
+        Self::Dedicated(worker_new(name_of_resource, is_relative, AGN::is_module()))
+    }
+
+    /// Connects to the shared worker running the agent, spawning it if necessary
+    fn shared<AGN: Agent>() -> Self {
+        let script_url = resource_url(AGN::name_of_resource(), AGN::resource_path_is_relative());
+        let worker = if AGN::is_module() {
+            web_sys::SharedWorker::new_with_worker_options(&script_url, &module_options())
+        } else {
+            web_sys::SharedWorker::new(&script_url)
+        };
+        let worker = worker.expect("failed to spawn shared worker");
+        let port = worker.port();
+        Self::Shared(worker, port)
+    }
+
+    /// Sets a handler, that is called with the errors of the worker.
+    ///
+    /// Browsers report only loading errors of shared workers.
+    fn set_onerror_closure(&self, handler: impl 'static + Fn(WorkerError)) -> ErrorListener {
+        let handler = move |event: Event| {
+            let error = match event.dyn_ref::<ErrorEvent>() {
+                Some(event) => {
+                    let message = event.message();
+                    // Traps of the wasm module, like panics, leave the agent in an unknown state
+                    let fatal = message.contains("RuntimeError");
+                    WorkerError { message, fatal }
+                }
+                None => WorkerError {
+                    message: "the worker failed to load".to_string(),
+                    fatal: true,
+                },
+            };
+            handler(error);
+        };
+        let closure = Closure::wrap(Box::new(handler) as Box<dyn Fn(Event)>);
+        match self {
+            Self::Dedicated(worker) => worker.set_onerror(Some(closure.as_ref().unchecked_ref())),
+            Self::Shared(worker, _) => worker.set_onerror(Some(closure.as_ref().unchecked_ref())),
+        }
+        closure
+    }
+
+    fn clear_onerror(&self) {
+        match self {
+            Self::Dedicated(worker) => worker.set_onerror(None),
+            Self::Shared(worker, _) => worker.set_onerror(None),
+        }
+    }
+
+    /// Stops the worker, or disconnects from it, if it is shared
+    fn terminate(&self) {
+        match self {
+            Self::Dedicated(worker) => worker.terminate(),
+            Self::Shared(_, port) => port.close(),
+        }
+    }
+}
+
+impl WorkerExt for RemoteWorker {
+    fn set_onmessage_closure(&self, handler: impl 'static + Fn(Packet)) {
+        match self {
+            Self::Dedicated(worker) => worker.set_onmessage_closure(handler),
+            Self::Shared(_, port) => port.set_onmessage_closure(handler),
+        }
+    }
+
+    fn post_packet(&self, packet: Packet) {
+        match self {
+            Self::Dedicated(worker) => worker.post_packet(packet),
+            Self::Shared(_, port) => port.post_packet(packet),
+        }
+    }
+}
+
+/// Handler of the error events of a worker
+type ErrorListener = Closure<dyn Fn(Event)>;
+
+/// An error reported by a worker
+struct WorkerError {
+    message: String,
+    /// Whether the worker can't run the agent any longer, because it failed to load, its wasm
+    /// module trapped or it terminated
+    fatal: bool,
+}
+
+impl WorkerError {
+    /// Error reported, when a worker terminated without being told so
+    fn terminated() -> Self {
+        Self {
+            message: "the worker terminated".to_string(),
+            fatal: true,
+        }
+    }
+
+    /// Indicates if the error crashed the worker, given whether it has loaded the agent.
+    ///
+    /// Any error of a worker, that did not load, means that it failed to load.
+    fn is_crash(&self, loaded: bool) -> bool {
+        self.fatal || !loaded
+    }
+}
+
+/// A worker spawned by the bridges of an agent, along with the handler of its errors
+pub(crate) struct SpawnedWorker {
+    worker: RemoteWorker,
+    onerror: Option<ErrorListener>,
+}
+
+impl SpawnedWorker {
+    /// Sets the handlers of the messages and the errors of a worker
+    fn new(
+        worker: RemoteWorker,
+        on_message: impl 'static + Fn(Packet, &RemoteWorker),
+        on_error: impl 'static + Fn(WorkerError),
+    ) -> Self {
+        let worker_clone = worker.clone();
+        worker.set_onmessage_closure(move |packet: Packet| {
+            on_message(packet, &worker_clone);
+        });
+        let onerror = worker.set_onerror_closure(on_error);
+        Self {
+            worker,
+            onerror: Some(onerror),
+        }
+    }
+
+    pub fn worker(&self) -> &RemoteWorker {
+        &self.worker
+    }
+}
+
+impl Drop for SpawnedWorker {
+    fn drop(&mut self) {
+        self.worker.clear_onerror();
+        // The worker is dropped, when it is restarted by its own error handler, which must not be
+        // freed while it runs
+        if let Some(onerror) = self.onerror.take() {
+            wasm_bindgen_futures::spawn_local(async move { drop(onerror) });
+        }
+    }
+}
+
+/// Returns the lock manager of the browser, unless it does not support locks
+fn lock_manager() -> Option<JsValue> {
+    let navigator = Reflect::get(&js_sys::global(), &JsValue::from_str("navigator")).ok()?;
+    let locks = Reflect::get(&navigator, &JsValue::from_str("locks")).ok()?;
+    if locks.is_undefined() {
+        None
+    } else {
+        Some(locks)
+    }
+}
+
+/// Requests the lock with `name`, calling `callback`, once it is granted
+fn request_lock(locks: &JsValue, name: &str, callback: &JsValue) -> Result<(), JsValue> {
+    let request: js_sys::Function =
+        Reflect::get(locks, &JsValue::from_str("request"))?.dyn_into()?;
+    request.call2(locks, &JsValue::from_str(name), callback)?;
+    Ok(())
+}
+
+/// Takes a lock, that the worker holds until it terminates, and returns its name, unless the
+/// browser does not support locks.
+///
+/// The bridges learn about the termination of the worker by requesting the same lock.
+fn hold_lock() -> Option<String> {
+    let locks = lock_manager()?;
+    let name = format!("yew-agent-{}", js_sys::Math::random());
+    // The lock is released, once the returned promise settles, which it never does
+    let hold = Closure::once_into_js(|| js_sys::Promise::new(&mut |_, _| {}));
+    request_lock(&locks, &name, &hold).ok()?;
+    Some(name)
+}
+
+/// Calls `handler`, once the worker holding the lock with `name` terminates
+fn watch_lock(name: &str, handler: impl 'static + FnOnce()) {
+    if let Some(locks) = lock_manager() {
+        let callback = Closure::once_into_js(handler);
+        if let Err(error) = request_lock(&locks, name, &callback) {
+            gloo_console::error!(error);
+        }
+    }
+}
+
 fn worker_self() -> DedicatedWorkerGlobalScope {
     JsValue::from(js_sys::global()).into()
 }
 
 trait WorkerExt {
-    fn set_onmessage_closure(&self, handler: impl 'static + Fn(Vec<u8>));
+    fn set_onmessage_closure(&self, handler: impl 'static + Fn(Packet));
 
-    fn post_message_vec(&self, data: Vec<u8>);
+    fn post_packet(&self, packet: Packet);
 }
 
 macro_rules! worker_ext_impl {
-    ($($type:ident),+) => {$(
+    ($($type:ident => $post_message:ident),+) => {$(
         impl WorkerExt for $type {
-            fn set_onmessage_closure(&self, handler: impl 'static + Fn(Vec<u8>)) {
+            fn set_onmessage_closure(&self, handler: impl 'static + Fn(Packet)) {
                 let handler = move |message: MessageEvent| {
-                    let data = Uint8Array::from(message.data()).to_vec();
-                    handler(data);
+                    handler(Packet::from_message(message.data()));
                 };
                 let closure = Closure::wrap(Box::new(handler) as Box<dyn Fn(MessageEvent)>);
                 self.set_onmessage(Some(closure.as_ref().unchecked_ref()));
                 closure.forget();
             }
 
-            fn post_message_vec(&self, data: Vec<u8>) {
-                self.post_message(&Uint8Array::from(data.as_slice()))
+            fn post_packet(&self, packet: Packet) {
+                let (message, transfer) = packet.to_message();
+                self.$post_message(&message, transfer)
                     .expect("failed to post message");
             }
         }
@@ -153,5 +471,91 @@ macro_rules! worker_ext_impl {
 }
 
 worker_ext_impl! {
-    Worker, DedicatedWorkerGlobalScope
+    Worker => post_message_with_transfer,
+    DedicatedWorkerGlobalScope => post_message_with_transfer,
+    MessagePort => post_message_with_transferable
+}
+
+struct WorkerResponder<CODEC> {
+    _codec: PhantomData<CODEC>,
+}
+
+impl<AGN, CODEC> Responder<AGN> for WorkerResponder<CODEC>
+where
+    AGN: Agent,
+    CODEC: Codec,
+    <AGN as Agent>::Output: Serialize + for<'de> Deserialize<'de>,
+{
+    fn respond(&self, id: HandlerId, output: AGN::Output) {
+        let msg = FromWorker::ProcessOutput(id, output);
+        post_from_worker::<CODEC, _>(&worker_self(), &msg);
+    }
+
+    fn end_stream(&self, id: HandlerId) {
+        let msg: FromWorker<AGN::Output> = FromWorker::StreamEnd(id);
+        post_from_worker::<CODEC, _>(&worker_self(), &msg);
+    }
+}
+
+impl<AGN> Threaded for AGN
+where
+    AGN: Agent,
+    <AGN as Agent>::Reach: WorkerReach,
+    <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
+    <AGN as Agent>::Output: Serialize + for<'de> Deserialize<'de>,
+{
+    fn register() {
+        if let Ok(global) = JsValue::from(js_sys::global()).dyn_into::<SharedWorkerGlobalScope>() {
+            shared::register::<AGN>(global);
+            return;
+        }
+
+        let scope = AgentScope::<AGN>::new();
+        let responder = WorkerResponder::<CodecOf<AGN>> {
+            _codec: PhantomData,
+        };
+        let link = AgentLink::connect(&scope, responder);
+        let upd = AgentLifecycleEvent::Create(link);
+        scope.send(upd);
+        let handler = move |packet: Packet| {
+            let msg = match packet.decode::<CodecOf<AGN>, ToWorker<AGN::Input>>() {
+                Ok(msg) => msg,
+                Err(error) => {
+                    let msg: FromWorker<AGN::Output> = FromWorker::Error(None, error);
+                    post_from_worker::<CodecOf<AGN>, _>(&worker_self(), &msg);
+                    return;
+                }
+            };
+            match msg {
+                ToWorker::Connected(id) => {
+                    let upd = AgentLifecycleEvent::Connected(id);
+                    scope.send(upd);
+                }
+                ToWorker::ProcessInput(id, value) => {
+                    let upd = AgentLifecycleEvent::Input(value, id);
+                    scope.send(upd);
+                }
+                ToWorker::StreamAck(id, count) => {
+                    scope.ack_stream(id, count);
+                }
+                ToWorker::Cancel(id) => {
+                    scope.cancel(id);
+                }
+                ToWorker::Disconnected(id) => {
+                    let upd = AgentLifecycleEvent::Disconnected(id);
+                    scope.send(upd);
+                }
+                ToWorker::Destroy => {
+                    let upd = AgentLifecycleEvent::Destroy;
+                    scope.send(upd);
+                    // Terminates web worker
+                    worker_self().close();
+                }
+            }
+        };
+        let loaded: FromWorker<AGN::Output> = FromWorker::WorkerLoaded(hold_lock());
+        let worker = worker_self();
+        worker.set_onmessage_closure(handler);
+        post_from_worker::<CodecOf<AGN>, _>(&worker, &loaded);
+    }
 }
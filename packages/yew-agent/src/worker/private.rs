@@ -3,9 +3,8 @@ use queue::Queue;
 use std::cell::RefCell;
 use std::fmt;
 use std::marker::PhantomData;
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
 use std::sync::atomic::{AtomicUsize, Ordering};
-use web_sys::Worker;
 use yew::callback::Callback;
 
 thread_local! {
@@ -13,65 +12,96 @@ thread_local! {
 }
 
 static PRIVATE_ID_COUNTER: AtomicUsize = AtomicUsize::new(0);
-const SINGLETON_ID: HandlerId = HandlerId(0, true);
+const SINGLETON_ID: HandlerId = HandlerId(0, true, None);
 
 /// Create a new instance for every bridge.
+///
+/// Messages are encoded with `CODEC`.
 #[allow(missing_debug_implementations)]
-pub struct Private<AGN> {
+pub struct Private<AGN, CODEC = Bincode> {
     _agent: PhantomData<AGN>,
+    _codec: PhantomData<CODEC>,
 }
 
-impl<AGN> Discoverer for Private<AGN>
+impl<AGN, CODEC> Private<AGN, CODEC>
 where
     AGN: Agent,
+    CODEC: Codec + 'static,
     <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
     <AGN as Agent>::Output: Serialize + for<'de> Deserialize<'de>,
 {
-    type Agent = AGN;
-
-    fn spawn_or_join(callback: Option<Callback<AGN::Output>>) -> Box<dyn Bridge<AGN>> {
+    fn spawn(
+        callback: Option<Callback<AGN::Output>>,
+        options: BridgeOptions,
+    ) -> Box<dyn Bridge<AGN>> {
+        let BridgeOptions {
+            on_error,
+            on_status,
+        } = options;
         let id = PRIVATE_ID_COUNTER.fetch_add(1, Ordering::Relaxed);
         let callback = callback.expect("Callback required for Private agents");
-        let handler = move |data: Vec<u8>, worker: &Worker| {
-            let msg = FromWorker::<AGN::Output>::unpack(&data);
+        let calls = PendingCalls::new();
+        let handler_calls = calls.clone();
+        let handler_on_error = on_error.clone();
+        let handler = move |packet: Packet, worker: &RemoteWorker, on_terminate: &Terminate| {
+            let on_error = handler_on_error.as_ref();
+            let msg = match packet.decode::<CODEC, FromWorker<AGN::Output>>() {
+                Ok(msg) => msg,
+                Err(error) => {
+                    report_error(on_error, error);
+                    return;
+                }
+            };
             match msg {
-                FromWorker::WorkerLoaded => {
+                FromWorker::WorkerLoaded(lock) => {
                     QUEUE.with(|queue| {
                         queue.insert_loaded_agent(id);
 
                         if let Some(msgs) = queue.remove_msg_queue(&id) {
                             for msg in msgs {
-                                worker.post_message_vec(msg)
+                                worker.post_packet(msg)
                             }
                         }
                     });
+                    if let Some(lock) = lock {
+                        let on_terminate = on_terminate.clone();
+                        watch_lock(&lock, move || on_terminate());
+                    }
                 }
                 FromWorker::ProcessOutput(id, output) => {
                     assert_eq!(id.raw_id(), SINGLETON_ID.raw_id());
-                    callback.emit(output);
+                    if let Some(output) = handler_calls.resolve(id, output) {
+                        callback.emit(output);
+                    }
+                }
+                FromWorker::StreamEnd(id) => {
+                    handler_calls.end(id);
+                }
+                FromWorker::Error(_, error) => {
+                    report_error(on_error, error);
                 }
             }
         };
 
-        let name_of_resource = AGN::name_of_resource();
-        let is_relative = AGN::resource_path_is_relative();
         let handler_cell = Rc::new(RefCell::new(Some(handler)));
 
-        let worker = {
-            let handler_cell = handler_cell.clone();
-            let worker = worker_new(name_of_resource, is_relative, AGN::is_module());
-            let worker_clone = worker.clone();
-            worker.set_onmessage_closure(move |data: Vec<u8>| {
-                if let Some(handler) = handler_cell.borrow().as_ref() {
-                    handler(data, &worker_clone)
-                }
-            });
-            worker
+        let workers = WorkerSet::new(AGN::restart_policy());
+        let spawn = Spawn {
+            id,
+            handler_cell: handler_cell.clone(),
+            workers: Rc::downgrade(&workers),
+            calls: calls.clone(),
+            on_error: on_error.clone(),
+            on_status,
         };
-        let bridge = PrivateBridge {
+        workers.push(spawn_worker::<AGN, CODEC, _>(Rc::new(spawn), 0));
+        let bridge = PrivateBridge::<AGN, CODEC, _> {
             handler_cell,
-            worker,
+            workers,
+            calls,
+            on_error,
             _agent: PhantomData,
+            _codec: PhantomData,
             id,
         };
         bridge.send_message(ToWorker::Connected(SINGLETON_ID));
@@ -79,77 +109,269 @@ where
     }
 }
 
+impl<AGN, CODEC> Discoverer for Private<AGN, CODEC>
+where
+    AGN: Agent,
+    CODEC: Codec + 'static,
+    <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
+    <AGN as Agent>::Output: Serialize + for<'de> Deserialize<'de>,
+{
+    type Agent = AGN;
+
+    fn spawn_or_join(callback: Option<Callback<AGN::Output>>) -> Box<dyn Bridge<AGN>> {
+        Self::spawn(callback, BridgeOptions::default())
+    }
+
+    fn spawn_or_join_with_options(
+        callback: Option<Callback<AGN::Output>>,
+        options: BridgeOptions,
+    ) -> Box<dyn Bridge<AGN>> {
+        Self::spawn(callback, options)
+    }
+}
+
+impl<AGN, CODEC: Codec> WorkerReach for Private<AGN, CODEC> {
+    type Codec = CODEC;
+}
+
+/// Handler of the termination of the worker of a private bridge
+type Terminate = Rc<dyn Fn()>;
+
+/// What a private bridge needs to spawn its worker again, after it crashed
+struct Spawn<OUT, HNDL> {
+    id: usize,
+    handler_cell: Rc<RefCell<Option<HNDL>>>,
+    workers: Weak<WorkerSet>,
+    calls: PendingCalls<OUT>,
+    on_error: Option<Callback<CodecError>>,
+    on_status: Option<Callback<WorkerStatus>>,
+}
+
+/// Spawns the given generation of the worker of a private bridge, which is restarted after a
+/// crash, if the restart policy of the agent allows it
+fn spawn_worker<AGN, CODEC, HNDL>(
+    spawn: Rc<Spawn<AGN::Output, HNDL>>,
+    generation: u32,
+) -> SpawnedWorker
+where
+    AGN: Agent,
+    CODEC: Codec + 'static,
+    <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
+    <AGN as Agent>::Output: Serialize + for<'de> Deserialize<'de>,
+    HNDL: Fn(Packet, &RemoteWorker, &Terminate) + 'static,
+{
+    let on_error = {
+        let spawn = spawn.clone();
+        move |error: WorkerError| {
+            let loaded = QUEUE.with(|queue| queue.is_worker_loaded(&spawn.id));
+            if error.is_crash(loaded) {
+                handle_crash::<AGN, CODEC, HNDL>(&spawn, generation, error.message);
+            } else {
+                report_status(&spawn.on_status, WorkerStatus::Error(error.message));
+            }
+        }
+    };
+    let on_terminate: Terminate = {
+        let on_error = on_error.clone();
+        Rc::new(move || on_error(WorkerError::terminated()))
+    };
+    let handler = {
+        let handler_cell = spawn.handler_cell.clone();
+        move |packet: Packet, worker: &RemoteWorker| {
+            if let Some(handler) = handler_cell.borrow().as_ref() {
+                handler(packet, worker, &on_terminate)
+            }
+        }
+    };
+    SpawnedWorker::new(RemoteWorker::dedicated::<AGN>(), handler, on_error)
+}
+
+/// Reports a crash of the worker of a private bridge, fails its calls and streams and restarts
+/// the worker, if the restart policy of the agent allows it.
+///
+/// Errors of workers of an older generation, which crashed already, are ignored.
+fn handle_crash<AGN, CODEC, HNDL>(
+    spawn: &Rc<Spawn<AGN::Output, HNDL>>,
+    generation: u32,
+    message: String,
+) where
+    AGN: Agent,
+    CODEC: Codec + 'static,
+    <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
+    <AGN as Agent>::Output: Serialize + for<'de> Deserialize<'de>,
+    HNDL: Fn(Packet, &RemoteWorker, &Terminate) + 'static,
+{
+    let workers = match spawn.workers.upgrade() {
+        Some(workers) => workers,
+        None => return,
+    };
+    let crash = match workers.crash(0, generation) {
+        Some(crash) => crash,
+        None => return,
+    };
+    QUEUE.with(|queue| {
+        queue.remove_agent(&spawn.id);
+    });
+    spawn.calls.fail_all(CallError::Crashed(message.clone()));
+    report_status(&spawn.on_status, WorkerStatus::Crashed(message));
+    if crash.restart {
+        let worker = spawn_worker::<AGN, CODEC, HNDL>(spawn.clone(), workers.generation(0));
+        workers.restart(0, worker);
+        let connected = ToWorker::Connected(SINGLETON_ID);
+        send_to_worker::<AGN, CODEC>(spawn.id, &workers, connected, spawn.on_error.as_ref());
+        report_status(&spawn.on_status, WorkerStatus::Restarted);
+    }
+}
+
+/// Send a message to the worker of the private bridge with `id`, queuing the message if
+/// necessary.
+///
+/// The message is dropped, if the worker crashed and was not restarted, or if it can't be encoded.
+fn send_to_worker<AGN, CODEC>(
+    id: usize,
+    workers: &WorkerSet,
+    msg: ToWorker<AGN::Input>,
+    on_error: Option<&Callback<CodecError>>,
+) where
+    AGN: Agent,
+    CODEC: Codec + 'static,
+    <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
+{
+    let worker = match workers.get(0) {
+        Some(worker) => worker,
+        None => return,
+    };
+    let packet = match encode_to_worker::<AGN, CODEC>(&msg, on_error) {
+        Some(packet) => packet,
+        None => return,
+    };
+    QUEUE.with(|queue| {
+        if queue.is_worker_loaded(&id) {
+            worker.post_packet(packet);
+        } else {
+            queue.add_msg_to_queue(packet, id);
+        }
+    });
+}
+
 /// A connection manager for components interaction with workers.
-pub struct PrivateBridge<AGN, HNDL>
+pub struct PrivateBridge<AGN, CODEC, HNDL>
 where
     AGN: Agent,
+    CODEC: Codec + 'static,
     <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
     <AGN as Agent>::Output: Serialize + for<'de> Deserialize<'de>,
-    HNDL: Fn(Vec<u8>, &Worker),
+    HNDL: Fn(Packet, &RemoteWorker, &Terminate),
 {
     handler_cell: Rc<RefCell<Option<HNDL>>>,
-    worker: Worker,
+    workers: Rc<WorkerSet>,
+    calls: PendingCalls<AGN::Output>,
+    on_error: Option<Callback<CodecError>>,
     _agent: PhantomData<AGN>,
+    _codec: PhantomData<CODEC>,
     id: usize,
 }
 
-impl<AGN, HNDL> PrivateBridge<AGN, HNDL>
+impl<AGN, CODEC, HNDL> PrivateBridge<AGN, CODEC, HNDL>
 where
     AGN: Agent,
+    CODEC: Codec + 'static,
     <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
     <AGN as Agent>::Output: Serialize + for<'de> Deserialize<'de>,
-    HNDL: Fn(Vec<u8>, &Worker),
+    HNDL: Fn(Packet, &RemoteWorker, &Terminate),
 {
-    /// Send a message to the worker, queuing the message if necessary
+    /// Send a message to the worker, queuing the message if necessary.
     fn send_message(&self, msg: ToWorker<AGN::Input>) {
-        QUEUE.with(|queue| {
-            if queue.is_worker_loaded(&self.id) {
-                send_to_remote::<AGN>(&self.worker, msg);
-            } else {
-                queue.add_msg_to_queue(msg.pack(), self.id);
-            }
-        });
+        send_to_worker::<AGN, CODEC>(self.id, &self.workers, msg, self.on_error.as_ref());
+    }
+
+    /// Send the input of a call or stream to the worker, or fail it, if the worker crashed and
+    /// was not restarted
+    fn send_request(&self, id: HandlerId, msg: AGN::Input) {
+        if self.workers.get(0).is_some() {
+            self.send_message(ToWorker::ProcessInput(id, msg));
+        } else {
+            self.calls.fail(id, no_worker_left());
+        }
+    }
+
+    /// Returns the controller of a call or stream to the worker
+    fn controller(&self) -> Controller {
+        let id = self.id;
+        let workers = self.workers.clone();
+        let on_error = self.on_error.clone();
+        Rc::new(move |request_id, control| {
+            let msg = ToWorker::control(request_id, control);
+            send_to_worker::<AGN, CODEC>(id, &workers, msg, on_error.as_ref());
+        })
     }
 }
 
-impl<AGN, HNDL> fmt::Debug for PrivateBridge<AGN, HNDL>
+impl<AGN, CODEC, HNDL> fmt::Debug for PrivateBridge<AGN, CODEC, HNDL>
 where
     AGN: Agent,
+    CODEC: Codec + 'static,
     <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
     <AGN as Agent>::Output: Serialize + for<'de> Deserialize<'de>,
-    HNDL: Fn(Vec<u8>, &Worker),
+    HNDL: Fn(Packet, &RemoteWorker, &Terminate),
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_str("PrivateBridge<_>")
     }
 }
 
-impl<AGN, HNDL> Bridge<AGN> for PrivateBridge<AGN, HNDL>
+impl<AGN, CODEC, HNDL> Bridge<AGN> for PrivateBridge<AGN, CODEC, HNDL>
 where
     AGN: Agent,
+    CODEC: Codec + 'static,
     <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
     <AGN as Agent>::Output: Serialize + for<'de> Deserialize<'de>,
-    HNDL: Fn(Vec<u8>, &Worker),
+    HNDL: Fn(Packet, &RemoteWorker, &Terminate),
 {
     fn send(&mut self, msg: AGN::Input) {
         let msg = ToWorker::ProcessInput(SINGLETON_ID, msg);
         self.send_message(msg);
     }
+
+    fn call(&mut self, msg: AGN::Input) -> AgentCall<AGN::Output> {
+        let (id, call) = self.calls.register(SINGLETON_ID, self.controller());
+        self.send_request(id, msg);
+        call
+    }
+
+    fn stream(&mut self, msg: AGN::Input) -> AgentStream<AGN::Output> {
+        let window = stream_window::<AGN>();
+        let (id, stream) = self
+            .calls
+            .register_stream(SINGLETON_ID, self.controller(), window);
+        self.send_request(id, msg);
+        stream
+    }
+
+    fn cancel_on_drop(&mut self) {
+        self.calls.cancel_on_drop();
+    }
 }
 
-impl<AGN, HNDL> Drop for PrivateBridge<AGN, HNDL>
+impl<AGN, CODEC, HNDL> Drop for PrivateBridge<AGN, CODEC, HNDL>
 where
     AGN: Agent,
+    CODEC: Codec + 'static,
     <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
     <AGN as Agent>::Output: Serialize + for<'de> Deserialize<'de>,
-    HNDL: Fn(Vec<u8>, &Worker),
+    HNDL: Fn(Packet, &RemoteWorker, &Terminate),
 {
     fn drop(&mut self) {
-        let disconnected = ToWorker::Disconnected(SINGLETON_ID);
-        send_to_remote::<AGN>(&self.worker, disconnected);
+        self.calls.close();
+
+        if let Some(worker) = self.workers.get(0) {
+            let on_error = self.on_error.as_ref();
+            let disconnected = ToWorker::Disconnected(SINGLETON_ID);
+            send_to_remote::<AGN, CODEC>(&worker, disconnected, on_error);
 
-        let destroy = ToWorker::Destroy;
-        send_to_remote::<AGN>(&self.worker, destroy);
+            let destroy = ToWorker::Destroy;
+            send_to_remote::<AGN, CODEC>(&worker, destroy, on_error);
+        }
 
         self.handler_cell.borrow_mut().take();
 
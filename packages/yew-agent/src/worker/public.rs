@@ -5,106 +5,417 @@ use queue::Queue;
 use slab::Slab;
 use std::any::TypeId;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fmt;
 use std::marker::PhantomData;
 use std::rc::Rc;
-use web_sys::Worker;
 use yew::callback::Callback;
 use yew::scheduler::Shared;
 
 thread_local! {
     static REMOTE_AGENTS_POOL: RefCell<AnyMap> = RefCell::new(AnyMap::new());
-    static QUEUE: Queue<TypeId> = Queue::new();
+    static QUEUE: Queue<(TypeId, usize)> = Queue::new();
+}
+
+/// Error callbacks of the bridges to a worker by the raw id of their handler
+type SharedErrorCallbacks = Shared<HashMap<usize, Callback<CodecError>>>;
+
+/// Status callbacks of the bridges to a worker by the raw id of their handler
+type SharedStatusCallbacks = Shared<HashMap<usize, Callback<WorkerStatus>>>;
+
+/// The handlers of the bridges to an agent, shared with the handlers of its workers
+struct Handlers<AGN: Agent> {
+    slab: SharedOutputSlab<AGN>,
+    errors: SharedErrorCallbacks,
+    statuses: SharedStatusCallbacks,
+}
+
+impl<AGN: Agent> Clone for Handlers<AGN> {
+    fn clone(&self) -> Self {
+        Self {
+            slab: self.slab.clone(),
+            errors: self.errors.clone(),
+            statuses: self.statuses.clone(),
+        }
+    }
 }
 
 /// Create a single instance in a tab.
+///
+/// Messages are encoded with `CODEC`.
 #[allow(missing_debug_implementations)]
-pub struct Public<AGN> {
+pub struct Public<AGN, CODEC = Bincode> {
     _agent: PhantomData<AGN>,
+    _codec: PhantomData<CODEC>,
 }
 
-impl<AGN> Discoverer for Public<AGN>
+/// Create a pool of `N` workers in a tab, which share the inputs of all bridges.
+///
+/// Every input is handled by the worker with the fewest unfinished calls and streams, so CPU-bound
+/// agents can use several cores. Inputs sent with [`Bridge::send`] are handed to the workers in
+/// turn, as the pool can't tell when the agent is done with them. Each worker runs its own instance
+/// of the agent, which is connected to all bridges, and its responses are routed back to the
+/// bridge, that sent the input.
+///
+/// A pool needs at least one worker, so `N` must not be zero:
+///
+/// ```compile_fail
+/// # use yew::callback::Callback;
+/// # use yew_agent::{Agent, AgentLink, Bridged, HandlerId, Pool};
+/// struct Worker;
+///
+/// impl Agent for Worker {
+///     type Reach = Pool<Self, 0>;
+///     type Message = ();
+///     type Input = ();
+///     type Output = ();
+///
+///     fn create(_link: AgentLink<Self>) -> Self {
+///         Self
+///     }
+///
+///     fn update(&mut self, _msg: Self::Message) {}
+///
+///     fn handle_input(&mut self, _msg: Self::Input, _id: HandlerId) {}
+/// }
+///
+/// Worker::bridge(Callback::noop());
+/// ```
+///
+/// Messages are encoded with `CODEC`. See [`Pool`] for pools using the default codec.
+#[allow(missing_debug_implementations)]
+pub struct PoolWithCodec<AGN, CODEC, const N: usize> {
+    _agent: PhantomData<AGN>,
+    _codec: PhantomData<CODEC>,
+}
+
+/// Create a pool of `N` workers in a tab, which share the inputs of all bridges.
+///
+/// See [`PoolWithCodec`].
+pub type Pool<AGN, const N: usize> = PoolWithCodec<AGN, Bincode, N>;
+
+/// Spawns `count` workers running the agent with `new_worker`, or joins them if they are running
+/// already
+pub(super) fn spawn_or_join_workers<AGN, CODEC>(
+    count: usize,
+    new_worker: fn() -> RemoteWorker,
+    callback: Option<Callback<AGN::Output>>,
+    options: BridgeOptions,
+) -> Box<dyn Bridge<AGN>>
 where
     AGN: Agent,
+    CODEC: Codec + 'static,
     <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
     <AGN as Agent>::Output: Serialize + for<'de> Deserialize<'de>,
 {
-    type Agent = AGN;
+    assert!(count > 0, "an agent needs at least one worker");
+    let bridge = REMOTE_AGENTS_POOL.with(|pool| {
+        let mut pool = pool.borrow_mut();
+        match pool.entry::<RemoteAgent<AGN, CODEC>>() {
+            anymap2::Entry::Occupied(mut entry) => entry.get_mut().create_bridge(callback, options),
+            anymap2::Entry::Vacant(entry) => {
+                let handlers = Handlers {
+                    slab: Rc::new(RefCell::new(Slab::new())),
+                    errors: Rc::new(RefCell::new(HashMap::new())),
+                    statuses: Rc::new(RefCell::new(HashMap::new())),
+                };
+                let workers = WorkerSet::new(AGN::restart_policy());
+                for index in 0..count {
+                    let worker =
+                        spawn_worker::<AGN, CODEC>(index, 0, new_worker, &workers, &handlers);
+                    workers.push(worker);
+                }
+                let launched = RemoteAgent::new(workers, handlers);
+                entry.insert(launched).create_bridge(callback, options)
+            }
+        }
+    });
+    Box::new(bridge)
+}
 
-    fn spawn_or_join(callback: Option<Callback<AGN::Output>>) -> Box<dyn Bridge<AGN>> {
-        let bridge = REMOTE_AGENTS_POOL.with(|pool| {
-            let mut pool = pool.borrow_mut();
-            match pool.entry::<RemoteAgent<AGN>>() {
-                anymap2::Entry::Occupied(mut entry) => entry.get_mut().create_bridge(callback),
-                anymap2::Entry::Vacant(entry) => {
-                    let slab: Shared<Slab<Option<Callback<AGN::Output>>>> =
-                        Rc::new(RefCell::new(Slab::new()));
-                    let handler = {
-                        let slab = slab.clone();
-                        move |data: Vec<u8>, worker: &Worker| {
-                            let msg = FromWorker::<AGN::Output>::unpack(&data);
-                            match msg {
-                                FromWorker::WorkerLoaded => {
-                                    QUEUE.with(|queue| {
-                                        queue.insert_loaded_agent(TypeId::of::<AGN>());
-
-                                        if let Some(msgs) =
-                                            queue.remove_msg_queue(&TypeId::of::<AGN>())
-                                        {
-                                            for msg in msgs {
-                                                worker.post_message_vec(msg)
-                                            }
-                                        }
-                                    });
-                                }
-                                FromWorker::ProcessOutput(id, output) => {
-                                    locate_callback_and_respond::<AGN>(&slab, id, output);
-                                }
+/// Spawns the worker at `index` of the workers running the agent, which is the given generation
+/// of workers at this index
+fn spawn_worker<AGN, CODEC>(
+    index: usize,
+    generation: u32,
+    new_worker: fn() -> RemoteWorker,
+    workers: &Rc<WorkerSet>,
+    handlers: &Handlers<AGN>,
+) -> SpawnedWorker
+where
+    AGN: Agent,
+    CODEC: Codec + 'static,
+    <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
+    <AGN as Agent>::Output: Serialize + for<'de> Deserialize<'de>,
+{
+    let key = (TypeId::of::<AGN>(), index);
+    let on_error = {
+        let workers = Rc::downgrade(workers);
+        let handlers = handlers.clone();
+        move |error: WorkerError| {
+            let loaded = QUEUE.with(|queue| queue.is_worker_loaded(&key));
+            if !error.is_crash(loaded) {
+                let statuses: Vec<_> = handlers.statuses.borrow().values().cloned().collect();
+                report_status(&statuses, WorkerStatus::Error(error.message));
+            } else if let Some(workers) = workers.upgrade() {
+                handle_crash::<AGN, CODEC>(
+                    index,
+                    generation,
+                    new_worker,
+                    &workers,
+                    &handlers,
+                    error.message,
+                );
+            }
+        }
+    };
+    let handler = {
+        let handlers = handlers.clone();
+        let workers = Rc::downgrade(workers);
+        let on_error = on_error.clone();
+        move |packet: Packet, worker: &RemoteWorker| {
+            let msg = match packet.decode::<CODEC, FromWorker<AGN::Output>>() {
+                Ok(msg) => msg,
+                Err(error) => {
+                    report_error_to_all(&handlers.errors, error);
+                    return;
+                }
+            };
+            match msg {
+                FromWorker::WorkerLoaded(lock) => {
+                    QUEUE.with(|queue| {
+                        queue.insert_loaded_agent(key);
+
+                        if let Some(msgs) = queue.remove_msg_queue(&key) {
+                            for msg in msgs {
+                                worker.post_packet(msg)
                             }
                         }
-                    };
-                    let name_of_resource = AGN::name_of_resource();
-                    let is_relative = AGN::resource_path_is_relative();
-                    let worker = {
-                        let worker = worker_new(name_of_resource, is_relative, AGN::is_module());
-                        let worker_clone = worker.clone();
-                        worker.set_onmessage_closure(move |data: Vec<u8>| {
-                            handler(data, &worker_clone);
-                        });
-                        worker
-                    };
-                    let launched = RemoteAgent::new(worker, slab);
-                    entry.insert(launched).create_bridge(callback)
+                    });
+                    if let Some(lock) = lock {
+                        let on_error = on_error.clone();
+                        watch_lock(&lock, move || on_error(WorkerError::terminated()));
+                    }
+                }
+                FromWorker::ProcessOutput(id, output) => {
+                    if let Some(workers) = workers.upgrade() {
+                        workers.respond(id);
+                    }
+                    locate_callback_and_respond::<AGN>(&handlers.slab, id, output);
+                }
+                FromWorker::StreamEnd(id) => {
+                    if let Some(workers) = workers.upgrade() {
+                        workers.finish(id);
+                    }
+                    locate_stream_and_end::<AGN>(&handlers.slab, id);
+                }
+                FromWorker::Error(Some(id), error) => {
+                    let on_error = handlers.errors.borrow().get(&id.raw_id()).cloned();
+                    report_error(on_error.as_ref(), error);
+                }
+                FromWorker::Error(None, error) => {
+                    report_error_to_all(&handlers.errors, error);
                 }
             }
-        });
-        Box::new(bridge)
+        }
+    };
+    SpawnedWorker::new(new_worker(), handler, on_error)
+}
+
+/// Reports a crash of the worker at `index` to all bridges, fails its calls and streams and
+/// restarts the worker, if the restart policy of the agent allows it.
+///
+/// Errors of workers of an older generation, which crashed already, are ignored.
+fn handle_crash<AGN, CODEC>(
+    index: usize,
+    generation: u32,
+    new_worker: fn() -> RemoteWorker,
+    workers: &Rc<WorkerSet>,
+    handlers: &Handlers<AGN>,
+    message: String,
+) where
+    AGN: Agent,
+    CODEC: Codec + 'static,
+    <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
+    <AGN as Agent>::Output: Serialize + for<'de> Deserialize<'de>,
+{
+    let crash = match workers.crash(index, generation) {
+        Some(crash) => crash,
+        None => return,
+    };
+    let key = (TypeId::of::<AGN>(), index);
+    QUEUE.with(|queue| {
+        queue.remove_agent(&key);
+    });
+    for id in crash.requests {
+        locate_call_and_fail::<AGN>(&handlers.slab, id, CallError::Crashed(message.clone()));
+    }
+    let statuses: Vec<_> = handlers.statuses.borrow().values().cloned().collect();
+    report_status(&statuses, WorkerStatus::Crashed(message));
+    if !crash.restart {
+        return;
+    }
+
+    let generation = workers.generation(index);
+    let worker = spawn_worker::<AGN, CODEC>(index, generation, new_worker, workers, handlers);
+    workers.restart(index, worker);
+    // Connects the live handlers to the new instance of the agent, once it has loaded
+    let ids: Vec<HandlerId> = handlers
+        .slab
+        .borrow()
+        .iter()
+        .map(|(id, handler)| HandlerId::new(id, handler.callback.is_some()))
+        .collect();
+    for id in ids {
+        let on_error = handlers.errors.borrow().get(&id.raw_id()).cloned();
+        send_to_worker::<AGN, CODEC>(workers, index, ToWorker::Connected(id), on_error.as_ref());
     }
+    report_status(&statuses, WorkerStatus::Restarted);
 }
 
-impl<AGN> Dispatchable for Public<AGN>
+impl<AGN, CODEC> Discoverer for Public<AGN, CODEC>
 where
     AGN: Agent,
+    CODEC: Codec + 'static,
     <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
     <AGN as Agent>::Output: Serialize + for<'de> Deserialize<'de>,
 {
+    type Agent = AGN;
+
+    fn spawn_or_join(callback: Option<Callback<AGN::Output>>) -> Box<dyn Bridge<AGN>> {
+        Self::spawn_or_join_with_options(callback, BridgeOptions::default())
+    }
+
+    fn spawn_or_join_with_options(
+        callback: Option<Callback<AGN::Output>>,
+        options: BridgeOptions,
+    ) -> Box<dyn Bridge<AGN>> {
+        spawn_or_join_workers::<AGN, CODEC>(1, RemoteWorker::dedicated::<AGN>, callback, options)
+    }
+}
+
+impl<AGN, CODEC> Dispatchable for Public<AGN, CODEC>
+where
+    AGN: Agent,
+    CODEC: Codec + 'static,
+    <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
+    <AGN as Agent>::Output: Serialize + for<'de> Deserialize<'de>,
+{
+}
+
+impl<AGN, CODEC: Codec> WorkerReach for Public<AGN, CODEC> {
+    type Codec = CODEC;
+}
+
+impl<AGN, CODEC, const N: usize> PoolWithCodec<AGN, CODEC, N> {
+    /// Number of workers of the pool.
+    ///
+    /// Evaluating it fails to compile for a pool without workers, as `N - 1` overflows.
+    const WORKERS: usize = N - 1 + 1;
+}
+
+impl<AGN, CODEC, const N: usize> Discoverer for PoolWithCodec<AGN, CODEC, N>
+where
+    AGN: Agent,
+    CODEC: Codec + 'static,
+    <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
+    <AGN as Agent>::Output: Serialize + for<'de> Deserialize<'de>,
+{
+    type Agent = AGN;
+
+    fn spawn_or_join(callback: Option<Callback<AGN::Output>>) -> Box<dyn Bridge<AGN>> {
+        Self::spawn_or_join_with_options(callback, BridgeOptions::default())
+    }
+
+    fn spawn_or_join_with_options(
+        callback: Option<Callback<AGN::Output>>,
+        options: BridgeOptions,
+    ) -> Box<dyn Bridge<AGN>> {
+        spawn_or_join_workers::<AGN, CODEC>(
+            Self::WORKERS,
+            RemoteWorker::dedicated::<AGN>,
+            callback,
+            options,
+        )
+    }
+}
+
+impl<AGN, CODEC, const N: usize> Dispatchable for PoolWithCodec<AGN, CODEC, N>
+where
+    AGN: Agent,
+    CODEC: Codec + 'static,
+    <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
+    <AGN as Agent>::Output: Serialize + for<'de> Deserialize<'de>,
+{
+}
+
+impl<AGN, CODEC: Codec, const N: usize> WorkerReach for PoolWithCodec<AGN, CODEC, N> {
+    type Codec = CODEC;
+}
+
+/// Send a message to the worker at `index`, queuing the message if necessary.
+///
+/// The message is dropped, if the worker crashed and was not restarted, or if it can't be encoded.
+fn send_to_worker<AGN, CODEC>(
+    workers: &WorkerSet,
+    index: usize,
+    msg: ToWorker<AGN::Input>,
+    on_error: Option<&Callback<CodecError>>,
+) where
+    AGN: Agent,
+    CODEC: Codec + 'static,
+    <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
+{
+    let key = (TypeId::of::<AGN>(), index);
+    let worker = match workers.get(index) {
+        Some(worker) => worker,
+        None => return,
+    };
+    let packet = match encode_to_worker::<AGN, CODEC>(&msg, on_error) {
+        Some(packet) => packet,
+        None => return,
+    };
+    QUEUE.with(|queue| {
+        if queue.is_worker_loaded(&key) {
+            worker.post_packet(packet);
+        } else {
+            queue.add_msg_to_queue(packet, key);
+        }
+    });
+}
+
+/// Reports a message, that could not be encoded or decoded, to all bridges of a worker
+fn report_error_to_all(errors: &SharedErrorCallbacks, error: CodecError) {
+    let callbacks: Vec<_> = errors.borrow().values().cloned().collect();
+    if callbacks.is_empty() {
+        report_error(None, error);
+    } else {
+        for callback in callbacks {
+            callback.emit(error.clone());
+        }
+    }
 }
 
 /// A connection manager for components interaction with workers.
-pub struct PublicBridge<AGN>
+pub struct PublicBridge<AGN, CODEC>
 where
     AGN: Agent,
+    CODEC: Codec + 'static,
     <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
     <AGN as Agent>::Output: Serialize + for<'de> Deserialize<'de>,
 {
-    worker: Worker,
+    workers: Rc<WorkerSet>,
     id: HandlerId,
+    calls: PendingCalls<AGN::Output>,
+    on_error: Option<Callback<CodecError>>,
     _agent: PhantomData<AGN>,
+    _codec: PhantomData<CODEC>,
 }
 
-impl<AGN> fmt::Debug for PublicBridge<AGN>
+impl<AGN, CODEC> fmt::Debug for PublicBridge<AGN, CODEC>
 where
     AGN: Agent,
+    CODEC: Codec + 'static,
     <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
     <AGN as Agent>::Output: Serialize + for<'de> Deserialize<'de>,
 {
@@ -113,47 +424,109 @@ where
     }
 }
 
-impl<AGN> PublicBridge<AGN>
+impl<AGN, CODEC> PublicBridge<AGN, CODEC>
 where
     AGN: Agent,
+    CODEC: Codec + 'static,
     <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
     <AGN as Agent>::Output: Serialize + for<'de> Deserialize<'de>,
 {
-    /// Send a message to the worker, queuing the message if necessary
-    fn send_message(&self, msg: ToWorker<AGN::Input>) {
-        QUEUE.with(|queue| {
-            if queue.is_worker_loaded(&TypeId::of::<AGN>()) {
-                send_to_remote::<AGN>(&self.worker, msg);
-            } else {
-                queue.add_msg_to_queue(msg.pack(), TypeId::of::<AGN>());
+    /// Send a message to the worker at `index`, queuing the message if necessary.
+    fn send_message(&self, index: usize, msg: ToWorker<AGN::Input>) {
+        send_to_worker::<AGN, CODEC>(&self.workers, index, msg, self.on_error.as_ref());
+    }
+
+    /// Send a message to all workers
+    fn broadcast_message(&self, msg: impl Fn() -> ToWorker<AGN::Input>) {
+        for index in 0..self.workers.len() {
+            self.send_message(index, msg());
+        }
+    }
+
+    /// Send an input to the next worker in turn
+    fn send_input(&self, id: HandlerId, msg: AGN::Input) {
+        if let Some(index) = self.workers.next_index() {
+            self.send_message(index, ToWorker::ProcessInput(id, msg));
+        }
+    }
+
+    /// Returns the index of the worker, that handles the next input, and the controller of a call
+    /// or stream to it
+    fn next_worker(&self) -> (Option<usize>, Controller) {
+        let index = self.workers.next_index();
+        let workers = self.workers.clone();
+        let on_error = self.on_error.clone();
+        let controller = move |id, control| {
+            if let Some(index) = index {
+                // The agent may never respond to a cancelled call
+                if control == Control::Cancel {
+                    workers.finish(id);
+                }
+                let msg = ToWorker::control(id, control);
+                send_to_worker::<AGN, CODEC>(&workers, index, msg, on_error.as_ref());
             }
-        });
+        };
+        (index, Rc::new(controller))
+    }
+
+    /// Send an input of a call or stream to the worker at `index`, or fail it, if all workers
+    /// crashed and were not restarted
+    fn send_request(&self, index: Option<usize>, id: HandlerId, msg: AGN::Input, stream: bool) {
+        match index {
+            Some(index) => {
+                self.workers.start(index, id, stream);
+                self.send_message(index, ToWorker::ProcessInput(id, msg));
+            }
+            None => self.calls.fail(id, no_worker_left()),
+        }
     }
 }
 
-impl<AGN> Bridge<AGN> for PublicBridge<AGN>
+impl<AGN, CODEC> Bridge<AGN> for PublicBridge<AGN, CODEC>
 where
     AGN: Agent,
+    CODEC: Codec + 'static,
     <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
     <AGN as Agent>::Output: Serialize + for<'de> Deserialize<'de>,
 {
     fn send(&mut self, msg: AGN::Input) {
-        let msg = ToWorker::ProcessInput(self.id, msg);
-        self.send_message(msg);
+        self.send_input(self.id, msg);
+    }
+
+    fn call(&mut self, msg: AGN::Input) -> AgentCall<AGN::Output> {
+        let (index, controller) = self.next_worker();
+        let (id, call) = self.calls.register(self.id, controller);
+        self.send_request(index, id, msg, false);
+        call
+    }
+
+    fn stream(&mut self, msg: AGN::Input) -> AgentStream<AGN::Output> {
+        let (index, controller) = self.next_worker();
+        let window = stream_window::<AGN>();
+        let (id, stream) = self.calls.register_stream(self.id, controller, window);
+        self.send_request(index, id, msg, true);
+        stream
+    }
+
+    fn cancel_on_drop(&mut self) {
+        self.calls.cancel_on_drop();
     }
 }
 
-impl<AGN> Drop for PublicBridge<AGN>
+impl<AGN, CODEC> Drop for PublicBridge<AGN, CODEC>
 where
     AGN: Agent,
+    CODEC: Codec + 'static,
     <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
     <AGN as Agent>::Output: Serialize + for<'de> Deserialize<'de>,
 {
     fn drop(&mut self) {
+        self.calls.close();
+
         let terminate_worker = REMOTE_AGENTS_POOL.with(|pool| {
             let mut pool = pool.borrow_mut();
             let terminate_worker = {
-                if let Some(launched) = pool.get_mut::<RemoteAgent<AGN>>() {
+                if let Some(launched) = pool.get_mut::<RemoteAgent<AGN, CODEC>>() {
                     launched.remove_bridge(self)
                 } else {
                     false
@@ -161,122 +534,102 @@ where
             };
 
             if terminate_worker {
-                pool.remove::<RemoteAgent<AGN>>();
+                pool.remove::<RemoteAgent<AGN, CODEC>>();
             }
 
             terminate_worker
         });
 
-        let disconnected = ToWorker::Disconnected(self.id);
-        self.send_message(disconnected);
+        let id = self.id;
+        self.workers.disconnect(id);
+        self.broadcast_message(|| ToWorker::Disconnected(id));
 
         if terminate_worker {
-            let destroy = ToWorker::Destroy;
-            self.send_message(destroy);
+            self.broadcast_message(|| ToWorker::Destroy);
 
             QUEUE.with(|queue| {
-                queue.remove_agent(&TypeId::of::<AGN>());
+                for index in 0..self.workers.len() {
+                    queue.remove_agent(&(TypeId::of::<AGN>(), index));
+                }
             });
         }
     }
 }
 
-struct WorkerResponder {}
-
-impl<AGN> Responder<AGN> for WorkerResponder
-where
-    AGN: Agent,
-    <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
-    <AGN as Agent>::Output: Serialize + for<'de> Deserialize<'de>,
-{
-    fn respond(&self, id: HandlerId, output: AGN::Output) {
-        let msg = FromWorker::ProcessOutput(id, output);
-        let data = msg.pack();
-        worker_self().post_message_vec(data);
-    }
-}
-
-impl<AGN> Threaded for AGN
-where
-    AGN: Agent<Reach = Public<AGN>>,
-    <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
-    <AGN as Agent>::Output: Serialize + for<'de> Deserialize<'de>,
-{
-    fn register() {
-        let scope = AgentScope::<AGN>::new();
-        let responder = WorkerResponder {};
-        let link = AgentLink::connect(&scope, responder);
-        let upd = AgentLifecycleEvent::Create(link);
-        scope.send(upd);
-        let handler = move |data: Vec<u8>| {
-            let msg = ToWorker::<AGN::Input>::unpack(&data);
-            match msg {
-                ToWorker::Connected(id) => {
-                    let upd = AgentLifecycleEvent::Connected(id);
-                    scope.send(upd);
-                }
-                ToWorker::ProcessInput(id, value) => {
-                    let upd = AgentLifecycleEvent::Input(value, id);
-                    scope.send(upd);
-                }
-                ToWorker::Disconnected(id) => {
-                    let upd = AgentLifecycleEvent::Disconnected(id);
-                    scope.send(upd);
-                }
-                ToWorker::Destroy => {
-                    let upd = AgentLifecycleEvent::Destroy;
-                    scope.send(upd);
-                    // Terminates web worker
-                    worker_self().close();
-                }
-            }
-        };
-        let loaded: FromWorker<AGN::Output> = FromWorker::WorkerLoaded;
-        let loaded = loaded.pack();
-        let worker = worker_self();
-        worker.set_onmessage_closure(handler);
-        worker.post_message_vec(loaded);
-    }
-}
-
-struct RemoteAgent<AGN>
+struct RemoteAgent<AGN, CODEC>
 where
     AGN: Agent,
+    CODEC: Codec + 'static,
     <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
     <AGN as Agent>::Output: Serialize + for<'de> Deserialize<'de>,
 {
-    worker: Worker,
-    slab: SharedOutputSlab<AGN>,
+    workers: Rc<WorkerSet>,
+    handlers: Handlers<AGN>,
+    _codec: PhantomData<CODEC>,
 }
 
-impl<AGN> RemoteAgent<AGN>
+impl<AGN, CODEC> RemoteAgent<AGN, CODEC>
 where
     AGN: Agent,
+    CODEC: Codec + 'static,
     <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
     <AGN as Agent>::Output: Serialize + for<'de> Deserialize<'de>,
 {
-    pub fn new(worker: Worker, slab: SharedOutputSlab<AGN>) -> Self {
-        RemoteAgent { worker, slab }
+    fn new(workers: Rc<WorkerSet>, handlers: Handlers<AGN>) -> Self {
+        RemoteAgent {
+            workers,
+            handlers,
+            _codec: PhantomData,
+        }
     }
 
-    fn create_bridge(&mut self, callback: Option<Callback<AGN::Output>>) -> PublicBridge<AGN> {
+    fn create_bridge(
+        &mut self,
+        callback: Option<Callback<AGN::Output>>,
+        options: BridgeOptions,
+    ) -> PublicBridge<AGN, CODEC> {
         let respondable = callback.is_some();
-        let mut slab = self.slab.borrow_mut();
-        let id: usize = slab.insert(callback);
+        let calls = PendingCalls::new();
+        let mut slab = self.handlers.slab.borrow_mut();
+        let id: usize = slab.insert(BridgeHandler {
+            callback,
+            calls: calls.clone(),
+        });
+        let BridgeOptions {
+            on_error,
+            on_status,
+        } = options;
+        if let Some(on_error) = on_error.clone() {
+            self.handlers.errors.borrow_mut().insert(id, on_error);
+        }
+        if let Some(on_status) = on_status {
+            self.handlers.statuses.borrow_mut().insert(id, on_status);
+        }
         let id = HandlerId::new(id, respondable);
         let bridge = PublicBridge {
-            worker: self.worker.clone(),
+            workers: self.workers.clone(),
             id,
+            calls,
+            on_error,
             _agent: PhantomData,
+            _codec: PhantomData,
         };
-        bridge.send_message(ToWorker::Connected(bridge.id));
+        bridge.broadcast_message(|| ToWorker::Connected(id));
 
         bridge
     }
 
-    fn remove_bridge(&mut self, bridge: &PublicBridge<AGN>) -> Last {
-        let mut slab = self.slab.borrow_mut();
+    fn remove_bridge(&mut self, bridge: &PublicBridge<AGN, CODEC>) -> Last {
+        let mut slab = self.handlers.slab.borrow_mut();
         let _ = slab.remove(bridge.id.raw_id());
+        self.handlers
+            .errors
+            .borrow_mut()
+            .remove(&bridge.id.raw_id());
+        self.handlers
+            .statuses
+            .borrow_mut()
+            .remove(&bridge.id.raw_id());
         slab.is_empty()
     }
 }
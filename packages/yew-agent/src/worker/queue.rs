@@ -1,3 +1,4 @@
+use super::transfer::Packet;
 use std::cell::RefCell;
 use std::collections::{hash_map, HashMap, HashSet};
 use std::hash::Hash;
@@ -5,7 +6,7 @@ use std::hash::Hash;
 /// Thread-local instance used to queue worker messages
 pub struct Queue<T: Eq + Hash> {
     loaded_agents: RefCell<HashSet<T>>,
-    msg_queue: RefCell<HashMap<T, Vec<Vec<u8>>>>,
+    msg_queue: RefCell<HashMap<T, Vec<Packet>>>,
 }
 
 impl<T: Eq + Hash> Queue<T> {
@@ -17,7 +18,7 @@ impl<T: Eq + Hash> Queue<T> {
     }
 
     #[inline]
-    pub fn remove_msg_queue(&self, id: &T) -> Option<Vec<Vec<u8>>> {
+    pub fn remove_msg_queue(&self, id: &T) -> Option<Vec<Packet>> {
         self.msg_queue.borrow_mut().remove(id)
     }
 
@@ -31,7 +32,7 @@ impl<T: Eq + Hash> Queue<T> {
         self.loaded_agents.borrow().contains(id)
     }
 
-    pub fn add_msg_to_queue(&self, msg: Vec<u8>, id: T) {
+    pub fn add_msg_to_queue(&self, msg: Packet, id: T) {
         let mut queue = self.msg_queue.borrow_mut();
         match queue.entry(id) {
             hash_map::Entry::Vacant(record) => {
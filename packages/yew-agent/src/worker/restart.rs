@@ -0,0 +1,337 @@
+use super::{RemoteWorker, SpawnedWorker};
+use crate::HandlerId;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Whether the worker of an agent is restarted, after it crashed.
+///
+/// A restarted worker runs a new instance of the agent, so all state of the crashed one is lost.
+/// The bridges are connected to the new instance again, but inputs, that the crashed worker did
+/// not respond to, are lost with their calls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestartPolicy {
+    /// The worker is not restarted. Inputs are dropped, unless a pool has other workers left.
+    Never,
+    /// The workers of the agent are restarted at most the given number of times.
+    Limited(u32),
+    /// The worker is restarted after every crash.
+    ///
+    /// Note that a worker, that fails to load, will be restarted over and over again.
+    Always,
+}
+
+/// Status of the worker of an agent, that is reported to the bridges.
+///
+/// A worker crashes, when it fails to load, when its wasm module traps, for example after a panic,
+/// or when it terminates. Browsers without the Web Locks API don't tell, when a worker terminates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorkerStatus {
+    /// The worker crashed with an error message.
+    ///
+    /// It is followed by [`WorkerStatus::Restarted`], if the [`RestartPolicy`] of the agent allows
+    /// to restart the worker.
+    Crashed(String),
+    /// The worker reported an error, that did not crash it, like an uncaught JavaScript exception.
+    Error(String),
+    /// The worker was restarted and the bridges are connected to a new instance of the agent.
+    Restarted,
+}
+
+/// A worker of a [`WorkerSet`], which is terminated, when it crashes
+pub(crate) trait Terminate {
+    fn terminate(&self);
+}
+
+impl Terminate for SpawnedWorker {
+    fn terminate(&self) {
+        self.worker().terminate();
+    }
+}
+
+/// The workers running an agent, shared by its bridges
+pub(crate) struct WorkerSet<W = SpawnedWorker> {
+    workers: RefCell<Vec<W>>,
+    /// Whether the worker at an index crashed and was not restarted
+    crashed: RefCell<Vec<bool>>,
+    /// Number of crashes of the worker at an index, which tells the errors of the running worker
+    /// apart from late errors of crashed ones
+    generations: RefCell<Vec<u32>>,
+    /// Number of calls and streams, that the worker at an index has not finished yet
+    in_flight: RefCell<Vec<usize>>,
+    /// Calls and streams, that have not finished yet, by the raw id of their handler and their
+    /// request
+    requests: RefCell<HashMap<(usize, u64), Request>>,
+    /// Index of the worker, that is preferred for the next input, if several workers have the
+    /// fewest calls and streams in flight
+    next: Cell<usize>,
+    /// Number of crashed workers, that were restarted
+    restarts: Cell<u32>,
+    policy: RestartPolicy,
+}
+
+impl WorkerSet {
+    /// Returns the worker at `index`, unless it crashed
+    pub fn get(&self, index: usize) -> Option<RemoteWorker> {
+        if self.crashed.borrow()[index] {
+            None
+        } else {
+            Some(self.workers.borrow()[index].worker().clone())
+        }
+    }
+}
+
+impl<W: Terminate> WorkerSet<W> {
+    pub fn new(policy: RestartPolicy) -> Rc<Self> {
+        Rc::new(Self {
+            workers: RefCell::new(Vec::new()),
+            crashed: RefCell::new(Vec::new()),
+            generations: RefCell::new(Vec::new()),
+            in_flight: RefCell::new(Vec::new()),
+            requests: RefCell::new(HashMap::new()),
+            next: Cell::new(0),
+            restarts: Cell::new(0),
+            policy,
+        })
+    }
+
+    /// Adds a worker and returns its index
+    pub fn push(&self, worker: W) -> usize {
+        let mut workers = self.workers.borrow_mut();
+        workers.push(worker);
+        self.crashed.borrow_mut().push(false);
+        self.generations.borrow_mut().push(0);
+        self.in_flight.borrow_mut().push(0);
+        workers.len() - 1
+    }
+
+    pub fn len(&self) -> usize {
+        self.workers.borrow().len()
+    }
+
+    /// Returns the generation of the worker at `index`, that is spawned next
+    pub fn generation(&self, index: usize) -> u32 {
+        self.generations.borrow()[index]
+    }
+
+    /// Returns the index of the worker, that handles the next input, skipping crashed workers.
+    ///
+    /// This is the worker with the fewest calls and streams in flight. Ties are broken by taking
+    /// turns, so inputs sent without a call or stream are spread evenly.
+    pub fn next_index(&self) -> Option<usize> {
+        let crashed = self.crashed.borrow();
+        let in_flight = self.in_flight.borrow();
+        let count = crashed.len();
+        let index = (0..count)
+            .map(|offset| (self.next.get() + offset) % count)
+            .filter(|index| !crashed[*index])
+            .min_by_key(|index| in_flight[*index])?;
+        self.next.set((index + 1) % count);
+        Some(index)
+    }
+
+    /// Records, that the input of a call or stream was sent to the worker at `index`
+    pub fn start(&self, index: usize, id: HandlerId, stream: bool) {
+        if let Some(request) = id.request() {
+            self.requests
+                .borrow_mut()
+                .insert((id.raw_id(), request), Request { id, index, stream });
+            self.in_flight.borrow_mut()[index] += 1;
+        }
+    }
+
+    /// Records an output of a worker, which finishes the call, that `id` is tagged with
+    pub fn respond(&self, id: HandlerId) {
+        let request = match id.request() {
+            Some(request) => request,
+            None => return,
+        };
+        let is_call = matches!(
+            self.requests.borrow().get(&(id.raw_id(), request)),
+            Some(request) if !request.stream
+        );
+        if is_call {
+            self.finish(id);
+        }
+    }
+
+    /// Records, that the call or stream, that `id` is tagged with, ended or was cancelled
+    pub fn finish(&self, id: HandlerId) {
+        let request = id
+            .request()
+            .and_then(|request| self.requests.borrow_mut().remove(&(id.raw_id(), request)));
+        if let Some(request) = request {
+            let mut in_flight = self.in_flight.borrow_mut();
+            in_flight[request.index] = in_flight[request.index].saturating_sub(1);
+        }
+    }
+
+    /// Forgets the calls and streams of a handler, that disconnected
+    pub fn disconnect(&self, id: HandlerId) {
+        let mut in_flight = self.in_flight.borrow_mut();
+        self.requests.borrow_mut().retain(|(raw_id, _), request| {
+            let keep = *raw_id != id.raw_id();
+            if !keep {
+                in_flight[request.index] = in_flight[request.index].saturating_sub(1);
+            }
+            keep
+        });
+    }
+
+    /// Terminates the crashed worker at `index`, if it is the given generation.
+    ///
+    /// Returns `None`, if the worker of this generation crashed before already.
+    pub fn crash(&self, index: usize, generation: u32) -> Option<Crash> {
+        if self.crashed.borrow()[index] || self.generations.borrow()[index] != generation {
+            return None;
+        }
+        self.generations.borrow_mut()[index] += 1;
+        self.workers.borrow()[index].terminate();
+        // The calls and streams of the crashed worker are never finished
+        let mut requests = Vec::new();
+        self.requests.borrow_mut().retain(|_, request| {
+            let keep = request.index != index;
+            if !keep {
+                requests.push(request.id);
+            }
+            keep
+        });
+        self.in_flight.borrow_mut()[index] = 0;
+        let restart = match self.policy {
+            RestartPolicy::Never => false,
+            RestartPolicy::Limited(limit) => self.restarts.get() < limit,
+            RestartPolicy::Always => true,
+        };
+        if restart {
+            self.restarts.set(self.restarts.get() + 1);
+        } else {
+            self.crashed.borrow_mut()[index] = true;
+        }
+        Some(Crash { restart, requests })
+    }
+
+    /// Replaces the crashed worker at `index` with a restarted one
+    pub fn restart(&self, index: usize, worker: W) {
+        self.workers.borrow_mut()[index] = worker;
+    }
+}
+
+/// What happens after a worker crashed
+pub(crate) struct Crash {
+    /// Whether the worker may be restarted
+    pub restart: bool,
+    /// Ids of the calls and streams, that the crashed worker did not finish
+    pub requests: Vec<HandlerId>,
+}
+
+/// A call or stream, that a worker has not finished yet
+struct Request {
+    id: HandlerId,
+    /// Index of the worker
+    index: usize,
+    stream: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A worker, that records whether it was terminated
+    #[derive(Default)]
+    struct TestWorker {
+        terminated: Cell<bool>,
+    }
+
+    impl Terminate for TestWorker {
+        fn terminate(&self) {
+            self.terminated.set(true);
+        }
+    }
+
+    fn worker_set(policy: RestartPolicy, count: usize) -> Rc<WorkerSet<TestWorker>> {
+        let workers = WorkerSet::new(policy);
+        for _ in 0..count {
+            workers.push(TestWorker::default());
+        }
+        workers
+    }
+
+    fn request(raw_id: usize, request: u64) -> HandlerId {
+        HandlerId::new(raw_id, true).with_request(request)
+    }
+
+    #[test]
+    fn hands_inputs_to_least_busy_workers() {
+        let workers = worker_set(RestartPolicy::Never, 3);
+        // Inputs without calls take turns
+        let turns: Vec<_> = (0..4).map(|_| workers.next_index()).collect();
+        assert_eq!(turns, vec![Some(0), Some(1), Some(2), Some(0)]);
+
+        workers.start(0, request(1, 0), false);
+        workers.start(1, request(1, 1), true);
+        assert_eq!(workers.next_index(), Some(2));
+        workers.start(2, request(2, 0), false);
+        workers.start(2, request(2, 1), false);
+        assert_eq!(workers.next_index(), Some(0));
+
+        // A response finishes a call, but not a stream
+        workers.respond(request(1, 0));
+        workers.respond(request(1, 1));
+        assert_eq!(*workers.in_flight.borrow(), vec![0, 1, 2]);
+        workers.finish(request(1, 1));
+        assert_eq!(*workers.in_flight.borrow(), vec![0, 0, 2]);
+
+        // Untagged ids and unknown requests are ignored
+        workers.start(0, HandlerId::new(3, true), false);
+        workers.finish(request(1, 1));
+        assert_eq!(*workers.in_flight.borrow(), vec![0, 0, 2]);
+
+        workers.disconnect(HandlerId::new(2, true));
+        assert_eq!(*workers.in_flight.borrow(), vec![0, 0, 0]);
+        assert!(workers.requests.borrow().is_empty());
+    }
+
+    #[test]
+    fn skips_crashed_workers() {
+        let workers = worker_set(RestartPolicy::Never, 2);
+        workers.start(0, request(1, 0), false);
+        workers.start(1, request(1, 1), true);
+
+        let crash = workers.crash(0, 0).unwrap();
+        assert!(!crash.restart);
+        assert_eq!(crash.requests, vec![request(1, 0)]);
+        assert!(workers.workers.borrow()[0].terminated.get());
+        // Late errors of the crashed worker are ignored
+        assert!(workers.crash(0, 0).is_none());
+
+        assert_eq!(workers.next_index(), Some(1));
+        assert_eq!(workers.next_index(), Some(1));
+        assert!(workers.crash(1, 0).is_some());
+        assert_eq!(workers.next_index(), None);
+    }
+
+    #[test]
+    fn restarts_workers_as_allowed() {
+        let workers = worker_set(RestartPolicy::Limited(1), 1);
+        let crash = workers.crash(0, 0).unwrap();
+        assert!(crash.restart);
+        assert!(crash.requests.is_empty());
+        assert_eq!(workers.generation(0), 1);
+        workers.restart(0, TestWorker::default());
+        assert_eq!(workers.next_index(), Some(0));
+
+        // Errors of the crashed generation don't crash the restarted worker
+        assert!(workers.crash(0, 0).is_none());
+        assert!(!workers.workers.borrow()[0].terminated.get());
+
+        let crash = workers.crash(0, 1).unwrap();
+        assert!(!crash.restart);
+        assert_eq!(workers.next_index(), None);
+
+        let workers = worker_set(RestartPolicy::Always, 1);
+        for generation in 0..3 {
+            assert!(workers.crash(0, generation).unwrap().restart);
+            workers.restart(0, TestWorker::default());
+        }
+    }
+}
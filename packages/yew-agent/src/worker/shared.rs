@@ -0,0 +1,302 @@
+use super::public::spawn_or_join_workers;
+use super::*;
+use gloo_console as console;
+use slab::Slab;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use yew::scheduler::Shared;
+
+/// Create a single instance in a worker, that is shared by all tabs of the app.
+///
+/// All tabs bridging to the agent connect to the same worker, so that it can hold resources like
+/// a websocket connection once for the whole app. The agent is destroyed, when the last tab drops
+/// its last bridge. Browsers don't notify shared workers about closed tabs, so the handlers of a
+/// tab, that is closed while it has bridges, stay connected. Browsers also report only errors
+/// while a shared worker is loading, so its crashes at runtime are only detected, if they terminate
+/// the worker.
+///
+/// A shared worker has to be started from the same URL in all tabs, so unlike a dedicated worker
+/// it can't be started by a generated script. [`Agent::name_of_resource`] must name a script, that
+/// keeps the ports of the tabs connecting while the agent loads, and then loads the agent:
+///
+/// ```js
+/// self.yewAgentPorts = [];
+/// self.onconnect = (event) => self.yewAgentPorts.push(event.ports[0]);
+/// importScripts("worker.js");
+/// wasm_bindgen("worker_bg.wasm");
+/// ```
+///
+/// If [`Agent::is_module`] returns `true`, the loader is started as a module:
+///
+/// ```js
+/// self.yewAgentPorts = [];
+/// self.onconnect = (event) => self.yewAgentPorts.push(event.ports[0]);
+/// const { default: init } = await import("./worker.js");
+/// init();
+/// ```
+///
+/// Messages are encoded with `CODEC`.
+#[allow(missing_debug_implementations)]
+pub struct SharedWorker<AGN, CODEC = Bincode> {
+    _agent: PhantomData<AGN>,
+    _codec: PhantomData<CODEC>,
+}
+
+impl<AGN, CODEC> Discoverer for SharedWorker<AGN, CODEC>
+where
+    AGN: Agent,
+    CODEC: Codec + 'static,
+    <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
+    <AGN as Agent>::Output: Serialize + for<'de> Deserialize<'de>,
+{
+    type Agent = AGN;
+
+    fn spawn_or_join(callback: Option<Callback<AGN::Output>>) -> Box<dyn Bridge<AGN>> {
+        Self::spawn_or_join_with_options(callback, BridgeOptions::default())
+    }
+
+    fn spawn_or_join_with_options(
+        callback: Option<Callback<AGN::Output>>,
+        options: BridgeOptions,
+    ) -> Box<dyn Bridge<AGN>> {
+        spawn_or_join_workers::<AGN, CODEC>(1, RemoteWorker::shared::<AGN>, callback, options)
+    }
+}
+
+impl<AGN, CODEC> Dispatchable for SharedWorker<AGN, CODEC>
+where
+    AGN: Agent,
+    CODEC: Codec + 'static,
+    <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
+    <AGN as Agent>::Output: Serialize + for<'de> Deserialize<'de>,
+{
+}
+
+impl<AGN, CODEC: Codec> WorkerReach for SharedWorker<AGN, CODEC> {
+    type Codec = CODEC;
+}
+
+/// The tabs connected to an agent in a shared worker.
+///
+/// The ids of handlers are only unique within a tab, so the agent gets ids of its own, which are
+/// mapped back when it responds.
+#[derive(Default)]
+struct Connections {
+    /// Ports of the connected tabs
+    ports: Slab<MessagePort>,
+    /// The port of the tab and the id in the tab of every handler by the id in the agent
+    handlers: Slab<(usize, HandlerId)>,
+    /// Raw ids of the handlers in the agent by port and raw id in the tab
+    ids: HashMap<(usize, usize), usize>,
+}
+
+impl Connections {
+    /// Registers a handler of a tab and returns its id in the agent
+    fn connect(&mut self, port: usize, id: HandlerId) -> HandlerId {
+        let raw_id = self.handlers.insert((port, id));
+        self.ids.insert((port, id.raw_id()), raw_id);
+        id.with_raw_id(raw_id)
+    }
+
+    /// Returns the id in the agent of a handler of a tab
+    fn locate(&self, port: usize, id: HandlerId) -> Option<HandlerId> {
+        self.ids
+            .get(&(port, id.raw_id()))
+            .map(|raw_id| id.with_raw_id(*raw_id))
+    }
+
+    /// Removes a handler of a tab and returns its id in the agent
+    fn disconnect(&mut self, port: usize, id: HandlerId) -> Option<HandlerId> {
+        let raw_id = self.ids.remove(&(port, id.raw_id()))?;
+        self.handlers.remove(raw_id);
+        Some(id.with_raw_id(raw_id))
+    }
+
+    /// Removes a tab and returns the ids in the agent of its remaining handlers
+    fn close(&mut self, port: usize) -> Vec<HandlerId> {
+        if self.ports.contains(port) {
+            self.ports.remove(port).close();
+        }
+        let handlers: Vec<HandlerId> = self
+            .handlers
+            .iter()
+            .filter(|(_, (handler_port, _))| *handler_port == port)
+            .map(|(raw_id, (_, id))| id.with_raw_id(raw_id))
+            .collect();
+        for id in handlers.iter() {
+            let (_, id_in_tab) = self.handlers.remove(id.raw_id());
+            self.ids.remove(&(port, id_in_tab.raw_id()));
+        }
+        handlers
+    }
+}
+
+struct SharedResponder<CODEC> {
+    connections: Shared<Connections>,
+    _codec: PhantomData<CODEC>,
+}
+
+impl<AGN, CODEC> Responder<AGN> for SharedResponder<CODEC>
+where
+    AGN: Agent,
+    CODEC: Codec,
+    <AGN as Agent>::Output: Serialize + for<'de> Deserialize<'de>,
+{
+    fn respond(&self, id: HandlerId, output: AGN::Output) {
+        if let Some((port, id)) = self.target(id) {
+            let msg = FromWorker::ProcessOutput(id, output);
+            post_from_worker::<CODEC, _>(&port, &msg);
+        }
+    }
+
+    fn end_stream(&self, id: HandlerId) {
+        if let Some((port, id)) = self.target(id) {
+            let msg: FromWorker<AGN::Output> = FromWorker::StreamEnd(id);
+            post_from_worker::<CODEC, _>(&port, &msg);
+        }
+    }
+}
+
+impl<CODEC> SharedResponder<CODEC> {
+    /// Returns the port of the tab of a handler and its id in the tab
+    fn target(&self, id: HandlerId) -> Option<(MessagePort, HandlerId)> {
+        let connections = self.connections.borrow();
+        let target = connections
+            .handlers
+            .get(id.raw_id())
+            .and_then(|(port, id_in_tab)| {
+                let port = connections.ports.get(*port)?.clone();
+                Some((port, id.with_raw_id(id_in_tab.raw_id())))
+            });
+        if target.is_none() {
+            console::warn!(format!(
+                "Id of handler is not connected to the shared worker: {}.",
+                id.raw_id()
+            ));
+        }
+        target
+    }
+}
+
+/// Runs an agent in a shared worker, which all tabs connect to
+pub(super) fn register<AGN>(global: SharedWorkerGlobalScope)
+where
+    AGN: Agent,
+    <AGN as Agent>::Reach: WorkerReach,
+    <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
+    <AGN as Agent>::Output: Serialize + for<'de> Deserialize<'de>,
+{
+    let scope = AgentScope::<AGN>::new();
+    let connections: Shared<Connections> = Rc::new(RefCell::new(Connections::default()));
+    let responder = SharedResponder::<CodecOf<AGN>> {
+        connections: connections.clone(),
+        _codec: PhantomData,
+    };
+    let link = AgentLink::connect(&scope, responder);
+    let upd = AgentLifecycleEvent::Create(link);
+    scope.send(upd);
+
+    let lock = hold_lock();
+    let connect = move |port: MessagePort| {
+        let index = connections.borrow_mut().ports.insert(port.clone());
+        let handler = {
+            let scope = scope.clone();
+            let connections = connections.clone();
+            let port = port.clone();
+            move |packet: Packet| handle_message::<AGN>(&scope, &connections, index, &port, packet)
+        };
+        port.set_onmessage_closure(handler);
+        let loaded: FromWorker<AGN::Output> = FromWorker::WorkerLoaded(lock.clone());
+        post_from_worker::<CodecOf<AGN>, _>(&port, &loaded);
+    };
+
+    // Tabs, that connected while the agent was loading, have been kept by the loader script
+    let pending = Reflect::get(&global, &JsValue::from_str("yewAgentPorts")).unwrap_throw();
+    if Array::is_array(&pending) {
+        for port in Array::from(&pending).iter() {
+            connect(port.unchecked_into());
+        }
+    }
+
+    let onconnect = move |event: MessageEvent| connect(event.ports().get(0).unchecked_into());
+    let closure = Closure::wrap(Box::new(onconnect) as Box<dyn Fn(MessageEvent)>);
+    global.set_onconnect(Some(closure.as_ref().unchecked_ref()));
+    closure.forget();
+}
+
+fn handle_message<AGN>(
+    scope: &AgentScope<AGN>,
+    connections: &Shared<Connections>,
+    index: usize,
+    port: &MessagePort,
+    packet: Packet,
+) where
+    AGN: Agent,
+    <AGN as Agent>::Reach: WorkerReach,
+    <AGN as Agent>::Input: Serialize + for<'de> Deserialize<'de>,
+    <AGN as Agent>::Output: Serialize + for<'de> Deserialize<'de>,
+{
+    let msg = match packet.decode::<CodecOf<AGN>, ToWorker<AGN::Input>>() {
+        Ok(msg) => msg,
+        Err(error) => {
+            let msg: FromWorker<AGN::Output> = FromWorker::Error(None, error);
+            post_from_worker::<CodecOf<AGN>, _>(port, &msg);
+            return;
+        }
+    };
+    match msg {
+        ToWorker::Connected(id) => {
+            let id = connections.borrow_mut().connect(index, id);
+            let upd = AgentLifecycleEvent::Connected(id);
+            scope.send(upd);
+        }
+        ToWorker::ProcessInput(id, value) => {
+            let located = connections.borrow().locate(index, id);
+            match located {
+                Some(id) => {
+                    let upd = AgentLifecycleEvent::Input(value, id);
+                    scope.send(upd);
+                }
+                None => console::warn!(format!(
+                    "Input of a handler, that is not connected to the shared worker: {}.",
+                    id.raw_id()
+                )),
+            }
+        }
+        ToWorker::StreamAck(id, count) => {
+            let located = connections.borrow().locate(index, id);
+            if let Some(id) = located {
+                scope.ack_stream(id, count);
+            }
+        }
+        ToWorker::Cancel(id) => {
+            let located = connections.borrow().locate(index, id);
+            if let Some(id) = located {
+                scope.cancel(id);
+            }
+        }
+        ToWorker::Disconnected(id) => {
+            let disconnected = connections.borrow_mut().disconnect(index, id);
+            if let Some(id) = disconnected {
+                let upd = AgentLifecycleEvent::Disconnected(id);
+                scope.send(upd);
+            }
+        }
+        ToWorker::Destroy => {
+            // The tab has no bridges left, the agent is destroyed only with the last tab
+            let handlers = connections.borrow_mut().close(index);
+            for id in handlers {
+                let upd = AgentLifecycleEvent::Disconnected(id);
+                scope.send(upd);
+            }
+            if connections.borrow().ports.is_empty() {
+                let upd = AgentLifecycleEvent::Destroy;
+                scope.send(upd);
+                // Terminates the shared worker
+                let global: SharedWorkerGlobalScope = js_sys::global().unchecked_into();
+                global.close();
+            }
+        }
+    }
+}
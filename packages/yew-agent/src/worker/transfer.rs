@@ -0,0 +1,188 @@
+use super::{Codec, CodecError};
+use js_sys::{Array, ArrayBuffer};
+use serde::de::{self, Deserialize, Deserializer};
+use serde::ser::{self, Serialize, Serializer};
+use std::cell::RefCell;
+use std::ops::{Deref, DerefMut};
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{ImageBitmap, MessagePort};
+
+thread_local! {
+    /// Payloads of the message, that is currently encoded
+    static SENT_PAYLOADS: RefCell<Option<Vec<JsValue>>> = Default::default();
+    /// Payloads of the message, that is currently decoded
+    static RECEIVED_PAYLOADS: RefCell<Vec<Option<JsValue>>> = Default::default();
+}
+
+/// A JavaScript object, that can be transferred to and from a worker.
+pub trait Transfer: JsCast {}
+
+impl Transfer for ArrayBuffer {}
+impl Transfer for ImageBitmap {}
+impl Transfer for MessagePort {}
+
+/// A payload of an agent message, that is moved to or from a worker without serializing it.
+///
+/// Use it as a field of an agent's input or output to send large data like image frames without
+/// copying it. Once a message is sent to a worker, the payload is no longer usable by the sender,
+/// for example a transferred [`ArrayBuffer`] becomes empty.
+///
+/// A `Transferable` can only be serialized as part of a message to or from a worker agent. Agents in
+/// the same thread don't serialize their messages at all. Inputs containing a `Transferable` are not
+/// posted to other tabs by agents with the [`Broadcast`](crate::Broadcast) reach.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transferable<T: Transfer>(T);
+
+impl<T: Transfer> Transferable<T> {
+    /// Wraps an object to be transferred.
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    /// Returns the transferred object.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: Transfer> From<T> for Transferable<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: Transfer> Deref for Transferable<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Transfer> DerefMut for Transferable<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Transfer> Serialize for Transferable<T> {
+    /// Serializes the index of the payload in the transfer list of the message
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let index = SENT_PAYLOADS.with(|payloads| {
+            payloads.borrow_mut().as_mut().map(|payloads| {
+                payloads.push(self.0.as_ref().clone());
+                payloads.len() - 1
+            })
+        });
+        match index {
+            Some(index) => serializer.serialize_u64(index as u64),
+            None => Err(ser::Error::custom(
+                "a Transferable can only be serialized in an agent message",
+            )),
+        }
+    }
+}
+
+impl<'de, T: Transfer> Deserialize<'de> for Transferable<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let index = u64::deserialize(deserializer)? as usize;
+        let payload = RECEIVED_PAYLOADS
+            .with(|payloads| payloads.borrow_mut().get_mut(index).and_then(Option::take));
+        match payload.map(JsCast::dyn_into::<T>) {
+            Some(Ok(value)) => Ok(Self(value)),
+            Some(Err(_)) => Err(de::Error::custom("transferred payload has the wrong type")),
+            None => Err(de::Error::custom(format!(
+                "transferred payload {} is missing",
+                index
+            ))),
+        }
+    }
+}
+
+/// An encoded agent message and the payloads transferred with it.
+pub(crate) struct Packet {
+    data: JsValue,
+    payloads: Array,
+}
+
+impl Packet {
+    /// Encodes a message and collects its transferable payloads
+    pub fn encode<CODEC: Codec, T: Serialize>(message: &T) -> Result<Self, CodecError> {
+        let previous = SENT_PAYLOADS.with(|payloads| payloads.replace(Some(Vec::new())));
+        let data = CODEC::encode(message);
+        let payloads = SENT_PAYLOADS.with(|payloads| payloads.replace(previous));
+        Ok(Self {
+            data: data?,
+            payloads: payloads.unwrap_or_default().into_iter().collect(),
+        })
+    }
+
+    /// Encodes a message, that is posted without a transfer list.
+    ///
+    /// Fails, if the message contains `Transferable` payloads.
+    pub fn encode_without_transfer<CODEC: Codec, T: Serialize>(
+        message: &T,
+    ) -> Result<Self, CodecError> {
+        let previous = SENT_PAYLOADS.with(|payloads| payloads.replace(None));
+        let data = CODEC::encode(message);
+        SENT_PAYLOADS.with(|payloads| payloads.replace(previous));
+        Ok(Self {
+            data: data?,
+            payloads: Array::new(),
+        })
+    }
+
+    /// Decodes the message, moving its payloads into the `Transferable`s it contains
+    pub fn decode<CODEC: Codec, T: for<'de> Deserialize<'de>>(self) -> Result<T, CodecError> {
+        let received = self.payloads.iter().map(Some).collect();
+        let previous = RECEIVED_PAYLOADS.with(|payloads| payloads.replace(received));
+        let message = CODEC::decode(self.data);
+        RECEIVED_PAYLOADS.with(|payloads| payloads.replace(previous));
+        message
+    }
+
+    /// Returns the value to post and its transfer list
+    pub fn to_message(&self) -> (JsValue, &Array) {
+        if self.payloads.length() == 0 {
+            (self.data.clone(), &self.payloads)
+        } else {
+            (
+                Array::of2(&self.data, &self.payloads).into(),
+                &self.payloads,
+            )
+        }
+    }
+
+    /// Splits a posted value into the encoded message and its payloads
+    pub fn from_message(message: JsValue) -> Self {
+        // Codecs don't encode messages as plain arrays, so an array always carries payloads
+        if Array::is_array(&message) {
+            let message = Array::from(&message);
+            Self {
+                data: message.get(0),
+                payloads: message.get(1).unchecked_into(),
+            }
+        } else {
+            Self {
+                data: message,
+                payloads: Array::new(),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Bincode;
+
+    #[test]
+    fn payloads_need_a_transfer_list() {
+        let payload = Transferable::new(ArrayBuffer::unchecked_from_js(JsValue::NULL));
+        let error = Packet::encode_without_transfer::<Bincode, _>(&payload)
+            .err()
+            .unwrap();
+        assert!(error.is_encode());
+        assert!(SENT_PAYLOADS.with(|payloads| payloads.borrow().is_none()));
+    }
+}
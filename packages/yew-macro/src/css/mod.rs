@@ -0,0 +1,107 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::parse::{Parse, ParseStream};
+use syn::{Expr, LitStr};
+
+/// Style block with interpolated expressions.
+pub struct Css {
+    /// Style block as written
+    value: String,
+    /// Format string of the style block, with braces escaped
+    format: String,
+    /// Expressions interpolated with `${..}`
+    args: Vec<Expr>,
+}
+
+impl Parse for Css {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let lit: LitStr = input.parse()?;
+        if !input.is_empty() {
+            return Err(input.error("expected a single string literal"));
+        }
+
+        let value = lit.value();
+        let error = |msg: String| syn::Error::new(lit.span(), msg);
+        let mut format = String::with_capacity(value.len());
+        let mut args = Vec::new();
+        let mut depth = 0usize;
+        let mut quote = None;
+        let mut rest = value.as_str();
+        while let Some(c) = rest.chars().next() {
+            if quote.is_none() && rest.starts_with("${") {
+                let end = interpolation_end(&rest[2..]).ok_or_else(|| {
+                    error("unterminated interpolation (hint: close it with `}`)".into())
+                })?;
+                let expr = &rest[2..2 + end];
+                args.push(syn::parse_str(expr).map_err(|err| {
+                    error(format!("invalid interpolation `${{{}}}`: {}", expr, err))
+                })?);
+                format.push_str("{}");
+                rest = &rest[2 + end + 1..];
+                continue;
+            }
+
+            match (quote, c) {
+                (Some(q), c) if c == q => quote = None,
+                (Some(_), _) => {}
+                (None, '"') | (None, '\'') => quote = Some(c),
+                (None, '{') => depth += 1,
+                (None, '}') => {
+                    depth = depth
+                        .checked_sub(1)
+                        .ok_or_else(|| error("unexpected `}` in style block".into()))?;
+                }
+                _ => {}
+            }
+            match c {
+                '{' => format.push_str("{{"),
+                '}' => format.push_str("}}"),
+                c => format.push(c),
+            }
+            rest = &rest[c.len_utf8()..];
+        }
+        if depth != 0 {
+            return Err(error("unclosed `{` in style block".into()));
+        }
+
+        Ok(Self {
+            value,
+            format,
+            args,
+        })
+    }
+}
+
+/// Returns the position of the `}` closing an interpolation, that starts at the beginning of `s`
+fn interpolation_end(s: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, c) in s.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' if depth == 0 => return Some(i),
+            '}' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+impl ToTokens for Css {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let Self {
+            value,
+            format,
+            args,
+        } = self;
+        tokens.extend(if args.is_empty() {
+            quote! { ::yew::css::Css::new(#value) }
+        } else {
+            quote! {
+                ::yew::css::Css::dynamic(&::std::format!(
+                    #format,
+                    #(::yew::css::escape(&(#args))),*
+                ))
+            }
+        });
+    }
+}
@@ -141,6 +141,7 @@ impl Parse for FunctionComponent {
 
 pub struct FunctionComponentName {
     component_name: Ident,
+    forward_ref: bool,
 }
 
 impl Parse for FunctionComponentName {
@@ -151,7 +152,21 @@ impl Parse for FunctionComponentName {
 
         let component_name = input.parse()?;
 
-        Ok(Self { component_name })
+        let forward_ref = if input.is_empty() {
+            false
+        } else {
+            input.parse::<Comma>()?;
+            let option: Ident = input.parse()?;
+            if option != "forward_ref" {
+                return Err(syn::Error::new_spanned(option, "expected `forward_ref`"));
+            }
+            true
+        };
+
+        Ok(Self {
+            component_name,
+            forward_ref,
+        })
     }
 }
 
@@ -159,7 +174,10 @@ pub fn function_component_impl(
     name: FunctionComponentName,
     component: FunctionComponent,
 ) -> syn::Result<TokenStream> {
-    let FunctionComponentName { component_name } = name;
+    let FunctionComponentName {
+        component_name,
+        forward_ref,
+    } = name;
 
     let FunctionComponent {
         block,
@@ -188,6 +206,12 @@ pub fn function_component_impl(
         .map(|ty_param| ty_param.ident.clone()) // create a new Punctuated sequence without any type bounds
         .collect::<Punctuated<_, Comma>>();
 
+    let forward_ref = if forward_ref {
+        quote! { const FORWARD_REF: ::std::primitive::bool = true; }
+    } else {
+        quote! {}
+    };
+
     let quoted = quote! {
         #[doc(hidden)]
         #[allow(non_camel_case_types)]
@@ -199,6 +223,8 @@ pub fn function_component_impl(
         impl #impl_generics ::yew::functional::FunctionProvider for #function_name #ty_generics #where_clause {
             type TProps = #props_type;
 
+            #forward_ref
+
             fn run(#arg) -> #ret_type {
                 #block
             }
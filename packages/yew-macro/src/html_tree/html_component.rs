@@ -103,7 +103,9 @@ impl ToTokens for HtmlComponent {
         let special_props = props.special();
         let node_ref = if let Some(node_ref) = &special_props.node_ref {
             let value = &node_ref.value;
-            quote_spanned! {value.span()=> #value }
+            quote_spanned! {value.span()=>
+                ::yew::html::IntoComponentRef::<#ty>::into_component_ref(#value)
+            }
         } else {
             quote! { <::yew::html::NodeRef as ::std::default::Default>::default() }
         };
@@ -1,4 +1,5 @@
-use super::{HtmlChildrenTree, HtmlDashedName, TagTokens};
+use super::html_block::BlockContent;
+use super::{HtmlChildrenTree, HtmlDashedName, HtmlNode, HtmlTree, TagTokens};
 use crate::props::{ClassesForm, ElementProps, Prop};
 use crate::stringify::{Stringify, Value};
 use crate::{non_capitalized_ascii, Peek, PeekValue};
@@ -8,7 +9,7 @@ use quote::{quote, quote_spanned, ToTokens};
 use syn::buffer::Cursor;
 use syn::parse::{Parse, ParseStream};
 use syn::spanned::Spanned;
-use syn::{Block, Expr, Ident, Lit, LitStr, Token};
+use syn::{Block, Expr, ExprLit, Ident, Lit, LitStr, Token};
 
 pub struct HtmlElement {
     pub name: TagName,
@@ -88,9 +89,93 @@ impl Parse for HtmlElement {
     }
 }
 
-impl ToTokens for HtmlElement {
+impl HtmlElement {
+    /// Returns true, if the element and all of its descendants only consist of literals.
+    ///
+    /// Such subtrees render the same every time, so they are built once per call site and never
+    /// diffed again.
+    pub fn is_static(&self) -> bool {
+        let ElementProps {
+            classes,
+            attributes,
+            properties,
+            booleans,
+            value,
+            checked,
+            node_ref,
+            key,
+            listeners,
+        } = &self.props;
+
+        let name_is_static = match &self.name {
+            // `input` and `textarea` keep their value in the element, which must be diffed
+            TagName::Lit(name) => !matches!(
+                name.to_ascii_lowercase_string().as_str(),
+                "input" | "textarea"
+            ),
+            TagName::Expr(_) => false,
+        };
+        let classes_are_static = match classes {
+            Some(ClassesForm::Single(classes)) => classes.try_into_lit().is_some(),
+            Some(ClassesForm::Tuple(_)) => false,
+            None => true,
+        };
+        let children_are_static = self.children.0.iter().all(|child| match child {
+            HtmlTree::Element(element) => element.is_static(),
+            HtmlTree::Block(block) => matches!(
+                &block.content,
+                BlockContent::Node(node) if matches!(**node, HtmlNode::Literal(_))
+            ),
+            _ => false,
+        });
+
+        name_is_static
+            && classes_are_static
+            && children_are_static
+            && value.is_none()
+            && checked.is_none()
+            && node_ref.is_none()
+            && key.is_none()
+            && listeners.is_empty()
+            && properties.is_empty()
+            && attributes
+                .iter()
+                .all(|Prop { value, .. }| value.try_into_lit().is_some())
+            && booleans.iter().all(|Prop { value, .. }| {
+                matches!(
+                    value,
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Bool(_),
+                        ..
+                    })
+                )
+            })
+    }
+
+    /// Build the element once per call site and reuse it on every render.
+    ///
+    /// Only valid for elements, for which [`is_static`](Self::is_static) returns true.
+    pub fn to_static_token_stream(&self) -> TokenStream {
+        let node = self.to_plain_token_stream();
+        quote! {
+            {
+                ::std::thread_local! {
+                    static __YEW_STATIC: ::yew::virtual_dom::VNode = #node;
+                }
+                ::yew::virtual_dom::VTag::__new_static(&__YEW_STATIC)
+            }
+        }
+    }
+
+    /// Build the element without hoisting any of its children.
+    pub fn to_plain_token_stream(&self) -> TokenStream {
+        let mut tokens = TokenStream::new();
+        self.build_tokens(&mut tokens, false);
+        tokens
+    }
+
     #[allow(clippy::cognitive_complexity)]
-    fn to_tokens(&self, tokens: &mut TokenStream) {
+    fn build_tokens(&self, tokens: &mut TokenStream, hoist_children: bool) {
         let Self {
             name,
             props,
@@ -100,6 +185,7 @@ impl ToTokens for HtmlElement {
         let ElementProps {
             classes,
             attributes,
+            properties,
             booleans,
             value,
             checked,
@@ -285,8 +371,17 @@ impl ToTokens for HtmlElement {
             }
         };
 
+        let set_properties = properties.iter().map(|Prop { label, value, .. }| {
+            let key = label.to_lit_str();
+            quote_spanned! {value.span()=>
+                __yew_vtag.set_property(#key, #value);
+            }
+        });
+        let set_properties = quote! { #(#set_properties)* };
+
         // TODO: if none of the children have possibly None expressions or literals as keys, we can
         // compute `VList.fully_keyed` at compile time.
+        let children = children.to_element_children_token_stream(hoist_children);
         let child_list = quote! {
             ::yew::virtual_dom::VList::with_children(
                 #children,
@@ -298,51 +393,57 @@ impl ToTokens for HtmlElement {
             TagName::Lit(name) => {
                 let name_span = name.span();
                 let name = name.to_ascii_lowercase_string();
-                match &*name {
+                let vtag = match &*name {
                     "input" => {
                         quote_spanned! {name_span=>
-                            #[allow(clippy::redundant_clone, unused_braces)]
-                            ::std::convert::Into::<::yew::virtual_dom::VNode>::into(
-                                ::yew::virtual_dom::VTag::__new_input(
-                                    #value,
-                                    #checked,
-                                    #node_ref,
-                                    #key,
-                                    #attributes,
-                                    #listeners,
-                                ),
+                            ::yew::virtual_dom::VTag::__new_input(
+                                #value,
+                                #checked,
+                                #node_ref,
+                                #key,
+                                #attributes,
+                                #listeners,
                             )
                         }
                     }
                     "textarea" => {
                         quote_spanned! {name_span=>
-                            #[allow(clippy::redundant_clone, unused_braces)]
-                            ::std::convert::Into::<::yew::virtual_dom::VNode>::into(
-                                ::yew::virtual_dom::VTag::__new_textarea(
-                                    #value,
-                                    #node_ref,
-                                    #key,
-                                    #attributes,
-                                    #listeners,
-                                ),
+                            ::yew::virtual_dom::VTag::__new_textarea(
+                                #value,
+                                #node_ref,
+                                #key,
+                                #attributes,
+                                #listeners,
                             )
                         }
                     }
                     _ => {
                         quote_spanned! {name_span=>
-                            #[allow(clippy::redundant_clone, unused_braces)]
-                            ::std::convert::Into::<::yew::virtual_dom::VNode>::into(
-                                ::yew::virtual_dom::VTag::__new_other(
-                                    ::std::borrow::Cow::<'static, ::std::primitive::str>::Borrowed(#name),
-                                    #node_ref,
-                                    #key,
-                                    #attributes,
-                                    #listeners,
-                                    #child_list,
-                                ),
+                            ::yew::virtual_dom::VTag::__new_other(
+                                ::std::borrow::Cow::<'static, ::std::primitive::str>::Borrowed(#name),
+                                #node_ref,
+                                #key,
+                                #attributes,
+                                #listeners,
+                                #child_list,
                             )
                         }
                     }
+                };
+                let vtag = if properties.is_empty() {
+                    vtag
+                } else {
+                    quote_spanned! {name_span=>
+                        {
+                            let mut __yew_vtag = #vtag;
+                            #set_properties
+                            __yew_vtag
+                        }
+                    }
+                };
+                quote_spanned! {name_span=>
+                    #[allow(clippy::redundant_clone, unused_braces)]
+                    ::std::convert::Into::<::yew::virtual_dom::VNode>::into(#vtag)
                 }
             }
             TagName::Expr(name) => {
@@ -413,6 +514,8 @@ impl ToTokens for HtmlElement {
                         }
                     };
 
+                    #set_properties
+
                     // These are the runtime-checks exclusive to dynamic tags.
                     // For literal tags this is already done at compile-time.
                     //
@@ -442,6 +545,12 @@ impl ToTokens for HtmlElement {
     }
 }
 
+impl ToTokens for HtmlElement {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.build_tokens(tokens, true);
+    }
+}
+
 fn wrap_attr_prop(prop: &Prop) -> TokenStream {
     let value = prop.value.optimize_literals();
     quote_spanned! {value.span()=>
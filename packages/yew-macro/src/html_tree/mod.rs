@@ -231,13 +231,40 @@ impl HtmlChildrenTree {
     }
 
     pub fn to_build_vec_token_stream(&self) -> TokenStream {
+        self.build_vec_token_stream(ToTokens::to_token_stream)
+    }
+
+    /// Like [`to_build_vec_token_stream`](Self::to_build_vec_token_stream), but hoists child
+    /// elements which only contain literals out of the render if `hoist` is set.
+    ///
+    /// `hoist` is unset for the children of an element that is itself hoisted, because the whole
+    /// subtree is already built only once.
+    pub fn to_element_children_token_stream(&self, hoist: bool) -> TokenStream {
+        self.build_vec_token_stream(|child| match child {
+            HtmlTree::Element(element) if hoist && element.is_static() => {
+                lint::lint_all(child);
+                element.to_static_token_stream()
+            }
+            HtmlTree::Element(element) if !hoist => {
+                lint::lint_all(child);
+                element.to_plain_token_stream()
+            }
+            child => child.to_token_stream(),
+        })
+    }
+
+    fn build_vec_token_stream(
+        &self,
+        child_tokens: impl Fn(&HtmlTree) -> TokenStream,
+    ) -> TokenStream {
         let Self(children) = self;
 
         if self.only_single_node_children() {
             // optimize for the common case where all children are single nodes (only using literal html).
-            let children_into = children
-                .iter()
-                .map(|child| quote_spanned! {child.span()=> ::std::convert::Into::into(#child) });
+            let children_into = children.iter().map(|child| {
+                let tokens = child_tokens(child);
+                quote_spanned! {child.span()=> ::std::convert::Into::into(#tokens) }
+            });
             return quote! {
                 ::std::vec![#(#children_into),*]
             };
@@ -250,8 +277,9 @@ impl HtmlChildrenTree {
                     ::std::iter::Extend::extend(&mut #vec_ident, #node_iterator_stream);
                 }
             } else {
+                let tokens = child_tokens(child);
                 quote_spanned! {child.span()=>
-                    #vec_ident.push(::std::convert::Into::into(#child));
+                    #vec_ident.push(::std::convert::Into::into(#tokens));
                 }
             }
         });
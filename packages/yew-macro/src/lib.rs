@@ -47,11 +47,13 @@
 //! Please refer to [https://github.com/yewstack/yew](https://github.com/yewstack/yew) for how to set this up.
 
 mod classes;
+mod css;
 mod derive_props;
 mod function_component;
 mod html_tree;
 mod props;
 mod stringify;
+mod style;
 
 use derive_props::DerivePropsInput;
 use function_component::{function_component_impl, FunctionComponent, FunctionComponentName};
@@ -122,6 +124,18 @@ pub fn classes(input: TokenStream) -> TokenStream {
     TokenStream::from(classes.into_token_stream())
 }
 
+#[proc_macro]
+pub fn css(input: TokenStream) -> TokenStream {
+    let css = parse_macro_input!(input as css::Css);
+    TokenStream::from(css.into_token_stream())
+}
+
+#[proc_macro]
+pub fn style(input: TokenStream) -> TokenStream {
+    let style = parse_macro_input!(input as style::Style);
+    TokenStream::from(style.into_token_stream())
+}
+
 #[proc_macro_attribute]
 pub fn function_component(
     attr: proc_macro::TokenStream,
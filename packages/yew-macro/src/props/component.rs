@@ -173,7 +173,12 @@ impl TryFrom<Props> for ComponentProps {
 fn validate(props: Props) -> Result<Props, syn::Error> {
     props.check_no_duplicates()?;
     props.check_all(|prop| {
-        if !prop.label.extended.is_empty() {
+        if let Some(tilde) = &prop.tilde {
+            Err(syn::Error::new_spanned(
+                tilde,
+                "DOM properties can only be set on elements",
+            ))
+        } else if !prop.label.extended.is_empty() {
             Err(syn::Error::new_spanned(
                 &prop.label,
                 "expected a valid Rust identifier",
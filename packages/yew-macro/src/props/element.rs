@@ -19,6 +19,7 @@ impl ClassesForm {
 
 pub struct ElementProps {
     pub attributes: Vec<Prop>,
+    pub properties: Vec<Prop>,
     pub listeners: Vec<Prop>,
     pub classes: Option<ClassesForm>,
     pub booleans: Vec<Prop>,
@@ -32,6 +33,9 @@ impl Parse for ElementProps {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let mut props = input.parse::<Props>()?;
 
+        let properties = props.drain_filter(|prop| prop.tilde.is_some());
+        properties.check_no_duplicates()?;
+
         let listeners =
             props.drain_filter(|prop| LISTENER_SET.contains(prop.label.to_string().as_str()));
 
@@ -51,6 +55,7 @@ impl Parse for ElementProps {
 
         Ok(Self {
             attributes: props.prop_list.into_vec(),
+            properties: properties.into_vec(),
             classes,
             listeners: listeners.into_vec(),
             checked,
@@ -14,17 +14,21 @@ use syn::{
 };
 
 pub struct Prop {
+    /// `~` marking a DOM property, which is assigned instead of set as an attribute.
+    pub tilde: Option<Token![~]>,
     pub label: HtmlDashedName,
     /// Punctuation between `label` and `value`.
     pub value: Expr,
 }
 impl Parse for Prop {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        if input.peek(Brace) {
+        let tilde = input.parse()?;
+        let prop = if input.peek(Brace) {
             Self::parse_shorthand_prop_assignment(input)
         } else {
             Self::parse_prop_assignment(input)
-        }
+        }?;
+        Ok(Self { tilde, ..prop })
     }
 }
 
@@ -58,7 +62,11 @@ impl Prop {
             ));
         }?;
 
-        Ok(Self { label, value: expr })
+        Ok(Self {
+            tilde: None,
+            label,
+            value: expr,
+        })
     }
 
     /// Parse a prop of the form `label={value}`
@@ -78,7 +86,11 @@ impl Prop {
         }
 
         let value = parse_prop_value(input)?;
-        Ok(Self { label, value })
+        Ok(Self {
+            tilde: None,
+            label,
+            value,
+        })
     }
 }
 
@@ -302,7 +314,15 @@ impl SpecialProps {
     fn pop_from(props: &mut SortedPropList) -> syn::Result<Self> {
         let node_ref = props.pop_unique(Self::REF_LABEL)?;
         let key = props.pop_unique(Self::KEY_LABEL)?;
-        Ok(Self { node_ref, key })
+        let special = Self { node_ref, key };
+        special.check_all(|prop| match &prop.tilde {
+            Some(tilde) => Err(syn::Error::new_spanned(
+                tilde,
+                format!("`{}` cannot be set as a DOM property", prop.label),
+            )),
+            None => Ok(()),
+        })?;
+        Ok(special)
     }
 
     fn iter(&self) -> impl Iterator<Item = &Prop> {
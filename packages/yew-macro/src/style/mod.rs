@@ -0,0 +1,71 @@
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned, ToTokens};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{Expr, ExprLit, Lit, Token};
+
+/// List of CSS declarations and styles.
+pub struct Style(Punctuated<StyleExpr, Token![,]>);
+
+impl Parse for Style {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        input.parse_terminated(StyleExpr::parse).map(Self)
+    }
+}
+
+impl ToTokens for Style {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let n = self.0.len();
+        let push_styles = self.0.iter().map(|x| match x {
+            StyleExpr::Declaration { property, value } => quote_spanned! {value.span()=>
+                __yew_style.set(#property, #value);
+            },
+            StyleExpr::Expr(style) => quote_spanned! {style.span()=>
+                __yew_style.push(#style);
+            },
+        });
+        tokens.extend(quote! {
+            {
+                let mut __yew_style = ::yew::html::Style::with_capacity(#n);
+                #(#push_styles)*
+                __yew_style
+            }
+        });
+    }
+}
+
+enum StyleExpr {
+    Declaration {
+        property: Box<Expr>,
+        value: Box<Expr>,
+    },
+    Expr(Box<Expr>),
+}
+
+impl Parse for StyleExpr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let expr: Expr = input.parse()?;
+        if input.peek(Token![=>]) {
+            input.parse::<Token![=>]>()?;
+            if let Expr::Lit(ExprLit {
+                lit: Lit::Str(lit_str),
+                ..
+            }) = &expr
+            {
+                if lit_str.value().contains(&[':', ';'][..]) {
+                    return Err(syn::Error::new(
+                        lit_str.span(),
+                        "property names must not contain `:` or `;` (hint: use `\"property\" => value`)",
+                    ));
+                }
+            }
+            Ok(Self::Declaration {
+                property: Box::new(expr),
+                value: Box::new(input.parse()?),
+            })
+        } else {
+            Ok(Self::Expr(Box::new(expr)))
+        }
+    }
+}
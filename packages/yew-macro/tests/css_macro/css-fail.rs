@@ -0,0 +1,18 @@
+use yew::prelude::*;
+
+struct NotDisplay;
+
+fn compile_fail() {
+    css!();
+    css!(42);
+    css!("color: red", "color: blue");
+
+    css!("&:hover { color: red;");
+    css!("color: red; }");
+    css!("color: ${red");
+    css!("color: ${1 +}");
+
+    css!("color: ${NotDisplay}");
+}
+
+fn main() {}
@@ -0,0 +1,57 @@
+#![no_implicit_prelude]
+
+// Shadow primitives
+#[allow(non_camel_case_types)]
+pub struct bool;
+#[allow(non_camel_case_types)]
+pub struct char;
+#[allow(non_camel_case_types)]
+pub struct f32;
+#[allow(non_camel_case_types)]
+pub struct f64;
+#[allow(non_camel_case_types)]
+pub struct i128;
+#[allow(non_camel_case_types)]
+pub struct i16;
+#[allow(non_camel_case_types)]
+pub struct i32;
+#[allow(non_camel_case_types)]
+pub struct i64;
+#[allow(non_camel_case_types)]
+pub struct i8;
+#[allow(non_camel_case_types)]
+pub struct isize;
+#[allow(non_camel_case_types)]
+pub struct str;
+#[allow(non_camel_case_types)]
+pub struct u128;
+#[allow(non_camel_case_types)]
+pub struct u16;
+#[allow(non_camel_case_types)]
+pub struct u32;
+#[allow(non_camel_case_types)]
+pub struct u64;
+#[allow(non_camel_case_types)]
+pub struct u8;
+#[allow(non_camel_case_types)]
+pub struct usize;
+
+fn compile_pass() {
+    // declarations and nested rules
+    ::yew::css!("color: red; &:hover { color: blue; } @media (max-width: 1px) { display: none; }");
+    // raw string
+    ::yew::css!(r#"content: "}";"#);
+    // empty
+    ::yew::css!("");
+
+    // interpolated values
+    let color = "red";
+    let width = 1;
+    ::yew::css!("color: ${color}; width: ${width}px; & > a { height: ${{ width * 2 }}px; }");
+
+    // conversion into classes
+    let _: ::yew::html::Classes = ::std::convert::Into::into(::yew::css!("color: red"));
+    ::yew::classes!("one", ::yew::css!("color: red"));
+}
+
+fn main() {}
@@ -0,0 +1,57 @@
+#![no_implicit_prelude]
+
+// Shadow primitives
+#[allow(non_camel_case_types)]
+pub struct bool;
+#[allow(non_camel_case_types)]
+pub struct char;
+#[allow(non_camel_case_types)]
+pub struct f32;
+#[allow(non_camel_case_types)]
+pub struct f64;
+#[allow(non_camel_case_types)]
+pub struct i128;
+#[allow(non_camel_case_types)]
+pub struct i16;
+#[allow(non_camel_case_types)]
+pub struct i32;
+#[allow(non_camel_case_types)]
+pub struct i64;
+#[allow(non_camel_case_types)]
+pub struct i8;
+#[allow(non_camel_case_types)]
+pub struct isize;
+#[allow(non_camel_case_types)]
+pub struct str;
+#[allow(non_camel_case_types)]
+pub struct u128;
+#[allow(non_camel_case_types)]
+pub struct u16;
+#[allow(non_camel_case_types)]
+pub struct u32;
+#[allow(non_camel_case_types)]
+pub struct u64;
+#[allow(non_camel_case_types)]
+pub struct u8;
+#[allow(non_camel_case_types)]
+pub struct usize;
+
+#[::yew::function_component(Comp, forward_ref)]
+fn comp() -> ::yew::Html {
+    let node_ref = ::yew::functional::use_forwarded_ref();
+    ::yew::html! {
+        <p>
+            <input ref={node_ref} />
+        </p>
+    }
+}
+
+#[::yew::function_component(Parent)]
+fn parent() -> ::yew::Html {
+    let node_ref = ::yew::functional::use_node_ref();
+    ::yew::html! {
+        <Comp ref={node_ref} />
+    }
+}
+
+fn main() {}
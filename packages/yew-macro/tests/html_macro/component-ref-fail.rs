@@ -0,0 +1,35 @@
+use yew::html::ComponentRef;
+use yew::prelude::*;
+
+pub struct Child;
+impl Component for Child {
+    type Message = ();
+    type Properties = ();
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        unimplemented!()
+    }
+    fn view(&self, _ctx: &Context<Self>) -> Html {
+        unimplemented!()
+    }
+}
+
+pub struct Other;
+impl Component for Other {
+    type Message = ();
+    type Properties = ();
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        unimplemented!()
+    }
+    fn view(&self, _ctx: &Context<Self>) -> Html {
+        unimplemented!()
+    }
+}
+
+fn compile_fail() {
+    let other = ComponentRef::<Other>::default();
+    html! { <Child ref={other} /> };
+}
+
+fn main() {}
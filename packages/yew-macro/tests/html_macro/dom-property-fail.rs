@@ -0,0 +1,32 @@
+use yew::prelude::*;
+
+#[derive(Clone, Properties, PartialEq)]
+pub struct Props {
+    pub value: i32,
+}
+
+pub struct Comp;
+impl Component for Comp {
+    type Message = ();
+    type Properties = Props;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        unimplemented!()
+    }
+
+    fn view(&self, _ctx: &Context<Self>) -> Html {
+        unimplemented!()
+    }
+}
+
+struct NotIntoJsValue;
+
+fn compile_fail() {
+    html! { <input ~indeterminate=true ~indeterminate=false /> };
+    html! { <div ~ref={NodeRef::default()} /> };
+    html! { <div ~key="key" /> };
+    html! { <Comp ~value=1 /> };
+    html! { <div ~custom={NotIntoJsValue} /> };
+}
+
+fn main() {}
@@ -114,6 +114,16 @@ fn compile_pass() {
     ];
     ::yew::html! { <div>{children}</div> };
 
+    // DOM properties
+    let indeterminate = true;
+    ::yew::html! {
+        <div ~custom={::std::string::ToString::to_string("value")}>
+            <input type="checkbox" ~indeterminate={indeterminate} ~checked=true />
+            <video ~muted=true ~{indeterminate} />
+            <@{"div"} ~hidden=false />
+        </div>
+    };
+
     // handle misleading angle brackets
     ::yew::html! { <div data-val={<::std::string::String as ::std::default::Default>::default()}></div> };
     ::yew::html! { <div><a data-val={<::std::string::String as ::std::default::Default>::default()} /></div> };
@@ -0,0 +1,11 @@
+use yew::prelude::*;
+
+fn compile_fail() {
+    style!(42);
+    style!("color" => 42);
+
+    style!("color" "red");
+    style!("color: red" => "blue");
+}
+
+fn main() {}
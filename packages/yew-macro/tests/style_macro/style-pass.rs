@@ -0,0 +1,68 @@
+#![no_implicit_prelude]
+
+// Shadow primitives
+#[allow(non_camel_case_types)]
+pub struct bool;
+#[allow(non_camel_case_types)]
+pub struct char;
+#[allow(non_camel_case_types)]
+pub struct f32;
+#[allow(non_camel_case_types)]
+pub struct f64;
+#[allow(non_camel_case_types)]
+pub struct i128;
+#[allow(non_camel_case_types)]
+pub struct i16;
+#[allow(non_camel_case_types)]
+pub struct i32;
+#[allow(non_camel_case_types)]
+pub struct i64;
+#[allow(non_camel_case_types)]
+pub struct i8;
+#[allow(non_camel_case_types)]
+pub struct isize;
+#[allow(non_camel_case_types)]
+pub struct str;
+#[allow(non_camel_case_types)]
+pub struct u128;
+#[allow(non_camel_case_types)]
+pub struct u16;
+#[allow(non_camel_case_types)]
+pub struct u32;
+#[allow(non_camel_case_types)]
+pub struct u64;
+#[allow(non_camel_case_types)]
+pub struct u8;
+#[allow(non_camel_case_types)]
+pub struct usize;
+
+fn compile_pass() {
+    // multiple declarations
+    ::yew::style!("color" => "red", "display" => "none");
+    // single declaration
+    ::yew::style!("color" => "red");
+    // empty
+    ::yew::style!();
+
+    // optional values
+    ::yew::style!(
+        "color" => ::std::option::Option::Some("red"),
+        "display" => ::std::option::Option::None::<&'static ::std::primitive::str>,
+    );
+
+    // merged styles
+    ::yew::style!("color: red; display: none", ::std::option::Option::Some("width: 1px"));
+
+    // mixed types
+    {
+        use ::std::borrow::ToOwned;
+        let property = "color".to_owned();
+        ::yew::style!(
+            "margin: 0",
+            property => "red".to_owned(),
+            ::std::vec!["width: 1px", "height: 1px"],
+        );
+    }
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+#[allow(dead_code)]
+#[rustversion::attr(stable(1.51), test)]
+fn style_macro() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/style_macro/*-pass.rs");
+    t.compile_fail("tests/style_macro/*-fail.rs");
+}
@@ -4,6 +4,7 @@
 use std::ops::Deref;
 
 use crate::html::{Component, NodeRef, Scope, Scoped};
+use crate::virtual_dom::listeners::EventRoot;
 use gloo_utils::document;
 use std::rc::Rc;
 use web_sys::Element;
@@ -24,12 +25,22 @@ where
     /// function which will update the state of the model and a `view` function which
     /// will render the model to a virtual DOM tree.
     pub(crate) fn mount_with_props(element: Element, props: Rc<COMP::Properties>) -> Self {
+        let event_root = EventRoot::for_host(&element);
+        Self::mount_with_props_and_event_root(element, event_root, props)
+    }
+
+    /// Alternative to `mount_with_props` which delegates the events of the app to `event_root`.
+    pub(crate) fn mount_with_props_and_event_root(
+        element: Element,
+        event_root: EventRoot,
+        props: Rc<COMP::Properties>,
+    ) -> Self {
         clear_element(&element);
-        let app = Self {
-            scope: Scope::new(None),
-        };
+        let mut scope = Scope::new(None);
+        scope.event_root = event_root;
+        let app = Self { scope };
         app.scope
-            .mount_in_place(element, NodeRef::default(), NodeRef::default(), props);
+            .mount_in_place(element, NodeRef::default(), NodeRef::default(), None, props);
 
         app
     }
@@ -50,7 +61,8 @@ where
 
     /// Schedule the app for destruction
     pub fn destroy(mut self) {
-        self.scope.destroy()
+        self.scope.destroy();
+        self.scope.event_root.remove();
     }
 }
 
@@ -0,0 +1,438 @@
+//! This module contains the runtime of the [`css!`](crate::css!) macro, which scopes a style block
+//! to the elements of a component.
+//!
+//! The style block is hashed into a unique class name and the stylesheet, with all of its rules
+//! scoped to that class, is added to the document head once.
+//!
+//! Inside of the style block:
+//! - declarations apply to the elements with the class
+//! - `&` in nested rules is replaced with the class selector, like in `&:hover { .. }`
+//! - nested rules without `&` apply to descendants, like `p { .. }`
+//! - `@media`, `@supports`, `@container` and `@layer` rules can contain declarations and nested
+//!   rules, which are scoped in the same way
+//! - other at-rules, like `@keyframes` and `@font-face`, are added as they are
+//!
+//! Values interpolated with `${expr}` are escaped with [`escape`], so that they can not end the
+//! declaration or rule they are part of. Each distinct result of interpolating values is a separate
+//! style block. Its stylesheet is removed again, once no [`Css`] value of it is left and no element
+//! in the document has its class.
+
+use crate::html::{Classes, IntoPropValue};
+use crate::virtual_dom::AttrValue;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::{Display, Write};
+use std::rc::Rc;
+
+/// Attribute of the `<style>` elements added for scoped styles. Set to the class name.
+const STYLE_ATTRIBUTE: &str = "data-yew-css";
+
+/// Number of stylesheets of style blocks with interpolated values, above which unused ones are
+/// removed.
+///
+/// A stylesheet is unused, when no [`Css`] value of its style block is left and no element in the
+/// document has its class. Stylesheets in use are never removed. The next removal happens, once
+/// there are twice as many stylesheets as were left, but at least this many.
+pub const MAX_DYNAMIC_SHEETS: usize = 256;
+
+thread_local! {
+    static REGISTRY: RefCell<Registry> = Default::default();
+}
+
+/// Class name of a style block created with the [`css!`](crate::css!) macro.
+///
+/// Converts into [`Classes`] and can be used as the value of a `class` attribute. Each value is a
+/// handle keeping the stylesheet of a style block with interpolated values in the document head.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Css {
+    class: Rc<str>,
+}
+
+impl Css {
+    /// Scope the style block `css` to a class and add its stylesheet to the document head, if it
+    /// has not been added yet.
+    ///
+    /// Prefer the [`css!`](crate::css!) macro, which checks the style block at compile time.
+    pub fn new(css: &str) -> Self {
+        let class = REGISTRY.with(|registry| registry.borrow_mut().register(css, false));
+        Self { class }
+    }
+
+    /// Like [`Css::new`], but for style blocks with interpolated values.
+    ///
+    /// The stylesheet is removed, once it is unused, as described for [`MAX_DYNAMIC_SHEETS`].
+    /// Values must be escaped with [`escape`].
+    pub fn dynamic(css: &str) -> Self {
+        let class = REGISTRY.with(|registry| registry.borrow_mut().register(css, true));
+        Self { class }
+    }
+
+    /// Get the class name of the style block
+    pub fn class_name(&self) -> &str {
+        &self.class
+    }
+}
+
+impl From<Css> for Classes {
+    fn from(css: Css) -> Self {
+        let mut classes = Classes::with_capacity(1);
+        // SAFETY: the class name is generated and does not contain whitespace
+        unsafe { classes.unchecked_push(css.class.to_string()) };
+        classes
+    }
+}
+
+impl From<&Css> for Classes {
+    fn from(css: &Css) -> Self {
+        css.clone().into()
+    }
+}
+
+impl IntoPropValue<AttrValue> for Css {
+    fn into_prop_value(self) -> AttrValue {
+        AttrValue::Rc(self.class)
+    }
+}
+
+impl IntoPropValue<Option<AttrValue>> for Css {
+    fn into_prop_value(self) -> Option<AttrValue> {
+        Some(self.into_prop_value())
+    }
+}
+
+/// Escapes a value, that is interpolated into a style block.
+///
+/// Characters, that could end a string, declaration, rule or the `<style>` element, or start a
+/// comment, are replaced with CSS escape sequences. The escaped value can only be part of a single
+/// declaration value.
+pub fn escape<T: Display + ?Sized>(value: &T) -> String {
+    let value = value.to_string();
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            ';' | '{' | '}' | '"' | '\'' | '\\' | '<' | '>' | '*' | '@' => {
+                // The space ends the escape sequence and is not part of the value
+                let _ = write!(escaped, "\\{:x} ", c as u32);
+            }
+            c if c.is_control() => {
+                let _ = write!(escaped, "\\{:x} ", c as u32);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[derive(Default)]
+struct Registry {
+    /// Class names by hash of their style block. Each `Css` value holds another reference to the
+    /// class name.
+    classes: HashMap<u64, Rc<str>>,
+
+    /// Hashes of style blocks with interpolated values
+    dynamic: Vec<u64>,
+
+    /// Number of style blocks with interpolated values, above which unused ones are removed
+    sweep_at: usize,
+
+    /// Whether removing unused stylesheets is scheduled
+    sweep_scheduled: bool,
+}
+
+impl Registry {
+    fn register(&mut self, css: &str, dynamic: bool) -> Rc<str> {
+        let hash = hash(css);
+        if let Some(class) = self.classes.get(&hash) {
+            return Rc::clone(class);
+        }
+
+        let class: Rc<str> = Rc::from(format!("yew-css-{:x}", hash));
+        if cfg!(target_arch = "wasm32") {
+            inject(&class, &scope(&format!(".{}", class), css));
+        }
+        self.classes.insert(hash, Rc::clone(&class));
+        if dynamic {
+            self.dynamic.push(hash);
+            if self.dynamic.len() > self.sweep_at.max(MAX_DYNAMIC_SHEETS) && !self.sweep_scheduled {
+                self.schedule_sweep();
+            }
+        }
+        class
+    }
+
+    /// Removes unused stylesheets once the current render is applied to the DOM, as the classes of
+    /// `Css` values converted into `Classes` are only in the document by then
+    fn schedule_sweep(&mut self) {
+        if cfg!(target_arch = "wasm32") {
+            self.sweep_scheduled = true;
+            wasm_bindgen_futures::spawn_local(async {
+                REGISTRY.with(|registry| registry.borrow_mut().sweep());
+            });
+        } else {
+            self.sweep();
+        }
+    }
+
+    /// Removes the stylesheets of style blocks with interpolated values, that are not used anymore
+    fn sweep(&mut self) {
+        let classes = &mut self.classes;
+        self.dynamic.retain(|hash| {
+            let class = match classes.get(hash) {
+                Some(class) => class,
+                None => return false,
+            };
+            if Rc::strong_count(class) > 1 || is_in_document(class) {
+                return true;
+            }
+            if cfg!(target_arch = "wasm32") {
+                remove(class);
+            }
+            classes.remove(hash);
+            false
+        });
+        self.sweep_at = self.dynamic.len() * 2;
+        self.sweep_scheduled = false;
+    }
+}
+
+/// Adds a stylesheet to the document head
+fn inject(class: &str, sheet: &str) {
+    let document = gloo_utils::document();
+    let head = document.head().expect("document has no head");
+    let style = document.create_element("style").unwrap();
+    style.set_attribute(STYLE_ATTRIBUTE, class).unwrap();
+    style.set_text_content(Some(sheet));
+    head.append_child(&style).unwrap();
+}
+
+/// Check an element in the document has the class
+fn is_in_document(class: &str) -> bool {
+    cfg!(target_arch = "wasm32")
+        && matches!(
+            gloo_utils::document().query_selector(&format!(".{}", class)),
+            Ok(Some(_))
+        )
+}
+
+/// Removes the stylesheet of a style block from the document head
+fn remove(class: &str) {
+    let selector = format!("style[{}=\"{}\"]", STYLE_ATTRIBUTE, class);
+    if let Ok(Some(style)) = gloo_utils::document().query_selector(&selector) {
+        style.remove();
+    }
+}
+
+/// FNV-1a hash, which is stable across builds, so that class names do not change between them
+fn hash(css: &str) -> u64 {
+    css.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Returns the stylesheet of the style block `css` with all rules scoped to `selector`
+fn scope(selector: &str, css: &str) -> String {
+    let mut sheet = String::new();
+    scope_block(selector, &strip_comments(css), &mut sheet);
+    sheet
+}
+
+fn scope_block(selector: &str, css: &str, sheet: &mut String) {
+    let mut declarations = String::new();
+    let mut rules = String::new();
+
+    let mut rest = css;
+    while let Some((i, c)) = find_top_level(rest, &[';', '{']) {
+        let prelude = rest[..i].trim();
+        if c == ';' {
+            push_declaration(&mut declarations, prelude);
+            rest = &rest[i + 1..];
+            continue;
+        }
+
+        let body_start = i + 1;
+        let body_end = match find_top_level(&rest[body_start..], &['}']) {
+            Some((j, _)) => body_start + j,
+            None => rest.len(),
+        };
+        let body = &rest[body_start..body_end];
+        rest = rest.get(body_end + 1..).unwrap_or("");
+
+        if let Some(at_rule) = prelude.strip_prefix('@') {
+            let name = at_rule
+                .split(|c: char| c.is_whitespace() || c == '(')
+                .next()
+                .unwrap_or("");
+            if ["media", "supports", "container", "layer"].contains(&name) {
+                rules.push_str(prelude);
+                rules.push('{');
+                scope_block(selector, body, &mut rules);
+                rules.push('}');
+            } else {
+                rules.push_str(prelude);
+                rules.push('{');
+                rules.push_str(body.trim());
+                rules.push('}');
+            }
+        } else {
+            let nested = selector
+                .split(',')
+                .flat_map(|parent| {
+                    prelude.split(',').map(move |part| {
+                        let part = part.trim();
+                        if part.contains('&') {
+                            part.replace('&', parent)
+                        } else {
+                            format!("{} {}", parent, part)
+                        }
+                    })
+                })
+                .collect::<Vec<_>>()
+                .join(",");
+            scope_block(&nested, body, &mut rules);
+        }
+    }
+    push_declaration(&mut declarations, rest.trim());
+
+    if !declarations.is_empty() {
+        sheet.push_str(selector);
+        sheet.push('{');
+        sheet.push_str(&declarations);
+        sheet.push('}');
+    }
+    sheet.push_str(&rules);
+}
+
+fn push_declaration(declarations: &mut String, declaration: &str) {
+    if !declaration.is_empty() {
+        declarations.push_str(declaration);
+        declarations.push(';');
+    }
+}
+
+/// Returns the position of the first of `chars` in `css`, that is not inside of quotes,
+/// parentheses or a block
+fn find_top_level(css: &str, chars: &[char]) -> Option<(usize, char)> {
+    let mut quote = None;
+    let mut depth = 0usize;
+    for (i, c) in css.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, c) if depth == 0 && chars.contains(&c) => return Some((i, c)),
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (None, '(') | (None, '{') => depth += 1,
+            (None, ')') | (None, '}') => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn strip_comments(css: &str) -> String {
+    let mut stripped = String::with_capacity(css.len());
+    let mut rest = css;
+    while let Some(start) = rest.find("/*") {
+        stripped.push_str(&rest[..start]);
+        rest = match rest[start + 2..].find("*/") {
+            Some(end) => &rest[start + 2 + end + 2..],
+            None => "",
+        };
+    }
+    stripped.push_str(rest);
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scopes_declarations_and_nested_rules() {
+        assert_eq!(
+            scope(
+                ".c",
+                "color: red; /* comment */ &:hover, &.active { color: blue } p { margin: 0; } padding: 1px"
+            ),
+            ".c{color: red;padding: 1px;}.c:hover,.c.active{color: blue;}.c p{margin: 0;}"
+        );
+    }
+
+    #[test]
+    fn scopes_rules_nested_in_selector_lists() {
+        assert_eq!(
+            scope(".c", "a, b { &:hover { color: red } }"),
+            ".c a:hover,.c b:hover{color: red;}"
+        );
+    }
+
+    #[test]
+    fn scopes_at_rules() {
+        assert_eq!(
+            scope(
+                ".c",
+                "@media (max-width: 100px) { display: none; & > a { color: red } } \
+                 @keyframes spin { from { opacity: 0 } to { opacity: 1 } }"
+            ),
+            "@media (max-width: 100px){.c{display: none;}.c > a{color: red;}}\
+             @keyframes spin{from { opacity: 0 } to { opacity: 1 }}"
+        );
+    }
+
+    #[test]
+    fn keeps_semicolons_in_strings() {
+        assert_eq!(
+            scope(".c", "content: \"a;b\"; background: url(a;b)"),
+            ".c{content: \"a;b\";background: url(a;b);}"
+        );
+    }
+
+    #[test]
+    fn registers_style_blocks_once() {
+        let a = Css::new("color: red");
+        let b = Css::new("color: red");
+        let c = Css::new("color: blue");
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert!(a.class_name().starts_with("yew-css-"));
+    }
+
+    #[test]
+    fn escapes_interpolated_values() {
+        assert_eq!(escape("red"), "red");
+        assert_eq!(escape(&4), "4");
+        assert_eq!(
+            escape("red; } * { x: \"</style>"),
+            "red\\3b  \\7d  \\2a  \\7b  x: \\22 \\3c /style\\3e "
+        );
+        assert_eq!(
+            scope(".c", &format!("color: {}", escape("red} p { color: blue"))),
+            ".c{color: red\\7d  p \\7b  color: blue;}"
+        );
+    }
+
+    #[test]
+    fn keeps_sheets_of_live_dynamic_style_blocks() {
+        let registered = |css: &Css| {
+            REGISTRY.with(|registry| {
+                registry
+                    .borrow()
+                    .classes
+                    .values()
+                    .any(|class| **class == *css.class_name())
+            })
+        };
+
+        let fixed = Css::new("color: red");
+        let live = (0..MAX_DYNAMIC_SHEETS * 2)
+            .map(|i| Css::dynamic(&format!("color: #{:03x}", i)))
+            .collect::<Vec<_>>();
+        assert!(live.iter().all(registered));
+
+        let kept = live[0].clone();
+        drop(live);
+        REGISTRY.with(|registry| registry.borrow_mut().sweep());
+        assert!(registered(&fixed));
+        assert!(registered(&kept));
+        assert_eq!(REGISTRY.with(|registry| registry.borrow().dynamic.len()), 1);
+    }
+}
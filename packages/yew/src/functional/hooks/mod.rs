@@ -1,14 +1,18 @@
 mod use_context;
 mod use_effect;
+mod use_imperative_handle;
 mod use_reducer;
 mod use_ref;
 mod use_state;
+mod use_title;
 
 pub use use_context::*;
 pub use use_effect::*;
+pub use use_imperative_handle::*;
 pub use use_reducer::*;
 pub use use_ref::*;
 pub use use_state::*;
+pub use use_title::*;
 
 use crate::functional::{HookUpdater, CURRENT_HOOK};
 use std::cell::RefCell;
@@ -0,0 +1,77 @@
+use crate::functional::{FunctionComponent, FunctionProvider, CURRENT_HOOK};
+use crate::html::ComponentRef;
+use std::rc::Rc;
+
+/// This hook is used for exposing a handle to the component, which a parent can get through a
+/// [`ComponentRef`].
+///
+/// The handle is replaced on every render, so it should be a cheap value like a struct of
+/// [`Callback`](crate::Callback)s.
+///
+/// # Example
+/// ```rust
+/// # use yew::prelude::*;
+/// use yew::html::ComponentRef;
+///
+/// #[derive(Clone)]
+/// struct CounterHandle {
+///     reset: Callback<()>,
+/// }
+///
+/// #[function_component(Counter)]
+/// fn counter() -> Html {
+///     let count = use_state(|| 0);
+///     use_imperative_handle(CounterHandle {
+///         reset: {
+///             let count = count.clone();
+///             Callback::from(move |_| count.set(0))
+///         },
+///     });
+///
+///     let onclick = {
+///         let count = count.clone();
+///         Callback::from(move |_| count.set(*count + 1))
+///     };
+///     html! { <button {onclick}>{ *count }</button> }
+/// }
+///
+/// #[function_component(App)]
+/// fn app() -> Html {
+///     let counter = use_state(ComponentRef::<Counter>::default);
+///     let onclick = {
+///         let counter = (*counter).clone();
+///         Callback::from(move |_| {
+///             if let Some(handle) = counter.handle::<CounterHandle>() {
+///                 handle.reset.emit(());
+///             }
+///         })
+///     };
+///
+///     html! {
+///         <>
+///             <Counter ref={(*counter).clone()} />
+///             <button {onclick}>{ "Reset" }</button>
+///         </>
+///     }
+/// }
+/// ```
+pub fn use_imperative_handle<H: 'static>(handle: H) {
+    if !CURRENT_HOOK.is_set() {
+        panic!("No current hook state. `use_imperative_handle` can only be called inside function components");
+    }
+    CURRENT_HOOK.with(|state| state.imperative_handle = Some(Rc::new(handle)));
+}
+
+impl<T: FunctionProvider + 'static> ComponentRef<FunctionComponent<T>> {
+    /// Get the handle the referenced component exposes with [`use_imperative_handle`], if it is
+    /// mounted and the handle is of type `H`.
+    ///
+    /// Returns `None`, while the referenced component is rendering.
+    pub fn handle<H: 'static>(&self) -> Option<Rc<H>> {
+        let scope = self.get()?;
+        let component = scope.get_component()?;
+        let hook_state = component.hook_state.try_borrow().ok()?;
+        let handle = hook_state.imperative_handle.clone()?;
+        handle.downcast().ok()
+    }
+}
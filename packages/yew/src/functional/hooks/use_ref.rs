@@ -1,4 +1,5 @@
-use crate::{functional::use_hook, NodeRef};
+use crate::functional::{get_forwarded_ref, use_hook};
+use crate::NodeRef;
 use std::{cell::RefCell, rc::Rc};
 
 /// This hook is used for obtaining a mutable reference to a stateful value.
@@ -127,3 +128,62 @@ pub fn use_ref<T: 'static>(initial_value: impl FnOnce() -> T) -> Rc<T> {
 pub fn use_node_ref() -> NodeRef {
     use_hook(NodeRef::default, |state, _| state.clone(), |_| {})
 }
+
+/// This hook is used for obtaining the `ref` passed to a function component, that forwards its
+/// ref with `#[function_component(Name, forward_ref)]`.
+///
+/// Set the returned [`NodeRef`] on an inner element to expose that element to the parent.
+///
+/// # Example
+/// ```rust
+/// # use yew::prelude::*;
+/// # use web_sys::HtmlInputElement;
+/// #
+/// #[function_component(Input, forward_ref)]
+/// fn input() -> Html {
+///     let node_ref = use_forwarded_ref();
+///
+///     html! {
+///         <label>
+///             { "Name" }
+///             <input ref={node_ref} />
+///         </label>
+///     }
+/// }
+///
+/// #[function_component(Form)]
+/// fn form() -> Html {
+///     let input_ref = use_node_ref();
+///     let onclick = {
+///         let input_ref = input_ref.clone();
+///         Callback::from(move |_| {
+///             if let Some(input) = input_ref.cast::<HtmlInputElement>() {
+///                 input.focus().unwrap();
+///             }
+///         })
+///     };
+///
+///     html! {
+///         <>
+///             <Input ref={input_ref} />
+///             <button {onclick}>{ "Edit" }</button>
+///         </>
+///     }
+/// }
+/// ```
+///
+/// # Panics
+///
+/// Panics, if the component does not forward its ref. The ref of such a component references
+/// its first node, so a forwarded ref would never be set.
+pub fn use_forwarded_ref() -> NodeRef {
+    get_forwarded_ref()
+        .expect(
+            "No current hook state. `use_forwarded_ref` can only be called inside function \
+             components",
+        )
+        .expect(
+            "`use_forwarded_ref` can only be called inside function components declared with \
+             `#[function_component(Name, forward_ref)]`",
+        )
+}
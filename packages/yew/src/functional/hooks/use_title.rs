@@ -0,0 +1,51 @@
+use crate::functional::{get_current_scope, use_hook};
+use crate::head::{contribute, withdraw};
+use crate::virtual_dom::VTag;
+
+/// This hook is used for setting the title of the document.
+///
+/// The title is contributed to the document head like a `<title>` in a [`Head`](crate::head::Head),
+/// so the title of the innermost component wins and the previous title is restored, when the
+/// component is destroyed.
+///
+/// # Example
+/// ```rust
+/// # use yew::prelude::*;
+/// #
+/// #[function_component(Settings)]
+/// fn settings() -> Html {
+///     use_title("Settings");
+///
+///     html! { <h1>{ "Settings" }</h1> }
+/// }
+/// ```
+pub fn use_title(title: impl Into<String>) {
+    struct UseTitleState {
+        title: Option<String>,
+        contribution: Option<usize>,
+    }
+
+    let title = title.into();
+    let scope = get_current_scope()
+        .expect("No current Scope. `use_title` can only be called inside function components");
+
+    use_hook(
+        || UseTitleState {
+            title: None,
+            contribution: None,
+        },
+        move |state: &mut UseTitleState, _| {
+            if state.title.as_ref() != Some(&title) {
+                let mut tag = VTag::new("title");
+                tag.add_child(title.clone().into());
+                contribute(&mut state.contribution, &scope, vec![tag.into()]);
+                state.title = Some(title);
+            }
+        },
+        |state| {
+            if let Some(id) = state.contribution.take() {
+                withdraw(id);
+            }
+        },
+    )
+}
@@ -14,7 +14,7 @@
 //! More details about function components and Hooks can be found on [Yew Docs](https://yew.rs/next/concepts/function-components)
 
 use crate::html::AnyScope;
-use crate::{Component, Html, Properties};
+use crate::{Component, Html, NodeRef, Properties};
 use scoped_tls_hkt::scoped_thread_local;
 use std::cell::RefCell;
 use std::fmt;
@@ -60,6 +60,10 @@ type ProcessMessage = Rc<dyn Fn(Msg, bool)>;
 struct HookState {
     counter: usize,
     scope: AnyScope,
+    /// The `ref` passed to the component, if it forwards its ref
+    forwarded_ref: Option<NodeRef>,
+    /// Handle set with [`use_imperative_handle`](hooks::use_imperative_handle())
+    imperative_handle: Option<Rc<dyn std::any::Any>>,
     process_message: ProcessMessage,
     hooks: Vec<Rc<RefCell<dyn std::any::Any>>>,
     destroy_listeners: Vec<Box<dyn FnOnce()>>,
@@ -70,6 +74,12 @@ pub trait FunctionProvider {
     /// Properties for the Function Component.
     type TProps: Properties + PartialEq;
 
+    /// Whether the `ref` passed to the component is forwarded to
+    /// [`use_forwarded_ref`](hooks::use_forwarded_ref()).
+    ///
+    /// Equivalent of [`Component::FORWARD_REF`].
+    const FORWARD_REF: bool = false;
+
     /// Render the component. This function returns the [`Html`] to be rendered for the component.
     ///
     /// Equivalent of [`Component::view`].
@@ -93,11 +103,22 @@ impl<T> FunctionComponent<T>
 where
     T: FunctionProvider,
 {
-    fn with_hook_state<R>(&self, f: impl FnOnce() -> R) -> R {
+    fn with_hook_state<R>(&self, ctx: &Context<Self>, f: impl FnOnce() -> R) -> R {
         let mut hook_state = self.hook_state.borrow_mut();
         hook_state.counter = 0;
+        hook_state.forwarded_ref = Self::forwarded_ref(ctx);
+        // The handle is exposed again by the render, if it still calls `use_imperative_handle`
+        hook_state.imperative_handle = None;
         CURRENT_HOOK.set(&mut *hook_state, f)
     }
+
+    fn forwarded_ref(ctx: &Context<Self>) -> Option<NodeRef> {
+        if T::FORWARD_REF {
+            Some(ctx.forwarded_ref().clone())
+        } else {
+            None
+        }
+    }
 }
 
 impl<T: 'static> Component for FunctionComponent<T>
@@ -107,6 +128,8 @@ where
     type Message = Box<dyn FnOnce() -> bool>;
     type Properties = T::TProps;
 
+    const FORWARD_REF: bool = T::FORWARD_REF;
+
     fn create(ctx: &Context<Self>) -> Self {
         let scope = AnyScope::from(ctx.link().clone());
         let message_queue = MsgQueue::default();
@@ -117,6 +140,8 @@ where
             hook_state: RefCell::new(HookState {
                 counter: 0,
                 scope,
+                forwarded_ref: Self::forwarded_ref(ctx),
+                imperative_handle: None,
                 process_message: {
                     let scope = ctx.link().clone();
                     Rc::new(move |msg, post_render| {
@@ -138,7 +163,7 @@ where
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
-        self.with_hook_state(|| T::run(&*ctx.props()))
+        self.with_hook_state(ctx, || T::run(&*ctx.props()))
     }
 
     fn rendered(&mut self, ctx: &Context<Self>, _first_render: bool) {
@@ -163,6 +188,16 @@ pub(crate) fn get_current_scope() -> Option<AnyScope> {
     }
 }
 
+/// Returns the forwarded ref of the current function component, which is `None`, if it does not
+/// forward its ref
+pub(crate) fn get_forwarded_ref() -> Option<Option<NodeRef>> {
+    if CURRENT_HOOK.is_set() {
+        Some(CURRENT_HOOK.with(|state| state.forwarded_ref.clone()))
+    } else {
+        None
+    }
+}
+
 #[derive(Clone, Default)]
 struct MsgQueue(Rc<RefCell<Vec<Msg>>>);
 
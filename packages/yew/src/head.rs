@@ -0,0 +1,431 @@
+//! This module defines the `Head` component, which manages the tags of the document head.
+//!
+//! Components anywhere in the tree can contribute `<title>`, `<meta>`, `<link>` and other head
+//! tags. Contributions for the same tag are deduplicated, with the contribution of the innermost
+//! component winning, and are removed, when the contributing component is destroyed.
+//!
+//! Tags are considered the same, if they have the same key or otherwise:
+//! - `<title>` and `<base>` tags are unique
+//! - `<meta>` tags with the same `charset` or the same `name`, `property`, `http-equiv` or
+//!   `itemprop`
+//! - `<link rel="canonical">` tags and `<link>` tags with the same `rel` and `href`
+//!
+//! All other tags and raw HTML are never deduplicated.
+
+use crate::html::AnyScope;
+use crate::virtual_dom::{VDiff, VNode, VTag};
+use crate::{html, Children, Component, Context, Html, NodeRef, Properties};
+use gloo_utils::document;
+use slab::Slab;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+thread_local! {
+    static HEAD: RefCell<HeadManager> = Default::default();
+}
+
+/// Props for [`Head`]
+#[derive(Debug, Clone, PartialEq, Properties)]
+pub struct HeadProps {
+    /// Tags to add to the document head
+    pub children: Children,
+}
+
+/// Adds its children to the document head instead of rendering them in place.
+///
+/// # Example
+/// ```rust
+/// # use yew::prelude::*;
+/// use yew::head::Head;
+///
+/// #[function_component(Article)]
+/// fn article() -> Html {
+///     html! {
+///         <article>
+///             <Head>
+///                 <title>{ "Article" }</title>
+///                 <meta name="description" content="An article" />
+///             </Head>
+///             <p>{ "Content" }</p>
+///         </article>
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Head {
+    contribution: Option<usize>,
+}
+
+impl Component for Head {
+    type Message = ();
+    type Properties = HeadProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self { contribution: None }
+    }
+
+    fn view(&self, _ctx: &Context<Self>) -> Html {
+        html! {}
+    }
+
+    fn rendered(&mut self, ctx: &Context<Self>, _first_render: bool) {
+        let mut nodes = Vec::new();
+        for child in ctx.props().children.iter() {
+            collect_nodes(child, &mut nodes);
+        }
+        contribute(&mut self.contribution, &ctx.link().clone().into(), nodes);
+    }
+
+    fn destroy(&mut self, _ctx: &Context<Self>) {
+        if let Some(id) = self.contribution.take() {
+            withdraw(id);
+        }
+    }
+}
+
+/// Collects the tags and raw HTML of `node`
+fn collect_nodes(node: VNode, nodes: &mut Vec<VNode>) {
+    match node {
+        VNode::VTag(_) | VNode::VRaw(_) => nodes.push(node),
+        VNode::VList(list) => {
+            for node in list.iter() {
+                collect_nodes(node.clone(), nodes);
+            }
+        }
+        VNode::VMemo(memo) => collect_nodes(memo.render_subtree(), nodes),
+        _ => {}
+    }
+}
+
+/// Sets the head tags and raw HTML contributed by the component of `scope` to `nodes`.
+///
+/// `id` identifies the contribution and is assigned on the first call.
+pub(crate) fn contribute(id: &mut Option<usize>, scope: &AnyScope, nodes: Vec<VNode>) {
+    HEAD.with(|head| {
+        let mut head = head.borrow_mut();
+        match *id {
+            Some(id) => head.contributions[id].nodes = nodes,
+            None => {
+                let seq = head.seq_counter;
+                head.seq_counter += 1;
+                *id = Some(head.contributions.insert(Contribution {
+                    scope: scope.clone(),
+                    depth: depth(scope),
+                    seq,
+                    nodes,
+                }));
+            }
+        }
+    });
+    sync();
+}
+
+/// Removes a contribution added with [contribute]
+pub(crate) fn withdraw(id: usize) {
+    HEAD.with(|head| {
+        head.borrow_mut().contributions.remove(id);
+    });
+    sync();
+}
+
+/// Renders the winning tags into the document head.
+///
+/// The head is not borrowed, while the tags are applied, so components inside the tags can
+/// contribute to the head as well. Their contributions are rendered right after.
+fn sync() {
+    let start = HEAD.with(|head| {
+        let mut head = head.borrow_mut();
+        if head.syncing {
+            head.dirty = true;
+            false
+        } else {
+            head.syncing = true;
+            true
+        }
+    });
+    if !start {
+        return;
+    }
+
+    loop {
+        let (winners, rendered) = HEAD.with(|head| {
+            let mut head = head.borrow_mut();
+            head.dirty = false;
+            let mut winners = head.winners();
+            head.sync_title(&mut winners);
+            (winners, std::mem::take(&mut head.rendered))
+        });
+        let rendered = render(winners, rendered);
+        let done = HEAD.with(|head| {
+            let mut head = head.borrow_mut();
+            head.rendered = rendered;
+            head.syncing = head.dirty;
+            !head.dirty
+        });
+        if done {
+            break;
+        }
+    }
+}
+
+/// Renders `winners` into the document head, diffing them with the `rendered` nodes of the same
+/// key, and removes the remaining rendered nodes. Returns the rendered winners.
+fn render(
+    winners: Vec<(String, AnyScope, VNode)>,
+    mut rendered: HashMap<String, VNode>,
+) -> HashMap<String, VNode> {
+    let parent = document().head().expect("document has no head");
+    let mut next = HashMap::with_capacity(winners.len());
+    for (key, scope, mut node) in winners {
+        match rendered.remove(&key) {
+            Some(ancestor) if ancestor == node => node = ancestor,
+            ancestor => {
+                node.apply(&scope, &parent, NodeRef::default(), ancestor);
+            }
+        }
+        next.insert(key, node);
+    }
+    for (_, mut node) in rendered.drain() {
+        node.detach(&parent);
+    }
+    next
+}
+
+/// Returns the number of ancestors of the component of `scope`
+fn depth(scope: &AnyScope) -> usize {
+    let mut depth = 0;
+    let mut parent = scope.get_parent();
+    while let Some(scope) = parent {
+        depth += 1;
+        parent = scope.get_parent();
+    }
+    depth
+}
+
+/// Head tags of a component
+struct Contribution {
+    scope: AnyScope,
+    depth: usize,
+
+    /// Order in which the contributions were added
+    seq: usize,
+
+    /// Contributed tags and raw HTML
+    nodes: Vec<VNode>,
+}
+
+impl Contribution {
+    /// Contributions of inner components win over their ancestors. Later contributions win over
+    /// earlier contributions of components at the same depth.
+    fn rank(&self) -> (usize, usize) {
+        (self.depth, self.seq)
+    }
+}
+
+#[derive(Default)]
+struct HeadManager {
+    /// Counter for the order of contributions
+    seq_counter: usize,
+
+    contributions: Slab<Contribution>,
+
+    /// Tags currently rendered into the document head by their deduplication key
+    rendered: HashMap<String, VNode>,
+
+    /// Title of the document before any title was contributed
+    default_title: Option<String>,
+
+    /// Set while the tags are rendered into the document head
+    syncing: bool,
+
+    /// Set, if the contributions changed while the tags were rendered
+    dirty: bool,
+}
+
+impl HeadManager {
+    /// Returns the winning node of every deduplication key with the scope of its contribution in
+    /// the order they were contributed
+    fn winners(&self) -> Vec<(String, AnyScope, VNode)> {
+        let mut winners: HashMap<String, (&Contribution, usize)> = HashMap::new();
+        for (_, contribution) in self.contributions.iter() {
+            for (i, node) in contribution.nodes.iter().enumerate() {
+                let key = dedup_key(node).unwrap_or_else(|| format!("#{}:{}", contribution.seq, i));
+                let winner = winners.entry(key).or_insert((contribution, i));
+                if contribution.rank() > winner.0.rank() {
+                    *winner = (contribution, i);
+                }
+            }
+        }
+
+        let mut winners: Vec<_> = winners.into_iter().collect();
+        winners.sort_by_key(|(_, (contribution, i))| (contribution.seq, *i));
+        winners
+            .into_iter()
+            .map(|(key, (contribution, i))| {
+                (
+                    key,
+                    contribution.scope.clone(),
+                    contribution.nodes[i].clone(),
+                )
+            })
+            .collect()
+    }
+
+    /// Sets the document title from the winning `<title>` and removes it from `winners`.
+    ///
+    /// The document title is not taken from a `<title>` element appended after the one of the
+    /// page, so it is set directly.
+    fn sync_title(&mut self, winners: &mut Vec<(String, AnyScope, VNode)>) {
+        match winners.iter().position(|(key, _, _)| key == "title") {
+            Some(index) => {
+                let (_, _, node) = winners.remove(index);
+                if self.default_title.is_none() {
+                    self.default_title = Some(document().title());
+                }
+                if let VNode::VTag(tag) = node {
+                    document().set_title(&text_of(&tag));
+                }
+            }
+            None => {
+                if let Some(title) = self.default_title.take() {
+                    document().set_title(&title);
+                }
+            }
+        }
+    }
+}
+
+/// Returns the key, by which `node` is deduplicated with the nodes of other contributions, or
+/// `None`, if it is never deduplicated
+fn dedup_key(node: &VNode) -> Option<String> {
+    let tag = match node {
+        VNode::VTag(tag) => tag,
+        _ => return None,
+    };
+    if let Some(key) = &tag.key {
+        return Some(format!("key:{}", key));
+    }
+    let attr = |name: &str| {
+        tag.attributes
+            .iter()
+            .find(|(k, _)| *k == name)
+            .map(|(_, v)| v)
+    };
+    match tag.tag() {
+        "title" | "base" => Some(tag.tag().to_owned()),
+        "meta" => {
+            if attr("charset").is_some() {
+                return Some("meta charset".to_owned());
+            }
+            ["name", "property", "http-equiv", "itemprop"]
+                .iter()
+                .find_map(|name| attr(name).map(|value| format!("meta {}={}", name, value)))
+        }
+        "link" => match (attr("rel"), attr("href")) {
+            (Some("canonical"), _) => Some("link canonical".to_owned()),
+            (Some(rel), Some(href)) => Some(format!("link {} {}", rel, href)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns the text content of `tag`
+fn text_of(tag: &VTag) -> String {
+    fn write_text(node: &VNode, text: &mut String) {
+        match node {
+            VNode::VText(t) => text.push_str(&t.text),
+            VNode::VList(list) => list.iter().for_each(|node| write_text(node, text)),
+            VNode::VTag(tag) => tag
+                .children()
+                .iter()
+                .for_each(|node| write_text(node, text)),
+            _ => {}
+        }
+    }
+
+    let mut text = String::new();
+    for node in tag.children().iter() {
+        write_text(node, &mut text);
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag_of(node: Html) -> VTag {
+        match node {
+            VNode::VTag(tag) => *tag,
+            _ => panic!("expected tag"),
+        }
+    }
+
+    #[test]
+    fn dedup_keys() {
+        let key = |node: Html| dedup_key(&node);
+
+        assert_eq!(
+            key(html! { <title>{"a"}</title> }).as_deref(),
+            Some("title")
+        );
+        assert_eq!(
+            key(html! { <meta name="description" content="a" /> }),
+            key(html! { <meta name="description" content="b" /> })
+        );
+        assert_ne!(
+            key(html! { <meta name="description" content="a" /> }),
+            key(html! { <meta property="description" content="a" /> })
+        );
+        assert_eq!(
+            key(html! { <link rel="canonical" href="/a" /> }),
+            key(html! { <link rel="canonical" href="/b" /> })
+        );
+        assert_ne!(
+            key(html! { <link rel="stylesheet" href="/a" /> }),
+            key(html! { <link rel="stylesheet" href="/b" /> })
+        );
+        assert_eq!(
+            key(html! { <script key="analytics" src="/a" /> }),
+            key(html! { <script key="analytics" src="/b" /> })
+        );
+        assert_eq!(key(html! { <script src="/a" /> }), None);
+    }
+
+    #[test]
+    fn innermost_contributions_win() {
+        let scope = AnyScope::test();
+        let mut head = HeadManager::default();
+        let mut contribute = |depth, nodes: Html| {
+            let seq = head.contributions.len();
+            head.contributions.insert(Contribution {
+                scope: scope.clone(),
+                depth,
+                seq,
+                nodes: vec![nodes],
+            });
+        };
+        contribute(0, html! { <title>{"Outer"}</title> });
+        contribute(0, html! { <meta name="description" content="a \"b\"" /> });
+        contribute(1, html! { <title>{"Inner & more"}</title> });
+
+        let winners = head
+            .winners()
+            .into_iter()
+            .map(|(_, _, node)| tag_of(node))
+            .collect::<Vec<_>>();
+        let tags = winners.iter().map(|tag| tag.tag()).collect::<Vec<_>>();
+        assert_eq!(tags, vec!["meta", "title"]);
+        assert_eq!(text_of(&winners[1]), "Inner & more");
+    }
+
+    #[test]
+    fn title_text() {
+        let title = "dynamic".to_owned();
+        assert_eq!(
+            text_of(&tag_of(html! { <title>{"a "}{ title }</title> })),
+            "a dynamic"
+        );
+    }
+}
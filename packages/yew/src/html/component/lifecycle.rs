@@ -28,6 +28,7 @@ impl<COMP: Component> ComponentState<COMP> {
         next_sibling: NodeRef,
         root_node: VNode,
         node_ref: NodeRef,
+        forwarded_ref: Option<NodeRef>,
         scope: Scope<COMP>,
         props: Rc<COMP::Properties>,
     ) -> Self {
@@ -37,7 +38,11 @@ impl<COMP: Component> ComponentState<COMP> {
 
             scope.to_any().vcomp_id
         };
-        let context = Context { scope, props };
+        let context = Context {
+            scope,
+            props,
+            forwarded_ref: forwarded_ref.unwrap_or_default(),
+        };
 
         let component = Box::new(COMP::create(&context));
         Self {
@@ -60,6 +65,7 @@ pub(crate) struct CreateRunner<COMP: Component> {
     pub(crate) next_sibling: NodeRef,
     pub(crate) placeholder: VNode,
     pub(crate) node_ref: NodeRef,
+    pub(crate) forwarded_ref: Option<NodeRef>,
     pub(crate) props: Rc<COMP::Properties>,
     pub(crate) scope: Scope<COMP>,
 }
@@ -76,6 +82,7 @@ impl<COMP: Component> Runnable for CreateRunner<COMP> {
                 self.next_sibling,
                 self.placeholder,
                 self.node_ref,
+                self.forwarded_ref,
                 self.scope.clone(),
                 self.props,
             ));
@@ -88,8 +95,8 @@ pub(crate) enum UpdateEvent<COMP: Component> {
     Message(COMP::Message),
     /// Wraps batch of messages for a component.
     MessageBatch(Vec<COMP::Message>),
-    /// Wraps properties, node ref, and next sibling for a component.
-    Properties(Rc<COMP::Properties>, NodeRef, NodeRef),
+    /// Wraps properties, node ref, forwarded ref, and next sibling for a component.
+    Properties(Rc<COMP::Properties>, NodeRef, Option<NodeRef>, NodeRef),
 }
 
 pub(crate) struct UpdateRunner<COMP: Component> {
@@ -107,9 +114,14 @@ impl<COMP: Component> Runnable for UpdateRunner<COMP> {
                         state.component.update(&state.context, msg) || acc
                     })
                 }
-                UpdateEvent::Properties(props, node_ref, next_sibling) => {
+                UpdateEvent::Properties(props, node_ref, forwarded_ref, next_sibling) => {
                     // When components are updated, a new node ref could have been passed in
                     state.node_ref = node_ref;
+                    if let Some(forwarded_ref) = forwarded_ref {
+                        // The new ref references the forwarded node until the next render
+                        forwarded_ref.reuse(state.context.forwarded_ref.clone());
+                        state.context.forwarded_ref = forwarded_ref;
+                    }
                     // When components are updated, their siblings were likely also updated
                     state.next_sibling = next_sibling;
                     // Only trigger changed if props were changed
@@ -328,7 +340,13 @@ mod tests {
         let lifecycle = props.lifecycle.clone();
 
         lifecycle.borrow_mut().clear();
-        scope.mount_in_place(el, NodeRef::default(), NodeRef::default(), Rc::new(props));
+        scope.mount_in_place(
+            el,
+            NodeRef::default(),
+            NodeRef::default(),
+            None,
+            Rc::new(props),
+        );
 
         assert_eq!(&lifecycle.borrow_mut().deref()[..], expected);
     }
@@ -5,7 +5,7 @@ mod lifecycle;
 mod properties;
 mod scope;
 
-use super::Html;
+use super::{Html, NodeRef};
 pub use children::*;
 pub use properties::*;
 pub(crate) use scope::Scoped;
@@ -18,6 +18,7 @@ use std::rc::Rc;
 pub struct Context<COMP: Component> {
     pub(crate) scope: Scope<COMP>,
     pub(crate) props: Rc<COMP::Properties>,
+    pub(crate) forwarded_ref: NodeRef,
 }
 
 impl<COMP: Component> Context<COMP> {
@@ -32,6 +33,13 @@ impl<COMP: Component> Context<COMP> {
     pub fn props(&self) -> &COMP::Properties {
         &*self.props
     }
+
+    /// The `ref` passed to the component, if it forwards its ref with
+    /// [`FORWARD_REF`](Component::FORWARD_REF)
+    #[inline]
+    pub fn forwarded_ref(&self) -> &NodeRef {
+        &self.forwarded_ref
+    }
 }
 
 /// Components are the basic building blocks of the UI in a Yew app. Each Component
@@ -51,6 +59,13 @@ pub trait Component: Sized + 'static {
     /// receive new properties in the context passed to the `changed` lifecycle method.
     type Properties: Properties;
 
+    /// Whether the `ref` passed to the component is forwarded to [`Context::forwarded_ref`]
+    /// instead of referencing the first node of the component.
+    ///
+    /// This allows a component to expose one of its inner elements, like the `<input>` of a
+    /// wrapper component.
+    const FORWARD_REF: bool = false;
+
     /// Called when component is created.
     fn create(ctx: &Context<Self>) -> Self;
 
@@ -11,6 +11,7 @@ use crate::callback::Callback;
 use crate::context::{ContextHandle, ContextProvider};
 use crate::html::NodeRef;
 use crate::scheduler::{self, Shared};
+use crate::virtual_dom::listeners::EventRoot;
 use crate::virtual_dom::{insert_node, VNode};
 use gloo_utils::document;
 use std::any::{Any, TypeId};
@@ -28,6 +29,7 @@ pub struct AnyScope {
     type_id: TypeId,
     parent: Option<Rc<AnyScope>>,
     state: Rc<dyn Any>,
+    pub(crate) event_root: EventRoot,
 
     // Used for debug logging
     #[cfg(debug_assertions)]
@@ -40,6 +42,7 @@ impl<COMP: Component> From<Scope<COMP>> for AnyScope {
             type_id: TypeId::of::<COMP>(),
             parent: scope.parent,
             state: scope.state,
+            event_root: scope.event_root,
 
             #[cfg(debug_assertions)]
             vcomp_id: scope.vcomp_id,
@@ -54,6 +57,7 @@ impl AnyScope {
             type_id: TypeId::of::<()>(),
             parent: None,
             state: Rc::new(()),
+            event_root: EventRoot::default(),
 
             #[cfg(debug_assertions)]
             vcomp_id: 0,
@@ -87,6 +91,7 @@ impl AnyScope {
         Scope {
             parent: self.parent,
             state,
+            event_root: self.event_root,
 
             #[cfg(debug_assertions)]
             vcomp_id,
@@ -151,6 +156,8 @@ impl<COMP: Component> Scoped for Scope<COMP> {
 pub struct Scope<COMP: Component> {
     parent: Option<Rc<AnyScope>>,
     pub(crate) state: Shared<Option<ComponentState<COMP>>>,
+    /// Delegation root for events of the app the component belongs to
+    pub(crate) event_root: EventRoot,
 
     // Used for debug logging
     #[cfg(debug_assertions)]
@@ -168,6 +175,7 @@ impl<COMP: Component> Clone for Scope<COMP> {
         Scope {
             parent: self.parent.clone(),
             state: self.state.clone(),
+            event_root: self.event_root,
 
             #[cfg(debug_assertions)]
             vcomp_id: self.vcomp_id,
@@ -200,6 +208,7 @@ impl<COMP: Component> Scope<COMP> {
 
         Scope {
             state,
+            event_root: parent.as_ref().map(|p| p.event_root).unwrap_or_default(),
             parent,
 
             #[cfg(debug_assertions)]
@@ -213,6 +222,7 @@ impl<COMP: Component> Scope<COMP> {
         parent: Element,
         next_sibling: NodeRef,
         node_ref: NodeRef,
+        forwarded_ref: Option<NodeRef>,
         props: Rc<COMP::Properties>,
     ) {
         #[cfg(debug_assertions)]
@@ -230,6 +240,7 @@ impl<COMP: Component> Scope<COMP> {
                 next_sibling,
                 placeholder,
                 node_ref,
+                forwarded_ref,
                 props,
                 scope: self.clone(),
             },
@@ -248,12 +259,18 @@ impl<COMP: Component> Scope<COMP> {
         &self,
         props: Rc<COMP::Properties>,
         node_ref: NodeRef,
+        forwarded_ref: Option<NodeRef>,
         next_sibling: NodeRef,
     ) {
         #[cfg(debug_assertions)]
         crate::virtual_dom::vcomp::log_event(self.vcomp_id, "reuse");
 
-        self.push_update(UpdateEvent::Properties(props, node_ref, next_sibling));
+        self.push_update(UpdateEvent::Properties(
+            props,
+            node_ref,
+            forwarded_ref,
+            next_sibling,
+        ));
     }
 
     fn push_update(&self, event: UpdateEvent<COMP>) {
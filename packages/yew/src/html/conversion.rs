@@ -1,6 +1,8 @@
-use super::{Component, NodeRef, Scope};
+use super::{Component, ComponentRef, NodeRef, Scope};
+use crate::callback::Callback;
 use crate::virtual_dom::AttrValue;
 use std::{borrow::Cow, rc::Rc};
+use web_sys::Element;
 
 /// Marker trait for types that the [`html!`] macro may clone implicitly.
 pub trait ImplicitClone: Clone {}
@@ -13,6 +15,7 @@ impl<T> ImplicitClone for Rc<T> {}
 
 impl ImplicitClone for NodeRef {}
 impl<Comp: Component> ImplicitClone for Scope<Comp> {}
+impl<Comp: Component> ImplicitClone for ComponentRef<Comp> {}
 // TODO there are still a few missing like AgentScope
 
 /// A trait similar to `Into<T>` which allows conversion to a value of a `Properties` struct.
@@ -87,6 +90,29 @@ impl_into_prop!(|value: &'static str| -> String { value.to_owned() });
 impl_into_prop!(|value: &'static str| -> AttrValue { AttrValue::Static(value) });
 impl_into_prop!(|value: String| -> AttrValue { AttrValue::Owned(value) });
 
+impl_into_prop!(|value: Callback<Option<Element>>| -> NodeRef { value.into() });
+
+/// Conversion into the `ref` of a component of type `COMP`.
+///
+/// Any value, that can be the `ref` of an element, can be the `ref` of a component, but a
+/// [ComponentRef] only of a component of the type it references.
+pub trait IntoComponentRef<COMP: Component> {
+    /// Convert `self` to the reference of the component
+    fn into_component_ref(self) -> NodeRef;
+}
+
+impl<COMP: Component, T: IntoPropValue<NodeRef>> IntoComponentRef<COMP> for T {
+    fn into_component_ref(self) -> NodeRef {
+        self.into_prop_value()
+    }
+}
+
+impl<COMP: Component> IntoComponentRef<COMP> for ComponentRef<COMP> {
+    fn into_component_ref(self) -> NodeRef {
+        self.into()
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
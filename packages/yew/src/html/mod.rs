@@ -4,16 +4,21 @@ mod classes;
 mod component;
 mod conversion;
 mod listener;
+mod style;
 
 pub use classes::*;
 pub use component::*;
 pub use conversion::*;
 pub use listener::*;
+pub use style::*;
 
-use crate::virtual_dom::{VNode, VPortal};
+use crate::callback::Callback;
+use crate::virtual_dom::{VMemo, VNode, VPortal};
+use std::any::TypeId;
 use std::cell::RefCell;
+use std::marker::PhantomData;
 use std::rc::Rc;
-use wasm_bindgen::JsValue;
+use wasm_bindgen::{JsCast, JsValue};
 use web_sys::{Element, Node};
 
 /// A type which expected as a result of `view` function implementation.
@@ -56,6 +61,23 @@ pub type Html = VNode;
 ///     }
 /// }
 /// ```
+///
+/// # Callback refs
+/// A `NodeRef` can also be created from a `Callback<Option<Element>>`, which is called with the
+/// element, when it is attached, and with `None`, when it is detached.
+/// ```
+/// # use yew::prelude::*;
+/// use web_sys::Element;
+///
+/// # fn view() -> Html {
+/// let onref = Callback::from(|el: Option<Element>| {
+///     if let Some(el) = el {
+///         el.scroll_into_view();
+///     }
+/// });
+/// html! { <div ref={onref} /> }
+/// # }
+/// ```
 /// ## Relevant examples
 /// - [Node Refs](https://github.com/yewstack/yew/tree/master/examples/node_refs)
 #[derive(Default, Clone)]
@@ -77,10 +99,16 @@ impl std::fmt::Debug for NodeRef {
     }
 }
 
-#[derive(PartialEq, Debug, Default, Clone)]
+#[derive(Debug, Default, Clone)]
 struct NodeRefInner {
     node: Option<Node>,
     link: Option<NodeRef>,
+    /// Called with the referenced element, when it changes
+    callback: Option<Callback<Option<Element>>>,
+    /// Node the callback was last called with
+    emitted: Option<Node>,
+    /// Scope of the component this reference was passed to
+    scope: Option<AnyScope>,
 }
 
 impl NodeRef {
@@ -105,9 +133,43 @@ impl NodeRef {
 
     /// Place a Node in a reference for later use
     pub(crate) fn set(&self, node: Option<Node>) {
-        let mut this = self.0.borrow_mut();
-        this.node = node;
-        this.link = None;
+        {
+            let mut this = self.0.borrow_mut();
+            this.node = node;
+            this.link = None;
+        }
+        self.notify();
+    }
+
+    /// Calls the callback of a callback ref with the referenced element, if it changed since the
+    /// last call
+    fn notify(&self) {
+        let (callback, node) = {
+            let node = self.get();
+            let mut this = self.0.borrow_mut();
+            match &this.callback {
+                Some(callback) if this.emitted != node => {
+                    let callback = callback.clone();
+                    this.emitted = node.clone();
+                    (callback, node)
+                }
+                _ => return,
+            }
+        };
+        callback.emit(node.and_then(|node| node.dyn_into().ok()));
+    }
+
+    /// Returns whether both references are callback refs with the same callback
+    pub(crate) fn has_same_callback(&self, other: &Self) -> bool {
+        match (&self.0.borrow().callback, &other.0.borrow().callback) {
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    /// Set the scope of the component this reference was passed to
+    pub(crate) fn set_scope(&self, scope: AnyScope) {
+        self.0.borrow_mut().scope = Some(scope);
     }
 
     /// Link a downstream `NodeRef`
@@ -117,9 +179,13 @@ impl NodeRef {
             return;
         }
 
-        let mut this = self.0.borrow_mut();
-        this.node = None;
-        this.link = Some(node_ref);
+        {
+            let mut this = self.0.borrow_mut();
+            this.node = None;
+            this.link = Some(node_ref);
+        }
+        // Components link the reference passed to them to their first node
+        self.notify();
     }
 
     /// Reuse an existing `NodeRef`
@@ -133,6 +199,137 @@ impl NodeRef {
         let existing = node_ref.0.borrow();
         this.node = existing.node.clone();
         this.link = existing.link.clone();
+        this.emitted = existing.emitted.clone();
+    }
+}
+
+impl From<Callback<Option<Element>>> for NodeRef {
+    fn from(callback: Callback<Option<Element>>) -> Self {
+        Self(Rc::new(RefCell::new(NodeRefInner {
+            callback: Some(callback),
+            ..Default::default()
+        })))
+    }
+}
+
+/// Typed reference to a component.
+///
+/// A `ComponentRef` is passed to a component with the `ref` attribute, like a [NodeRef], and gives
+/// access to the [Scope] of the component once it has been mounted. Components implemented with
+/// [function_component](crate::functional::function_component) can expose a handle with
+/// [use_imperative_handle](crate::functional::use_imperative_handle).
+///
+/// # Example
+/// ```
+/// # use yew::prelude::*;
+/// use yew::html::ComponentRef;
+///
+/// struct Counter {
+///     count: u32,
+/// }
+///
+/// impl Component for Counter {
+///     type Message = ();
+///     type Properties = ();
+///
+///     fn create(_ctx: &Context<Self>) -> Self {
+///         Self { count: 0 }
+///     }
+///
+///     fn update(&mut self, _ctx: &Context<Self>, _msg: ()) -> bool {
+///         self.count += 1;
+///         true
+///     }
+///
+///     fn view(&self, _ctx: &Context<Self>) -> Html {
+///         html! { <span>{ self.count }</span> }
+///     }
+/// }
+///
+/// #[function_component(App)]
+/// fn app() -> Html {
+///     let counter = use_state(ComponentRef::<Counter>::default);
+///     let onclick = {
+///         let counter = (*counter).clone();
+///         Callback::from(move |_| {
+///             if let Some(scope) = counter.get() {
+///                 scope.send_message(());
+///             }
+///         })
+///     };
+///
+///     html! {
+///         <>
+///             <Counter ref={(*counter).clone()} />
+///             <button {onclick}>{ "Increment" }</button>
+///         </>
+///     }
+/// }
+/// ```
+pub struct ComponentRef<COMP: Component> {
+    node_ref: NodeRef,
+    _marker: PhantomData<COMP>,
+}
+
+impl<COMP: Component> ComponentRef<COMP> {
+    /// Get the scope of the referenced component, if it is mounted and of type `COMP`
+    pub fn get(&self) -> Option<Scope<COMP>> {
+        let scope = self.node_ref.0.borrow().scope.clone()?;
+        if scope.get_type_id() != &TypeId::of::<COMP>() {
+            return None;
+        }
+        let scope = scope.downcast::<COMP>();
+        // The state is borrowed, while the component is being updated
+        let mounted = match scope.state.try_borrow() {
+            Ok(state) => state.is_some(),
+            Err(_) => true,
+        };
+        if mounted {
+            Some(scope)
+        } else {
+            None
+        }
+    }
+
+    /// Get the reference to the first node of the component
+    pub fn node_ref(&self) -> &NodeRef {
+        &self.node_ref
+    }
+}
+
+impl<COMP: Component> Default for ComponentRef<COMP> {
+    fn default() -> Self {
+        Self {
+            node_ref: NodeRef::default(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<COMP: Component> Clone for ComponentRef<COMP> {
+    fn clone(&self) -> Self {
+        Self {
+            node_ref: self.node_ref.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<COMP: Component> PartialEq for ComponentRef<COMP> {
+    fn eq(&self, other: &Self) -> bool {
+        self.node_ref == other.node_ref
+    }
+}
+
+impl<COMP: Component> std::fmt::Debug for ComponentRef<COMP> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ComponentRef {{ node_ref: {:?} }}", self.node_ref)
+    }
+}
+
+impl<COMP: Component> From<ComponentRef<COMP>> for NodeRef {
+    fn from(component_ref: ComponentRef<COMP>) -> Self {
+        component_ref.node_ref
     }
 }
 
@@ -144,6 +341,35 @@ pub fn create_portal(child: Html, host: Element) -> Html {
     VNode::VPortal(VPortal::new(child, host))
 }
 
+/// Render a subtree only when `deps` change.
+///
+/// `render` is only called, if `deps` differ from the dependencies of the subtree rendered in the
+/// same place during the previous render. Otherwise the previously rendered subtree and its DOM
+/// nodes are reused without diffing them.
+///
+/// # Example
+/// ```
+/// # use yew::prelude::*;
+/// use yew::html::memo;
+///
+/// # fn test(rows: std::rc::Rc<Vec<String>>) -> Html {
+/// html! {
+///     <table>
+///         { memo(rows.clone(), move || html! {
+///             { for rows.iter().map(|row| html! { <tr><td>{ row }</td></tr> }) }
+///         }) }
+///     </table>
+/// }
+/// # }
+/// ```
+pub fn memo<D, F>(deps: D, render: F) -> Html
+where
+    D: PartialEq + 'static,
+    F: Fn() -> Html + 'static,
+{
+    VNode::VMemo(VMemo::new(deps, render))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -170,4 +396,21 @@ mod tests {
         node_ref_2.link(node_ref);
         assert_eq!(node, node_ref_2.get().unwrap());
     }
+    #[test]
+    fn linked_callback_ref() {
+        let el = document().create_element("div").unwrap();
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let node_ref = NodeRef::from({
+            let calls = calls.clone();
+            Callback::from(move |el: Option<Element>| calls.borrow_mut().push(el))
+        });
+
+        // Like the ref of a component, that is linked to its first node on every render
+        node_ref.link(NodeRef::new(el.clone().into()));
+        node_ref.link(NodeRef::new(el.clone().into()));
+        assert_eq!(*calls.borrow(), vec![Some(el.clone())]);
+
+        node_ref.set(None);
+        assert_eq!(*calls.borrow(), vec![Some(el), None]);
+    }
 }
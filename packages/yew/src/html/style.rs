@@ -0,0 +1,289 @@
+use super::IntoPropValue;
+use crate::virtual_dom::AttrValue;
+use indexmap::IndexMap;
+use std::borrow::Cow;
+use std::fmt;
+use std::iter::FromIterator;
+
+/// An inline style, made of CSS property declarations.
+///
+/// The preferred way of creating this is using the [`style!`][yew::style!] macro.
+///
+/// Declaring a property, that has already been declared, replaces its value and moves it to the
+/// end, just like repeating a declaration in a `style` attribute would.
+#[derive(Debug, Clone, Default)]
+pub struct Style {
+    properties: IndexMap<Cow<'static, str>, AttrValue>,
+}
+
+impl Style {
+    /// Creates an empty style. (Does not allocate.)
+    pub fn new() -> Self {
+        Self {
+            properties: IndexMap::new(),
+        }
+    }
+
+    /// Creates an empty style with capacity for n properties. (Does not allocate if n is zero.)
+    pub fn with_capacity(n: usize) -> Self {
+        Self {
+            properties: IndexMap::with_capacity(n),
+        }
+    }
+
+    /// Declares a property.
+    ///
+    /// If the value is `None`, the style is left unchanged.
+    pub fn set(
+        &mut self,
+        property: impl Into<Cow<'static, str>>,
+        value: impl IntoPropValue<Option<AttrValue>>,
+    ) {
+        if let Some(value) = value.into_prop_value() {
+            let property = property.into();
+            self.properties.shift_remove(&*property);
+            self.properties.insert(property, value);
+        }
+    }
+
+    /// Merges the declarations of another style into this one.
+    pub fn push<T: Into<Self>>(&mut self, style: T) {
+        let style: Self = style.into();
+        for (property, value) in style.properties {
+            self.set(property, value);
+        }
+    }
+
+    /// Removes a property and returns its value.
+    pub fn remove(&mut self, property: &str) -> Option<AttrValue> {
+        self.properties.shift_remove(property)
+    }
+
+    /// Get the value of a property.
+    pub fn get(&self, property: &str) -> Option<&str> {
+        self.properties.get(property).map(AsRef::as_ref)
+    }
+
+    /// Check the style declares a property.
+    pub fn contains(&self, property: &str) -> bool {
+        self.properties.contains_key(property)
+    }
+
+    /// Check the style is empty.
+    pub fn is_empty(&self) -> bool {
+        self.properties.is_empty()
+    }
+
+    /// Return iterator over the property-value pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.properties
+            .iter()
+            .map(|(property, value)| (property.as_ref(), value.as_ref()))
+    }
+}
+
+/// Splits the declarations of an inline style into property-value pairs.
+///
+/// Semicolons inside of quotes and parentheses, like in `url("a;b")`, do not split the
+/// declarations.
+pub(crate) fn parse_declarations(style: &str) -> Vec<(&str, &str)> {
+    fn push<'a>(declarations: &mut Vec<(&'a str, &'a str)>, declaration: &'a str) {
+        let mut parts = declaration.splitn(2, ':');
+        if let (Some(property), Some(value)) = (parts.next(), parts.next()) {
+            let (property, value) = (property.trim(), value.trim());
+            if !property.is_empty() && !value.is_empty() {
+                declarations.push((property, value));
+            }
+        }
+    }
+
+    let mut declarations = Vec::new();
+
+    let mut quote = None;
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in style.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') => depth = depth.saturating_sub(1),
+            (None, ';') if depth == 0 => {
+                push(&mut declarations, &style[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    push(&mut declarations, &style[start..]);
+    declarations
+}
+
+impl IntoPropValue<AttrValue> for Style {
+    #[inline]
+    fn into_prop_value(self) -> AttrValue {
+        AttrValue::Owned(self.to_string())
+    }
+}
+
+impl IntoPropValue<Option<AttrValue>> for Style {
+    #[inline]
+    fn into_prop_value(self) -> Option<AttrValue> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.into_prop_value())
+        }
+    }
+}
+
+impl IntoPropValue<Style> for &'static str {
+    fn into_prop_value(self) -> Style {
+        self.into()
+    }
+}
+
+impl<T: Into<Style>> Extend<T> for Style {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for style in iter {
+            self.push(style);
+        }
+    }
+}
+
+impl<T: Into<Style>> FromIterator<T> for Style {
+    fn from_iter<IT: IntoIterator<Item = T>>(iter: IT) -> Self {
+        let mut style = Self::new();
+        style.extend(iter);
+        style
+    }
+}
+
+impl fmt::Display for Style {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (property, value)) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{}: {};", property, value)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<&'static str> for Style {
+    fn from(t: &'static str) -> Self {
+        parse_declarations(t)
+            .into_iter()
+            .map(|(property, value)| (Cow::Borrowed(property), AttrValue::Static(value)))
+            .collect()
+    }
+}
+
+impl From<String> for Style {
+    fn from(t: String) -> Self {
+        Self::from(&t)
+    }
+}
+
+impl From<&String> for Style {
+    fn from(t: &String) -> Self {
+        parse_declarations(t)
+            .into_iter()
+            .map(|(property, value)| {
+                (
+                    Cow::Owned(property.to_owned()),
+                    AttrValue::Owned(value.to_owned()),
+                )
+            })
+            .collect()
+    }
+}
+
+impl<K, V> From<(K, V)> for Style
+where
+    K: Into<Cow<'static, str>>,
+    V: IntoPropValue<Option<AttrValue>>,
+{
+    fn from((property, value): (K, V)) -> Self {
+        let mut style = Self::with_capacity(1);
+        style.set(property, value);
+        style
+    }
+}
+
+impl<T: Into<Style>> From<Option<T>> for Style {
+    fn from(t: Option<T>) -> Self {
+        t.map(|x| x.into()).unwrap_or_default()
+    }
+}
+
+impl<T: Into<Style> + Clone> From<&Option<T>> for Style {
+    fn from(t: &Option<T>) -> Self {
+        Self::from(t.clone())
+    }
+}
+
+impl<T: Into<Style>> From<Vec<T>> for Style {
+    fn from(t: Vec<T>) -> Self {
+        Self::from_iter(t)
+    }
+}
+
+impl PartialEq for Style {
+    fn eq(&self, other: &Self) -> bool {
+        self.properties.len() == other.properties.len()
+            && self.properties.iter().eq(other.properties.iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_sets_values() {
+        let mut subject = Style::new();
+        subject.set("color", "red");
+        subject.set("display", None::<&'static str>);
+        assert_eq!(subject.get("color"), Some("red"));
+        assert!(!subject.contains("display"));
+    }
+
+    #[test]
+    fn it_overrides_values_in_order() {
+        let mut subject = Style::from("margin-top: 1px; margin: 0");
+        subject.push("margin-top: 2px");
+        assert_eq!(subject.to_string(), "margin: 0; margin-top: 2px;");
+    }
+
+    #[test]
+    fn it_merges_optional_styles() {
+        let mut subject = Style::new();
+        subject.push(("color", "red"));
+        subject.push(None::<Style>);
+        subject.push(Some("width: 10px"));
+        assert_eq!(subject.to_string(), "color: red; width: 10px;");
+    }
+
+    #[test]
+    fn it_parses_declarations() {
+        assert_eq!(
+            parse_declarations(
+                " color : red;;background: url(\"a;b\") ;invalid; font-family: 'x;y' "
+            ),
+            vec![
+                ("color", "red"),
+                ("background", "url(\"a;b\")"),
+                ("font-family", "'x;y'"),
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_style_is_no_attribute() {
+        let value: Option<AttrValue> = Style::new().into_prop_value();
+        assert_eq!(value, None);
+    }
+}
@@ -100,6 +100,73 @@ use std::{cell::Cell, panic::PanicInfo};
 /// ```
 pub use yew_macro::classes;
 
+/// This macro provides a convenient way to create a [`Style`].
+///
+/// The macro takes a list of items and returns a [`Style`] instance. An item is either a
+/// `property => value` pair or an expression of any type that implements `Into<Style>`, which is
+/// merged into the style. Values can be of any type that implements
+/// `IntoPropValue<Option<AttrValue>>`, so optional values only declare the property, if they are
+/// `Some`.
+///
+/// Later declarations of a property override earlier ones.
+///
+/// # Example
+///
+/// ```
+/// # use yew::prelude::*;
+/// # fn test() {
+/// let hidden = true;
+/// let base = "font-size: 12px; color: black";
+///
+/// html! {
+///     <div style={style!(
+///         base,
+///         "color" => "red",
+///         "display" => if hidden { Some("none") } else { None },
+///     )}>
+///         // ...
+///     </div>
+/// };
+/// # }
+/// ```
+///
+/// [`Style`]: ./html/struct.Style.html
+pub use yew_macro::style;
+
+/// This macro scopes a style block to the elements of a component.
+///
+/// The macro takes a string literal with CSS declarations and nested rules and returns a
+/// [`Css`] instance, which converts into [`Classes`]. The style block is hashed into a unique class
+/// name and its stylesheet is added to the document head once. See the [`css`] module for the
+/// supported syntax.
+///
+/// Rust values can be interpolated with `${expr}`. They are escaped, so that they can only be part
+/// of a single declaration value. Each distinct result is a separate style block.
+///
+/// # Example
+///
+/// ```
+/// # use yew::prelude::*;
+/// # fn test() {
+/// let accent = "tomato";
+///
+/// html! {
+///     <div class={css!(r#"
+///         padding: 4px;
+///         & > a { color: ${accent}; }
+///         &:hover { background: #eee; }
+///         @media (max-width: 600px) { padding: 0; }
+///     "#)}>
+///         // ...
+///     </div>
+/// };
+/// # }
+/// ```
+///
+/// [`Css`]: ./css/struct.Css.html
+/// [`css`]: ./css/index.html
+pub use yew_macro::css;
+
 /// This macro implements JSX-like templates.
 ///
 /// This macro always returns [`Html`].
@@ -251,19 +318,26 @@ pub use yew_macro::props;
 /// This module contains macros which implements html! macro and JSX-like templates
 pub mod macros {
     pub use crate::classes;
+    pub use crate::css;
     pub use crate::html;
     pub use crate::html_nested;
     pub use crate::props;
+    pub use crate::style;
 }
 
 mod app_handle;
 pub mod callback;
 pub mod context;
+pub mod css;
 pub mod functional;
+pub mod head;
 pub mod html;
 pub mod scheduler;
+pub mod transition;
 pub mod utils;
 pub mod virtual_dom;
+pub mod virtual_list;
+pub mod web_component;
 
 /// The module that contains all events available in the framework.
 pub mod events {
@@ -279,7 +353,8 @@ pub mod events {
 }
 
 pub use crate::app_handle::AppHandle;
-use web_sys::Element;
+use crate::virtual_dom::listeners::EventRoot;
+use web_sys::{Element, EventTarget};
 
 thread_local! {
     static PANIC_HOOK_IS_SET: Cell<bool> = Cell::new(false);
@@ -345,6 +420,28 @@ where
     AppHandle::<COMP>::mount_with_props(element, Rc::new(props))
 }
 
+/// The main entry point of a Yew application. This function does the same as
+/// `start_app_with_props_in_element(...)`, but delegates the events of the app to `event_root`
+/// instead of `element`.
+///
+/// `event_root` has to contain `element`. Several apps can be started with different event roots,
+/// each handling only the events of its own elements.
+pub fn start_app_with_props_and_event_root<COMP>(
+    element: Element,
+    event_root: EventTarget,
+    props: COMP::Properties,
+) -> AppHandle<COMP>
+where
+    COMP: Component,
+{
+    set_default_panic_hook();
+    AppHandle::<COMP>::mount_with_props_and_event_root(
+        element,
+        EventRoot::new(event_root),
+        Rc::new(props),
+    )
+}
+
 /// The main entry point of a Yew application.
 /// This function does the same as `start_app(...)` but allows to start an Yew application with properties.
 pub fn start_app_with_props<COMP>(props: COMP::Properties) -> AppHandle<COMP>
@@ -388,9 +485,9 @@ pub mod prelude {
     pub use crate::events::*;
     pub use crate::html::{
         create_portal, Children, ChildrenWithProps, Classes, Component, Context, Html, NodeRef,
-        Properties,
+        Properties, Style,
     };
-    pub use crate::macros::{classes, html, html_nested};
+    pub use crate::macros::{classes, css, html, html_nested, style};
 
     pub use crate::functional::*;
 }
@@ -0,0 +1,665 @@
+//! This module defines the `Transition` and `TransitionGroup` components.
+//!
+//! Both apply CSS classes to animate their children when they enter or exit and keep exiting
+//! children mounted until their CSS transition or animation has ended.
+//!
+//! With the default name `yew`, the classes applied while entering are:
+//! - `yew-enter yew-enter-active` on the first frame
+//! - `yew-enter-active yew-enter-to` until the transition or animation ends
+//!
+//! Exiting applies the same classes with `exit` instead of `enter`. No classes are applied once
+//! entered.
+//!
+//! A phase ends, once every transitioned property and every animation of the wrapping element has
+//! ended, or after their longest duration, if some of them never start.
+//!
+//! ```rust
+//! # use yew::prelude::*;
+//! use yew::transition::Transition;
+//!
+//! // .fade-enter, .fade-exit-to { opacity: 0; }
+//! // .fade-enter-active, .fade-exit-active { transition: opacity 300ms; }
+//! #[function_component(Notice)]
+//! fn notice() -> Html {
+//!     let show = use_state(|| true);
+//!     let onclick = {
+//!         let show = show.clone();
+//!         Callback::from(move |_| show.set(!*show))
+//!     };
+//!
+//!     html! {
+//!         <>
+//!             <button {onclick}>{ "Toggle" }</button>
+//!             <Transition name="fade" show={*show}>
+//!                 <p>{ "Hello" }</p>
+//!             </Transition>
+//!         </>
+//!     }
+//! }
+//! ```
+
+use crate::html::{Classes, NodeRef};
+use crate::virtual_dom::{AttrValue, Key};
+use crate::{html, Callback, Children, Component, Context, Html, Properties};
+use gloo::timers::callback::Timeout;
+use std::collections::{HashMap, HashSet};
+use web_sys::{AnimationEvent, CssStyleDeclaration, Event, HtmlElement, TransitionEvent};
+
+/// Props for [`Transition`]
+#[derive(Debug, Clone, PartialEq, Properties)]
+pub struct TransitionProps {
+    /// Whether the children are shown. Changing this enters or exits the children.
+    #[prop_or(true)]
+    pub show: bool,
+    /// Prefix of the applied classes
+    #[prop_or_else(|| "yew".into())]
+    pub name: AttrValue,
+    /// Enter the children when the component is mounted with `show` set
+    #[prop_or_default]
+    pub appear: bool,
+    /// Tag of the element wrapping the children
+    #[prop_or("div")]
+    pub tag: &'static str,
+    /// Called, when the children have entered
+    #[prop_or_default]
+    pub onentered: Callback<()>,
+    /// Called, when the children have exited and were unmounted
+    #[prop_or_default]
+    pub onexited: Callback<()>,
+    /// Children
+    pub children: Children,
+}
+
+/// Messages of the [`Transition`] component
+#[derive(Debug)]
+pub enum TransitionMsg {
+    /// The start classes have been applied and the transition can start
+    Start,
+    /// The transition of a property of the wrapping element has ended or was cancelled
+    TransitionEnd(String),
+    /// An animation of the wrapping element has ended or was cancelled
+    AnimationEnd(String),
+    /// The phase has ended
+    End,
+}
+
+/// Phase of a [`Transition`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Phase {
+    /// Entering. `started` is set after the first frame.
+    Entering {
+        started: bool,
+    },
+    Entered,
+    /// Exiting. `started` is set after the first frame.
+    Exiting {
+        started: bool,
+    },
+    /// The children are unmounted
+    Exited,
+}
+
+/// Enters and exits its children with CSS transitions or animations.
+///
+/// The children are wrapped in an element, which the transition classes are applied to. See the
+/// [module documentation](self) for the applied classes.
+#[derive(Debug)]
+pub struct Transition {
+    phase: Phase,
+    node_ref: NodeRef,
+    /// Transitions and animations of the current phase, that have not ended yet
+    pending: Pending,
+    /// Ends the current phase after the longest duration
+    timeout: Option<Timeout>,
+}
+
+/// Transitions and animations of the wrapping element, that have not ended yet
+#[derive(Debug, Default, PartialEq)]
+struct Pending {
+    /// Transitioned properties
+    properties: Vec<String>,
+    /// Names of the animations
+    animations: Vec<String>,
+    /// Longest duration including the delay in milliseconds
+    duration: f64,
+}
+
+impl Pending {
+    /// Reads the transitions and animations, that will run with `style`
+    fn of(style: &CssStyleDeclaration) -> Self {
+        let list = |property: &str| -> Vec<String> {
+            style
+                .get_property_value(property)
+                .unwrap_or_default()
+                .split(',')
+                .map(|value| value.trim().to_owned())
+                .collect()
+        };
+        let mut pending = Self::default();
+        pending.properties = pending.running(
+            list("transition-property"),
+            &list("transition-duration"),
+            &list("transition-delay"),
+        );
+        pending.animations = pending.running(
+            list("animation-name"),
+            &list("animation-duration"),
+            &list("animation-delay"),
+        );
+        pending
+    }
+
+    /// Returns the `names`, that run for a duration, and updates the longest duration. Durations
+    /// and delays are repeated, if there are fewer of them than names.
+    fn running(
+        &mut self,
+        names: Vec<String>,
+        durations: &[String],
+        delays: &[String],
+    ) -> Vec<String> {
+        let at = |values: &[String], i: usize| {
+            if values.is_empty() {
+                0.
+            } else {
+                parse_duration(&values[i % values.len()])
+            }
+        };
+        let mut running = Vec::new();
+        for (i, name) in names.into_iter().enumerate() {
+            let duration = at(durations, i);
+            if name.is_empty() || name == "none" || duration <= 0. {
+                continue;
+            }
+            self.duration = self.duration.max(duration + at(delays, i).max(0.));
+            running.push(name);
+        }
+        running
+    }
+
+    /// Removes an ended transition of `property`. A transition of `all` ends with any property.
+    fn end_transition(&mut self, property: &str) {
+        if let Some(i) = self
+            .properties
+            .iter()
+            .position(|p| p == property || p == "all")
+        {
+            self.properties.remove(i);
+        }
+    }
+
+    /// Removes an ended animation
+    fn end_animation(&mut self, name: &str) {
+        if let Some(i) = self.animations.iter().position(|a| a == name) {
+            self.animations.remove(i);
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.properties.is_empty() && self.animations.is_empty()
+    }
+}
+
+impl Transition {
+    fn classes(&self, name: &str) -> Classes {
+        let (stage, started) = match self.phase {
+            Phase::Entering { started } => ("enter", started),
+            Phase::Exiting { started } => ("exit", started),
+            Phase::Entered | Phase::Exited => return Classes::new(),
+        };
+        let mut classes = Classes::new();
+        if !started {
+            classes.push(format!("{}-{}", name, stage));
+        }
+        classes.push(format!("{}-{}-active", name, stage));
+        if started {
+            classes.push(format!("{}-{}-to", name, stage));
+        }
+        classes
+    }
+
+    /// Returns whether `event` was dispatched by the transition of the wrapping element and not
+    /// bubbled up from one of the children
+    fn is_own(node_ref: &NodeRef, event: &Event) -> bool {
+        match (event.target(), node_ref.get()) {
+            (Some(target), Some(node)) => target == *node,
+            _ => false,
+        }
+    }
+}
+
+impl Component for Transition {
+    type Message = TransitionMsg;
+    type Properties = TransitionProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let props = ctx.props();
+        let phase = match (props.show, props.appear) {
+            (true, true) => Phase::Entering { started: false },
+            (true, false) => Phase::Entered,
+            (false, _) => Phase::Exited,
+        };
+        Self {
+            phase,
+            node_ref: NodeRef::default(),
+            pending: Pending::default(),
+            timeout: None,
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        let started = matches!(
+            self.phase,
+            Phase::Entering { started: true } | Phase::Exiting { started: true }
+        );
+        let msg = match msg {
+            // Transitions of an interrupted phase, that end before the next phase started
+            TransitionMsg::TransitionEnd(_) | TransitionMsg::AnimationEnd(_) if !started => {
+                return false;
+            }
+            TransitionMsg::TransitionEnd(property) => {
+                self.pending.end_transition(&property);
+                if !self.pending.is_empty() {
+                    return false;
+                }
+                TransitionMsg::End
+            }
+            TransitionMsg::AnimationEnd(name) => {
+                self.pending.end_animation(&name);
+                if !self.pending.is_empty() {
+                    return false;
+                }
+                TransitionMsg::End
+            }
+            msg => msg,
+        };
+        if let TransitionMsg::End = msg {
+            self.timeout = None;
+        }
+        match (msg, self.phase) {
+            (TransitionMsg::Start, Phase::Entering { started: false }) => {
+                self.phase = Phase::Entering { started: true };
+            }
+            (TransitionMsg::Start, Phase::Exiting { started: false }) => {
+                self.phase = Phase::Exiting { started: true };
+            }
+            (TransitionMsg::End, Phase::Entering { .. }) => {
+                self.phase = Phase::Entered;
+                ctx.props().onentered.emit(());
+            }
+            (TransitionMsg::End, Phase::Exiting { .. }) => {
+                self.phase = Phase::Exited;
+                ctx.props().onexited.emit(());
+            }
+            // Messages of a phase, that has been interrupted
+            _ => return false,
+        }
+        true
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>) -> bool {
+        let show = ctx.props().show;
+        match self.phase {
+            Phase::Exiting { .. } | Phase::Exited if show => {
+                self.phase = Phase::Entering { started: false };
+            }
+            Phase::Entering { .. } | Phase::Entered if !show => {
+                self.phase = Phase::Exiting { started: false };
+            }
+            _ => return true,
+        }
+        // The transitions of an interrupted phase don't end it anymore
+        self.pending = Pending::default();
+        self.timeout = None;
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        if self.phase == Phase::Exited {
+            return html! {};
+        }
+
+        let props = ctx.props();
+        let ontransitionend = {
+            let node_ref = self.node_ref.clone();
+            ctx.link().batch_callback(move |e: TransitionEvent| {
+                if Self::is_own(&node_ref, &e) {
+                    Some(TransitionMsg::TransitionEnd(e.property_name()))
+                } else {
+                    None
+                }
+            })
+        };
+        let onanimationend = {
+            let node_ref = self.node_ref.clone();
+            ctx.link().batch_callback(move |e: AnimationEvent| {
+                if Self::is_own(&node_ref, &e) {
+                    Some(TransitionMsg::AnimationEnd(e.animation_name()))
+                } else {
+                    None
+                }
+            })
+        };
+        html! {
+            <@{props.tag}
+                ref={self.node_ref.clone()}
+                class={self.classes(&props.name)}
+                ontransitioncancel={ontransitionend.clone()}
+                onanimationcancel={onanimationend.clone()}
+                {ontransitionend}
+                {onanimationend}
+            >
+                { props.children.clone() }
+            </@>
+        }
+    }
+
+    fn rendered(&mut self, ctx: &Context<Self>, _first_render: bool) {
+        if let Phase::Entering { started: false } | Phase::Exiting { started: false } = self.phase {
+            let el = match self.node_ref.cast::<HtmlElement>() {
+                Some(el) => el,
+                None => return,
+            };
+            // Reading the layout applies the start classes, so the transition starts from them
+            // once they are replaced
+            let _ = el.offset_height();
+            ctx.link().send_message(TransitionMsg::Start);
+
+            self.pending = match gloo_utils::window().get_computed_style(&el) {
+                Ok(Some(style)) => Pending::of(&style),
+                _ => Pending::default(),
+            };
+            if self.pending.is_empty() {
+                ctx.link().send_message(TransitionMsg::End);
+            } else {
+                // Listed properties, that don't change, never dispatch an event
+                let link = ctx.link().clone();
+                self.timeout = Some(Timeout::new(
+                    self.pending.duration.ceil() as u32 + 50,
+                    move || link.send_message(TransitionMsg::End),
+                ));
+            }
+        }
+    }
+}
+
+/// Parses a CSS time like `0.3s` or `300ms` into milliseconds
+fn parse_duration(duration: &str) -> f64 {
+    let duration = duration.trim();
+    let (value, scale) = if let Some(value) = duration.strip_suffix("ms") {
+        (value, 1.)
+    } else if let Some(value) = duration.strip_suffix('s') {
+        (value, 1000.)
+    } else {
+        return 0.;
+    };
+    value.parse::<f64>().map(|v| v * scale).unwrap_or(0.)
+}
+
+/// Props for [`TransitionGroup`]
+#[derive(Debug, Clone, PartialEq, Properties)]
+pub struct TransitionGroupProps {
+    /// Prefix of the applied classes
+    #[prop_or_else(|| "yew".into())]
+    pub name: AttrValue,
+    /// Tag of the element wrapping the group
+    #[prop_or("div")]
+    pub tag: &'static str,
+    /// Tag of the elements wrapping each child
+    #[prop_or("div")]
+    pub item_tag: &'static str,
+    /// Classes of the element wrapping the group
+    #[prop_or_default]
+    pub class: Classes,
+    /// Keyed children
+    pub children: Children,
+}
+
+/// Messages of the [`TransitionGroup`] component
+#[derive(Debug)]
+pub enum TransitionGroupMsg {
+    /// The child with the key has exited
+    Exited(Key),
+}
+
+/// A child of a [`TransitionGroup`]
+#[derive(Debug)]
+struct Item {
+    key: Key,
+    node: Html,
+    /// The child has been removed and is exiting
+    exiting: bool,
+}
+
+/// Enters children, that are added, and exits children, that are removed.
+///
+/// Children are identified by their keys and each is wrapped in a [`Transition`]. Removed
+/// children stay at their position until they have exited.
+///
+/// # Panics
+///
+/// Panics, if a child has no key.
+#[derive(Debug)]
+pub struct TransitionGroup {
+    items: Vec<Item>,
+    /// Set after the first render. Children added afterwards are entered.
+    mounted: bool,
+}
+
+impl TransitionGroup {
+    fn children(props: &TransitionGroupProps) -> impl Iterator<Item = (Key, Html)> + '_ {
+        props.children.iter().map(|child| {
+            let key = child
+                .key()
+                .expect("children of a `TransitionGroup` must have keys");
+            (key, child)
+        })
+    }
+
+    /// Updates the items to the children of `props`, keeping exiting children after the child,
+    /// that preceded them
+    fn merge(&mut self, props: &TransitionGroupProps) {
+        let children: Vec<(Key, Html)> = Self::children(props).collect();
+        let keys: HashSet<&Key> = children.iter().map(|(key, _)| key).collect();
+
+        let mut exiting: HashMap<Option<Key>, Vec<Item>> = HashMap::new();
+        let mut preceding = None;
+        for mut item in self.items.drain(..) {
+            if keys.contains(&item.key) {
+                preceding = Some(item.key);
+            } else {
+                item.exiting = true;
+                exiting.entry(preceding.clone()).or_default().push(item);
+            }
+        }
+
+        let mut items = exiting.remove(&None).unwrap_or_default();
+        for (key, node) in children {
+            let following = exiting.remove(&Some(key.clone()));
+            items.push(Item {
+                key,
+                node,
+                exiting: false,
+            });
+            items.extend(following.into_iter().flatten());
+        }
+        self.items = items;
+    }
+}
+
+impl Component for TransitionGroup {
+    type Message = TransitionGroupMsg;
+    type Properties = TransitionGroupProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let items = Self::children(ctx.props())
+            .map(|(key, node)| Item {
+                key,
+                node,
+                exiting: false,
+            })
+            .collect();
+        Self {
+            items,
+            mounted: false,
+        }
+    }
+
+    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            TransitionGroupMsg::Exited(key) => {
+                let len = self.items.len();
+                self.items.retain(|item| !(item.exiting && item.key == key));
+                self.items.len() != len
+            }
+        }
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>) -> bool {
+        self.merge(ctx.props());
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let items = self.items.iter().map(|item| {
+            let onexited = {
+                let key = item.key.clone();
+                ctx.link()
+                    .callback(move |_| TransitionGroupMsg::Exited(key.clone()))
+            };
+            html! {
+                <Transition
+                    key={item.key.clone()}
+                    name={props.name.clone()}
+                    tag={props.item_tag}
+                    show={!item.exiting}
+                    appear={self.mounted}
+                    {onexited}
+                >
+                    { item.node.clone() }
+                </Transition>
+            }
+        });
+        html! {
+            <@{props.tag} class={props.class.clone()}>
+                { for items }
+            </@>
+        }
+    }
+
+    fn rendered(&mut self, _ctx: &Context<Self>, first_render: bool) {
+        if first_render {
+            self.mounted = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("0s"), 0.);
+        assert_eq!(parse_duration(" 0.3s"), 300.);
+        assert_eq!(parse_duration("150ms"), 150.);
+        assert_eq!(parse_duration("auto"), 0.);
+    }
+
+    #[test]
+    fn counts_pending_transitions() {
+        let mut pending = Pending::default();
+        pending.properties = pending.running(
+            vec!["opacity".into(), "transform".into(), "color".into()],
+            &["300ms".into(), "0s".into()],
+            &["0.1s".into()],
+        );
+        pending.animations = pending.running(vec!["none".into()], &["1s".into()], &[]);
+        assert_eq!(pending.properties, ["opacity", "color"]);
+        assert!(pending.animations.is_empty());
+        assert_eq!(pending.duration, 400.);
+
+        pending.end_transition("width");
+        pending.end_transition("opacity");
+        assert!(!pending.is_empty());
+        pending.end_transition("color");
+        assert!(pending.is_empty());
+
+        pending.properties = vec!["all".into()];
+        pending.animations = vec!["spin".into()];
+        pending.end_transition("opacity");
+        pending.end_animation("fade");
+        assert!(!pending.is_empty());
+        pending.end_animation("spin");
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn keeps_exiting_children_in_place() {
+        let props = |keys: &[&str]| TransitionGroupProps {
+            name: "yew".into(),
+            tag: "div",
+            item_tag: "div",
+            class: Classes::new(),
+            children: Children::new(
+                keys.iter()
+                    .map(|key| html! { <p key={*key}>{ key }</p> })
+                    .collect(),
+            ),
+        };
+        let mut group = TransitionGroup {
+            items: TransitionGroup::children(&props(&["a", "b", "c", "d"]))
+                .map(|(key, node)| Item {
+                    key,
+                    node,
+                    exiting: false,
+                })
+                .collect(),
+            mounted: true,
+        };
+
+        group.merge(&props(&["e", "c", "a"]));
+        let items: Vec<_> = group
+            .items
+            .iter()
+            .map(|item| (item.key.to_string(), item.exiting))
+            .collect();
+        assert_eq!(
+            items,
+            [
+                ("e", false),
+                ("c", false),
+                ("d", true),
+                ("a", false),
+                ("b", true)
+            ]
+            .iter()
+            .map(|(key, exiting)| (key.to_string(), *exiting))
+            .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn applies_phase_classes() {
+        let transition = |phase| Transition {
+            phase,
+            node_ref: NodeRef::default(),
+            pending: Pending::default(),
+            timeout: None,
+        };
+
+        assert_eq!(
+            transition(Phase::Entering { started: false })
+                .classes("fade")
+                .to_string(),
+            "fade-enter fade-enter-active"
+        );
+        assert_eq!(
+            transition(Phase::Exiting { started: true })
+                .classes("fade")
+                .to_string(),
+            "fade-exit-active fade-exit-to"
+        );
+        assert!(transition(Phase::Entered).classes("fade").is_empty());
+    }
+}
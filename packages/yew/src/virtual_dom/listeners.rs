@@ -5,7 +5,7 @@ use std::{
     rc::Rc,
 };
 use wasm_bindgen::{prelude::*, JsCast};
-use web_sys::{Element, Event};
+use web_sys::{Element, Event, EventTarget};
 
 thread_local! {
     /// Global event listener registry
@@ -14,8 +14,8 @@ thread_local! {
     /// Key used to store listener id on element
     static LISTENER_ID_PROP: wasm_bindgen::JsValue = "__yew_listener_id".into();
 
-    /// Cached reference to the document body
-    static BODY: web_sys::HtmlElement = gloo_utils::document().body().unwrap();
+    /// Events handled by a delegation root, that are still being dispatched
+    static HANDLED_EVENTS: RefCell<Vec<(EventRoot, Event)>> = Default::default();
 }
 
 /// Bubble events during delegation
@@ -37,6 +37,61 @@ pub fn set_event_bubbling(bubble: bool) {
     }
 }
 
+/// Registry of the nodes, that the events of an app are delegated to.
+///
+/// Every app registers its listeners on its own delegation root, which handles the events on the
+/// element the app is mounted in by default. Delegating to the mount host keeps working inside a
+/// shadow root, where events crossing the shadow boundary are retargeted to its host. Portals add
+/// their host to the delegation root of their app, so events on elements rendered outside of the
+/// app are delegated as well.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub(crate) struct EventRoot(u32);
+
+impl EventRoot {
+    /// Creates a new delegation root, that handles events on `target`
+    pub(crate) fn new(target: EventTarget) -> Self {
+        Registry::with(|reg| {
+            reg.root_counter += 1;
+            let root = Self(reg.root_counter);
+            reg.roots.insert(root, GlobalHandlers::new(target));
+            root
+        })
+    }
+
+    /// Delegation root for an app mounted in `host`
+    pub(crate) fn for_host(host: &Element) -> Self {
+        Self::new(host.clone().into())
+    }
+
+    /// Handles the events on `target` in addition to the ones on the existing targets, until it
+    /// is removed with [remove_target](Self::remove_target) as often as it was added
+    pub(crate) fn add_target(self, target: &Element) {
+        Registry::with(|reg| {
+            let root = GlobalHandlers::of(&mut reg.roots, self);
+            root.add_target(target.clone().into());
+        });
+    }
+
+    /// Stops handling the events on a `target` added with [add_target](Self::add_target)
+    pub(crate) fn remove_target(self, target: &Element) {
+        Registry::with(|reg| {
+            if let Some(root) = reg.roots.get_mut(&self) {
+                root.remove_target(target.clone().into());
+            }
+        });
+    }
+
+    /// Removes all event handlers of the delegation root. The document body is never removed.
+    pub(crate) fn remove(self) {
+        if self != Self::default() {
+            Registry::with(|reg| {
+                reg.roots.remove(&self);
+                reg.by_id.retain(|_, (root, _)| *root != self);
+            });
+        }
+    }
+}
+
 /// The [Listener] trait is an universal implementation of an event listener
 /// which is used to bind Rust-listener to JS-listener (DOM).
 pub trait Listener {
@@ -199,10 +254,10 @@ pub enum Listeners {
 
 impl Listeners {
     /// Register listeners and return their handle ID
-    fn register(el: &Element, pending: &[Option<Rc<dyn Listener>>]) -> Self {
+    fn register(el: &Element, root: EventRoot, pending: &[Option<Rc<dyn Listener>>]) -> Self {
         Self::Registered(Registry::with(|reg| {
             let id = reg.set_listener_id(el);
-            reg.register(id, pending);
+            reg.register(id, root, pending);
             id
         }))
     }
@@ -213,28 +268,26 @@ impl Listeners {
             Registry::with(|r| r.unregister(id));
         }
     }
-}
-
-impl super::Apply for Listeners {
-    type Element = Element;
 
-    fn apply(&mut self, el: &Self::Element) {
+    /// Register pending listeners of a new element with the delegation `root`
+    pub(super) fn apply(&mut self, el: &Element, root: EventRoot) {
         if let Self::Pending(pending) = self {
-            *self = Self::register(el, pending);
+            *self = Self::register(el, root, pending);
         }
     }
 
-    fn apply_diff(&mut self, el: &Self::Element, ancestor: Self) {
+    /// Register pending listeners with the delegation `root` and remove the ones of `ancestor`
+    pub(super) fn apply_diff(&mut self, el: &Element, root: EventRoot, ancestor: Self) {
         use Listeners::*;
 
         match (std::mem::take(self), ancestor) {
             (Pending(pending), Registered(id)) => {
                 // Reuse the ID
-                Registry::with(|reg| reg.patch(&id, &*pending));
+                Registry::with(|reg| reg.patch(&id, root, &*pending));
                 *self = Registered(id);
             }
             (Pending(pending), None) => {
-                *self = Self::register(el, &pending);
+                *self = Self::register(el, root, &pending);
             }
             (None, Registered(id)) => {
                 Registry::with(|reg| reg.unregister(&id));
@@ -256,7 +309,7 @@ impl PartialEq for Listeners {
                 use std::option::Option::None;
 
                 Registry::with(|reg| match reg.by_id.get(registered_id) {
-                    Some(reg) => {
+                    Some((_, reg)) => {
                         if reg.len() != pending.len() {
                             return false;
                         }
@@ -328,71 +381,113 @@ impl From<&dyn Listener> for EventDescriptor {
     }
 }
 
-/// Ensures global event handler registration.
+/// Ensures event handler registration on a delegation root.
 //
 // Separate struct to DRY, while avoiding partial struct mutability.
-#[derive(Default, Debug)]
+#[derive(Debug)]
 struct GlobalHandlers {
+    /// Nodes the event handlers are registered on and how often each of them was added
+    targets: Vec<(EventTarget, usize)>,
+
     /// Events with registered handlers that are possibly passive
     handling: HashSet<EventDescriptor>,
 
-    /// Keep track of all listeners to drop them on registry drop.
-    /// The registry is never dropped in production.
-    #[cfg(test)]
+    /// Keep track of all listeners to remove them, when the delegation root is removed
     #[allow(clippy::type_complexity)]
-    registered: Vec<(ListenerKind, Closure<dyn Fn(web_sys::Event)>)>,
+    registered: Vec<(EventDescriptor, Closure<dyn Fn(web_sys::Event)>)>,
 }
 
 impl GlobalHandlers {
-    /// Ensure a descriptor has a global event handler assigned
-    fn ensure_handled(&mut self, desc: EventDescriptor) {
-        if !self.handling.contains(&desc) {
-            let cl = BODY.with(|body| {
-                let cl = Closure::wrap(
-                    Box::new(move |e: Event| Registry::handle(desc, e)) as Box<dyn Fn(Event)>
-                );
-                AsRef::<web_sys::EventTarget>::as_ref(body)
-                    .add_event_listener_with_callback_and_add_event_listener_options(
-                        &desc.kind.as_ref()[2..],
-                        cl.as_ref().unchecked_ref(),
-                        &{
-                            let mut opts = web_sys::AddEventListenerOptions::new();
-                            opts.capture(true);
-                            // We need to explicitly set passive to override any browser defaults
-                            opts.passive(desc.passive);
-                            opts
-                        },
-                    )
-                    .map_err(|e| format!("could not register global listener: {:?}", e))
-                    .unwrap();
-                cl
-            });
+    fn new(target: EventTarget) -> Self {
+        Self {
+            targets: vec![(target, 1)],
+            handling: Default::default(),
+            registered: Default::default(),
+        }
+    }
 
-            // Never drop the closure as this event handler is static
-            #[cfg(not(test))]
-            cl.forget();
-            #[cfg(test)]
-            self.registered.push((desc.kind, cl));
+    fn add_target(&mut self, target: EventTarget) {
+        if let Some((_, count)) = self.targets.iter_mut().find(|(t, _)| *t == target) {
+            *count += 1;
+            return;
+        }
+        for (desc, cl) in self.registered.iter() {
+            Self::add_listener(&target, *desc, cl);
+        }
+        self.targets.push((target, 1));
+    }
 
+    fn remove_target(&mut self, target: EventTarget) {
+        let index = match self.targets.iter().position(|(t, _)| *t == target) {
+            Some(index) => index,
+            None => return,
+        };
+        self.targets[index].1 -= 1;
+        if self.targets[index].1 == 0 {
+            let (target, _) = self.targets.remove(index);
+            for (desc, cl) in self.registered.iter() {
+                Self::remove_listener(&target, *desc, cl);
+            }
+        }
+    }
+
+    fn add_listener(target: &EventTarget, desc: EventDescriptor, cl: &Closure<dyn Fn(Event)>) {
+        target
+            .add_event_listener_with_callback_and_add_event_listener_options(
+                &desc.kind.as_ref()[2..],
+                cl.as_ref().unchecked_ref(),
+                &{
+                    let mut opts = web_sys::AddEventListenerOptions::new();
+                    opts.capture(true);
+                    // We need to explicitly set passive to override any browser defaults
+                    opts.passive(desc.passive);
+                    opts
+                },
+            )
+            .map_err(|e| format!("could not register global listener: {:?}", e))
+            .unwrap();
+    }
+
+    fn remove_listener(target: &EventTarget, desc: EventDescriptor, cl: &Closure<dyn Fn(Event)>) {
+        target
+            .remove_event_listener_with_callback_and_bool(
+                &desc.kind.as_ref()[2..],
+                cl.as_ref().unchecked_ref(),
+                true,
+            )
+            .unwrap();
+    }
+
+    /// Returns the event handlers of a delegation root. Adds the document body on first use.
+    fn of(roots: &mut HashMap<EventRoot, Self>, root: EventRoot) -> &mut Self {
+        roots
+            .entry(root)
+            .or_insert_with(|| Self::new(gloo_utils::document().body().unwrap().into()))
+    }
+
+    /// Ensure a descriptor has an event handler assigned on the delegation root
+    fn ensure_handled(&mut self, root: EventRoot, desc: EventDescriptor) {
+        if !self.handling.contains(&desc) {
+            let cl = Closure::wrap(
+                Box::new(move |e: Event| Registry::handle(root, desc, e)) as Box<dyn Fn(Event)>
+            );
+            for (target, _) in self.targets.iter() {
+                Self::add_listener(target, desc, &cl);
+            }
+
+            self.registered.push((desc, cl));
             self.handling.insert(desc);
         }
     }
 }
 
-// Enable resetting between tests
-#[cfg(test)]
 impl Drop for GlobalHandlers {
     fn drop(&mut self) {
-        BODY.with(|body| {
-            for (kind, cl) in std::mem::take(&mut self.registered) {
-                AsRef::<web_sys::EventTarget>::as_ref(body)
-                    .remove_event_listener_with_callback(
-                        &kind.as_ref()[2..],
-                        cl.as_ref().unchecked_ref(),
-                    )
-                    .unwrap();
+        for (desc, cl) in std::mem::take(&mut self.registered) {
+            for (target, _) in self.targets.iter() {
+                Self::remove_listener(target, desc, &cl);
             }
-        });
+        }
     }
 }
 
@@ -402,11 +497,15 @@ struct Registry {
     /// Counter for assigning new IDs
     id_counter: u32,
 
-    /// Registered global event handlers
-    global: GlobalHandlers,
+    /// Counter for assigning new delegation root IDs
+    root_counter: u32,
 
-    /// Contains all registered event listeners by listener ID
-    by_id: HashMap<u32, HashMap<EventDescriptor, Vec<Rc<dyn Listener>>>>,
+    /// Registered event handlers by delegation root. The document body is added on first use.
+    roots: HashMap<EventRoot, GlobalHandlers>,
+
+    /// Contains all registered event listeners and their delegation root by listener ID
+    #[allow(clippy::type_complexity)]
+    by_id: HashMap<u32, (EventRoot, HashMap<EventDescriptor, Vec<Rc<dyn Listener>>>)>,
 }
 
 impl Registry {
@@ -417,20 +516,20 @@ impl Registry {
     }
 
     /// Register all passed listeners under ID
-    fn register(&mut self, id: u32, listeners: &[Option<Rc<dyn Listener>>]) {
+    fn register(&mut self, id: u32, root: EventRoot, listeners: &[Option<Rc<dyn Listener>>]) {
         let mut by_desc =
             HashMap::<EventDescriptor, Vec<Rc<dyn Listener>>>::with_capacity(listeners.len());
         for l in listeners.iter().filter_map(|l| l.as_ref()).cloned() {
             let desc = EventDescriptor::from(l.deref());
-            self.global.ensure_handled(desc);
+            GlobalHandlers::of(&mut self.roots, root).ensure_handled(root, desc);
             by_desc.entry(desc).or_default().push(l);
         }
-        self.by_id.insert(id, by_desc);
+        self.by_id.insert(id, (root, by_desc));
     }
 
     /// Patch an already registered set of handlers
-    fn patch(&mut self, id: &u32, listeners: &[Option<Rc<dyn Listener>>]) {
-        if let Some(by_desc) = self.by_id.get_mut(id) {
+    fn patch(&mut self, id: &u32, root: EventRoot, listeners: &[Option<Rc<dyn Listener>>]) {
+        if let Some((_, by_desc)) = self.by_id.get_mut(id) {
             // Keeping empty vectors is fine. Those don't do much and should happen rarely.
             for v in by_desc.values_mut() {
                 v.clear()
@@ -438,7 +537,7 @@ impl Registry {
 
             for l in listeners.iter().filter_map(|l| l.as_ref()).cloned() {
                 let desc = EventDescriptor::from(l.deref());
-                self.global.ensure_handled(desc);
+                GlobalHandlers::of(&mut self.roots, root).ensure_handled(root, desc);
                 by_desc.entry(desc).or_default().push(l);
             }
         }
@@ -463,8 +562,8 @@ impl Registry {
         id
     }
 
-    /// Handle a global event firing
-    fn handle(desc: EventDescriptor, event: Event) {
+    /// Handle an event firing on a delegation root
+    fn handle(root: EventRoot, desc: EventDescriptor, event: Event) {
         let target = match event
             .target()
             .map(|el| el.dyn_into::<web_sys::Element>().ok())
@@ -474,10 +573,31 @@ impl Registry {
             None => return,
         };
 
-        Self::run_handlers(desc, event, target);
+        // An event reaching several targets of the delegation root, like the host of a portal
+        // inside the app, is only handled by the first one
+        let handled = HANDLED_EVENTS.with(|handled| {
+            let mut handled = handled.borrow_mut();
+            // Events, that finished their dispatch, can not reach another target
+            handled.retain(|(_, e)| e.event_phase() != Event::NONE);
+            if handled.iter().any(|(r, e)| *r == root && *e == event) {
+                return true;
+            }
+            handled.push((root, event.clone()));
+            false
+        });
+        if handled {
+            return;
+        }
+
+        Self::run_handlers(root, desc, event, target);
     }
 
-    fn run_handlers(desc: EventDescriptor, event: Event, target: web_sys::Element) {
+    fn run_handlers(
+        root: EventRoot,
+        desc: EventDescriptor,
+        event: Event,
+        target: web_sys::Element,
+    ) {
         let run_handler = |el: &web_sys::Element| {
             if let Some(l) = LISTENER_ID_PROP
                 .with(|prop| js_sys::Reflect::get(el, prop).ok())
@@ -487,7 +607,9 @@ impl Registry {
                     Registry::with(|r| {
                         r.by_id
                             .get(&(num.value_of() as u32))
-                            .map(|s| s.get(&desc))
+                            // Elements of other apps are handled by their own delegation root
+                            .filter(|(r, _)| *r == root)
+                            .map(|(_, s)| s.get(&desc))
                             .flatten()
                             .cloned()
                     })
@@ -661,6 +783,38 @@ mod tests {
         assert_count(&el, 2);
     }
 
+    #[test]
+    fn shadow_root() {
+        struct ShadowRoot;
+
+        impl Mixin for ShadowRoot {}
+
+        super::Registry::with(|r| *r = Default::default());
+        let host = document().create_element("div").unwrap();
+        document().body().unwrap().append_child(&host).unwrap();
+        let shadow_root = host
+            .attach_shadow(&web_sys::ShadowRootInit::new(web_sys::ShadowRootMode::Open))
+            .unwrap();
+        let root = document().create_element("div").unwrap();
+        shadow_root.append_child(&root).unwrap();
+
+        let link = crate::start_app_in_element::<Comp<ShadowRoot>>(root);
+        let el = shadow_root
+            .query_selector("a")
+            .unwrap()
+            .unwrap()
+            .dyn_into::<web_sys::HtmlElement>()
+            .unwrap();
+
+        assert_count(&el, 0);
+
+        el.click();
+        assert_count(&el, 1);
+
+        link.destroy();
+        host.remove();
+    }
+
     async fn await_animation_frame() {
         JsFuture::from(js_sys::Promise::new(&mut |resolve, _| {
             gloo_utils::window()
@@ -4,15 +4,20 @@
 pub mod key;
 #[doc(hidden)]
 pub mod listeners;
+mod template;
 #[doc(hidden)]
 pub mod vcomp;
 #[doc(hidden)]
 pub mod vlist;
 #[doc(hidden)]
+pub mod vmemo;
+#[doc(hidden)]
 pub mod vnode;
 #[doc(hidden)]
 pub mod vportal;
 #[doc(hidden)]
+pub mod vraw;
+#[doc(hidden)]
 pub mod vtag;
 #[doc(hidden)]
 pub mod vtext;
@@ -21,7 +26,8 @@ use crate::html::{AnyScope, NodeRef};
 use indexmap::IndexMap;
 use std::borrow::Cow;
 use std::{collections::HashMap, fmt, hint::unreachable_unchecked, iter};
-use web_sys::{Element, Node};
+use wasm_bindgen::JsCast;
+use web_sys::{Element, HtmlElement, Node};
 
 #[doc(inline)]
 pub use self::key::Key;
@@ -32,10 +38,14 @@ pub use self::vcomp::{VChild, VComp};
 #[doc(inline)]
 pub use self::vlist::VList;
 #[doc(inline)]
+pub use self::vmemo::VMemo;
+#[doc(inline)]
 pub use self::vnode::VNode;
 #[doc(inline)]
 pub use self::vportal::VPortal;
 #[doc(inline)]
+pub use self::vraw::VRaw;
+#[doc(inline)]
 pub use self::vtag::VTag;
 #[doc(inline)]
 pub use self::vtext::VText;
@@ -304,7 +314,7 @@ impl Attributes {
                         break;
                     }
                     if new_value != old_value.as_ref() {
-                        Self::set_attribute(el, new_key, new_value);
+                        Self::update_attribute(el, new_key, old_value.as_ref(), new_value);
                     }
                 }
                 // new attributes
@@ -313,7 +323,7 @@ impl Attributes {
                         match old.get(key) {
                             Some(old_value) => {
                                 if value != old_value.as_ref() {
-                                    Self::set_attribute(el, key, value);
+                                    Self::update_attribute(el, key, old_value.as_ref(), value);
                                 }
                             }
                             None => {
@@ -360,11 +370,13 @@ impl Attributes {
 
         // Update existing or set new
         for (k, new) in new.iter() {
-            if match old.get(k) {
-                Some(old) => old != new,
-                None => true,
-            } {
-                el.set_attribute(k, new).unwrap();
+            match old.get(k) {
+                Some(old) => {
+                    if old != new {
+                        Self::update_attribute(el, k, old, new);
+                    }
+                }
+                None => Self::set_attribute(el, k, new),
             }
         }
 
@@ -380,6 +392,64 @@ impl Attributes {
         el.set_attribute(key, value).expect("invalid attribute key")
     }
 
+    /// Change the value of an attribute, that is already set on `el`.
+    ///
+    /// Inline styles are updated property by property, so that properties set from elsewhere,
+    /// like running CSS transitions, are kept.
+    fn update_attribute(el: &Element, key: &str, old: &str, new: &str) {
+        if key == "style" {
+            if let Some(el) = el.dyn_ref::<HtmlElement>() {
+                Self::update_style(el, old, new);
+                return;
+            }
+        }
+        Self::set_attribute(el, key, new)
+    }
+
+    /// Apply the changes between the inline styles `old` and `new` with
+    /// `CSSStyleDeclaration.setProperty`.
+    ///
+    /// Properties are only updated individually, when `new` declares the same properties as `old`
+    /// in the same order, followed by any new properties. Otherwise removed properties and
+    /// shorthand properties could change the values of other properties, so the whole attribute
+    /// is rewritten instead.
+    fn update_style(el: &HtmlElement, old_style: &str, new_style: &str) {
+        let old = crate::html::parse_declarations(old_style);
+        let new = crate::html::parse_declarations(new_style);
+        let same_order = old.len() <= new.len()
+            && old
+                .iter()
+                .zip(new.iter())
+                .all(|((old_property, _), (new_property, _))| old_property == new_property);
+        if !same_order {
+            Self::set_attribute(el, "style", new_style);
+            return;
+        }
+
+        // Properties after a changed property are set again, so that a changed shorthand property
+        // does not override them
+        let first_change = old
+            .iter()
+            .zip(new.iter())
+            .position(|(old, new)| old != new)
+            .unwrap_or(old.len());
+        let style = el.style();
+        for (property, value) in &new[first_change..] {
+            let value = value.trim_end();
+            let (value, priority) = match value.len().checked_sub("!important".len()) {
+                Some(i)
+                    if value.is_char_boundary(i)
+                        && value[i..].eq_ignore_ascii_case("!important") =>
+                {
+                    (value[..i].trim_end(), "important")
+                }
+                _ => (value, ""),
+            };
+            // Invalid declarations are ignored, just like in the attribute
+            let _ = style.set_property_with_priority(property, value, priority);
+        }
+    }
+
     fn remove_attribute(el: &Element, key: &str) {
         el.remove_attribute(key)
             .expect("could not remove attribute")
@@ -449,7 +519,7 @@ impl Apply for Attributes {
                     match unsafe { (new_v.get_unchecked(i), old_v.get_unchecked(i)) } {
                         (Some(new), Some(old)) => {
                             if new != old {
-                                set!(new);
+                                Self::update_attribute(el, key!(), old, new);
                             }
                         }
                         (Some(new), None) => set!(new),
@@ -0,0 +1,355 @@
+//! This module contains the creation of [VTag] subtrees by cloning cached `<template>` elements.
+//!
+//! Creating a subtree node by node costs a DOM call per element, attribute and text node. Lists
+//! often repeat the same structure for every row, so the static parts of a subtree (tags, static
+//! attributes and literal text) are built once into a template, which is then cloned for every
+//! new subtree of the same shape. Only the dynamic attributes and text nodes are set afterwards.
+//!
+//! Only subtrees, that are likely to be created repeatedly, are created from templates: rows of
+//! keyed lists and subtrees hoisted by the `html!` macro.
+
+use super::listeners::EventRoot;
+use super::vtag::SVG_NAMESPACE;
+use super::{AttrValue, Attributes, VList, VNode, VTag};
+use gloo_utils::document;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write;
+use std::hash::Hash;
+use wasm_bindgen::JsCast;
+use web_sys::{Document, Element, HtmlTemplateElement, Node, Text as TextNode};
+
+/// Maximum number of cached templates. The least recently built template is evicted first.
+const MAX_TEMPLATES: usize = 64;
+
+thread_local! {
+    /// Root elements of the built templates
+    static TEMPLATES: RefCell<Cache<TemplateKey, Element>> = RefCell::new(Cache::new(MAX_TEMPLATES));
+}
+
+/// Identifies the subtrees, that can be created from the same template
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum TemplateKey {
+    /// A subtree hoisted by the `html!` macro by the address of its call site and whether it is
+    /// created inside an SVG element
+    Hoisted { site: usize, svg: bool },
+    /// A keyed subtree by its shape
+    Shape(String),
+}
+
+/// A cache holding a bounded number of values, which evicts the oldest ones first
+struct Cache<K, V> {
+    values: HashMap<K, V>,
+    /// Keys of `values` in insertion order
+    order: VecDeque<K>,
+    capacity: usize,
+}
+
+impl<K: Clone + Eq + Hash, V> Cache<K, V> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            values: HashMap::new(),
+            order: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    /// Returns the value of `key`, inserting the one returned by `make`, if there is none
+    fn get_or_insert_with(&mut self, key: K, make: impl FnOnce() -> V) -> &V {
+        if !self.values.contains_key(&key) {
+            if self.order.len() >= self.capacity {
+                if let Some(oldest) = self.order.pop_front() {
+                    self.values.remove(&oldest);
+                }
+            }
+            self.order.push_back(key.clone());
+            self.values.insert(key.clone(), make());
+        }
+        &self.values[&key]
+    }
+}
+
+/// Creates the element of `tag` with its static attributes and all of its descendants by cloning
+/// a cached template.
+///
+/// Returns `None`, unless `tag` is keyed or was hoisted by the `html!` macro. Also returns `None`,
+/// if the subtree contains anything but elements and text, or if `tag` has no children, in which
+/// case a template would not save any work. The cloned element has to be linked to the subtree
+/// with [VTag::hydrate].
+pub(super) fn clone_element(tag: &VTag, parent: &Element) -> Option<Element> {
+    if tag.children().is_empty() {
+        return None;
+    }
+    let svg = is_svg(parent);
+    let key = match (tag.hoisted(), &tag.key) {
+        // Identified without walking the subtree, as it only contains literals
+        (Some(site), _) => TemplateKey::Hoisted {
+            site: site as *const _ as usize,
+            svg,
+        },
+        // Rows of keyed lists usually repeat the same structure
+        (None, Some(_)) => TemplateKey::Shape(shape(tag, svg)?),
+        (None, None) => return None,
+    };
+
+    TEMPLATES.with(|templates| {
+        let mut templates = templates.borrow_mut();
+        let template = templates.get_or_insert_with(key, || build(tag, parent));
+        Some(
+            document()
+                .import_node_with_deep(template, true)
+                .expect("can't clone template")
+                .unchecked_into(),
+        )
+    })
+}
+
+/// Links the children of a cloned element to `children` and applies everything the template does
+/// not contain
+pub(super) fn hydrate(el: &Element, children: &mut VList, event_root: EventRoot) {
+    hydrate_list(&mut el.first_child(), children, event_root);
+}
+
+fn hydrate_list(next: &mut Option<Node>, list: &mut VList, event_root: EventRoot) {
+    for node in list.nodes_mut() {
+        hydrate_node(next, node, event_root);
+    }
+}
+
+fn hydrate_node(next: &mut Option<Node>, node: &mut VNode, event_root: EventRoot) {
+    match node {
+        VNode::VList(list) => hydrate_list(next, list, event_root),
+        node => {
+            let dom_node = next.take().expect("cloned template is missing nodes");
+            *next = dom_node.next_sibling();
+            match node {
+                VNode::VTag(tag) => tag.hydrate(dom_node.unchecked_into(), event_root),
+                VNode::VText(text) => {
+                    if !matches!(text.text, AttrValue::Static(_)) {
+                        dom_node.set_node_value(Some(&text.text));
+                    }
+                    text.reference = Some(dom_node.unchecked_into::<TextNode>());
+                }
+                _ => unreachable!("template contains only elements and text"),
+            }
+        }
+    }
+}
+
+fn is_svg(parent: &Element) -> bool {
+    parent.namespace_uri().as_deref() == Some(SVG_NAMESPACE)
+}
+
+/// Describes the static structure of the subtree of `tag`. Subtrees of the same shape can be
+/// created from the same template.
+///
+/// Returns `None`, if the subtree can not be created from a template.
+fn shape(tag: &VTag, svg: bool) -> Option<String> {
+    let mut shape = String::from(if svg { "svg:" } else { "" });
+    if write_tag(&mut shape, tag) {
+        Some(shape)
+    } else {
+        None
+    }
+}
+
+fn write_tag(shape: &mut String, tag: &VTag) -> bool {
+    let _ = write!(shape, "<{}", tag.tag());
+    // Dynamic attributes may differ between subtrees and are applied after cloning
+    if let Attributes::Static(attrs) = &tag.attributes {
+        for [k, v] in attrs.iter() {
+            let _ = write!(shape, " {}={:?}", k, v);
+        }
+    }
+    shape.push('>');
+    let ok = write_list(shape, tag.children());
+    shape.push_str("</>");
+    ok
+}
+
+fn write_list(shape: &mut String, list: &VList) -> bool {
+    list.iter().all(|node| write_node(shape, node))
+}
+
+fn write_node(shape: &mut String, node: &VNode) -> bool {
+    match node {
+        VNode::VTag(tag) => write_tag(shape, tag),
+        VNode::VText(text) => {
+            match &text.text {
+                AttrValue::Static(s) => {
+                    let _ = write!(shape, "{:?}", s);
+                }
+                // Dynamic text is set after cloning
+                _ => shape.push('#'),
+            }
+            true
+        }
+        // Empty lists render a placeholder, that depends on the diff
+        VNode::VList(list) => !list.is_empty() && write_list(shape, list),
+        _ => false,
+    }
+}
+
+/// Builds the template for the subtree of `tag`
+fn build(tag: &VTag, parent: &Element) -> Element {
+    // Nodes created for the content of a template belong to an inert document, so they don't
+    // load any resources.
+    let template: HtmlTemplateElement = document()
+        .create_element("template")
+        .expect("can't create template element")
+        .unchecked_into();
+    let document = template
+        .content()
+        .owner_document()
+        .expect("template content has no document");
+    build_tag(&document, tag, parent)
+}
+
+fn build_tag(document: &Document, tag: &VTag, parent: &Element) -> Element {
+    let el = tag.create_element_in(document, parent);
+    if let Attributes::Static(attrs) = &tag.attributes {
+        for [k, v] in attrs.iter() {
+            Attributes::set_attribute(&el, k, v);
+        }
+    }
+    build_list(document, &el, tag.children());
+    el
+}
+
+fn build_list(document: &Document, el: &Element, list: &VList) {
+    for node in list.iter() {
+        build_node(document, el, node);
+    }
+}
+
+fn build_node(document: &Document, el: &Element, node: &VNode) {
+    let child: Node = match node {
+        VNode::VTag(tag) => build_tag(document, tag, el).into(),
+        VNode::VText(text) => document
+            .create_text_node(match &text.text {
+                AttrValue::Static(s) => s,
+                _ => "",
+            })
+            .into(),
+        VNode::VList(list) => return build_list(document, el, list),
+        _ => unreachable!("template contains only elements and text"),
+    };
+    el.append_child(&child).expect("failed to append child");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::html;
+    use crate::virtual_dom::VMemo;
+
+    fn shape_of(node: VNode) -> Option<String> {
+        match node {
+            VNode::VTag(tag) => shape(&tag, false),
+            _ => panic!("expected tag"),
+        }
+    }
+
+    #[test]
+    fn shape_ignores_dynamic_parts() {
+        let row = |text: String, class: &'static str| {
+            html! { <tr class={class}><td>{"label"}</td><td>{ text }</td></tr> }
+        };
+
+        assert_eq!(
+            shape_of(row("a".to_owned(), "x")),
+            shape_of(row("b".to_owned(), "y"))
+        );
+        assert_eq!(
+            shape_of(row("a".to_owned(), "x")).unwrap(),
+            r#"<tr><td>"label"</><td>#</></>"#
+        );
+    }
+
+    #[test]
+    fn shape_contains_static_parts() {
+        assert_ne!(
+            shape_of(html! { <p class="a"><b/></p> }),
+            shape_of(html! { <p class="b"><b/></p> })
+        );
+        assert_ne!(
+            shape_of(html! { <p>{"a"}</p> }),
+            shape_of(html! { <p>{"b"}</p> })
+        );
+        assert_ne!(
+            shape_of(html! { <p><b/><i/></p> }),
+            shape_of(html! { <p><b><i/></b></p> })
+        );
+    }
+
+    #[test]
+    fn cache_evicts_oldest_values() {
+        let mut cache = Cache::new(2);
+        assert_eq!(*cache.get_or_insert_with("a", || 1), 1);
+        assert_eq!(*cache.get_or_insert_with("b", || 2), 2);
+        assert_eq!(*cache.get_or_insert_with("a", || 3), 1);
+        assert_eq!(*cache.get_or_insert_with("c", || 4), 4);
+        assert_eq!(cache.values.len(), 2);
+        assert_eq!(*cache.get_or_insert_with("a", || 5), 5);
+        assert_eq!(*cache.get_or_insert_with("c", || 6), 4);
+    }
+
+    #[test]
+    fn shape_rejects_other_nodes() {
+        assert_eq!(shape_of(html! { <p>{ html! {} }</p> }), None);
+        assert_eq!(
+            shape_of(html! { <p>{ VMemo::new((), || html! { <b/> }) }</p> }),
+            None
+        );
+    }
+}
+
+#[cfg(test)]
+mod layout_tests {
+    extern crate self as yew;
+
+    use crate::html;
+    use crate::virtual_dom::layout_tests::{diff_layouts, TestLayout};
+
+    #[cfg(feature = "wasm_test")]
+    use wasm_bindgen_test::{wasm_bindgen_test as test, wasm_bindgen_test_configure};
+
+    #[cfg(feature = "wasm_test")]
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[test]
+    fn diff() {
+        let row = |i: usize| {
+            html! {
+                <li key={i} class={format!("row-{}", i)}>
+                    <span>{"#"}</span>
+                    <b>{ i }</b>
+                </li>
+            }
+        };
+
+        let layout1 = TestLayout {
+            name: "1",
+            node: html! { <ul>{ for (0..2).map(row) }</ul> },
+            expected: r#"<ul><li class="row-0"><span>#</span><b>0</b></li><li class="row-1"><span>#</span><b>1</b></li></ul>"#,
+        };
+
+        let layout2 = TestLayout {
+            name: "2",
+            node: html! { <ul>{ for (1..4).rev().map(row) }</ul> },
+            expected: r#"<ul><li class="row-3"><span>#</span><b>3</b></li><li class="row-2"><span>#</span><b>2</b></li><li class="row-1"><span>#</span><b>1</b></li></ul>"#,
+        };
+
+        let layout3 = TestLayout {
+            name: "3",
+            node: html! {
+                <svg>
+                    <g><circle r="1"/>{"a"}</g>
+                </svg>
+            },
+            expected: r#"<svg><g><circle r="1"></circle>a</g></svg>"#,
+        };
+
+        diff_layouts(vec![layout1, layout2, layout3]);
+    }
+}
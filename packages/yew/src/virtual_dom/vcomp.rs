@@ -129,10 +129,18 @@ impl VComp {
     where
         COMP: Component,
     {
+        // The first node of a component is also used to position its siblings, so a forwarded
+        // ref is passed to the component and the component is referenced with a separate ref
+        let (node_ref, forwarded_ref) = if COMP::FORWARD_REF {
+            (NodeRef::default(), Some(node_ref))
+        } else {
+            (node_ref, None)
+        };
+
         VComp {
             type_id: TypeId::of::<COMP>(),
             node_ref,
-            props: Some(Box::new(PropsWrapper::<COMP>::new(props))),
+            props: Some(Box::new(PropsWrapper::<COMP>::new(props, forwarded_ref))),
             scope: None,
             key,
 
@@ -185,11 +193,15 @@ trait Mountable {
 
 struct PropsWrapper<COMP: Component> {
     props: Rc<COMP::Properties>,
+    forwarded_ref: Option<NodeRef>,
 }
 
 impl<COMP: Component> PropsWrapper<COMP> {
-    pub fn new(props: Rc<COMP::Properties>) -> Self {
-        Self { props }
+    pub fn new(props: Rc<COMP::Properties>, forwarded_ref: Option<NodeRef>) -> Self {
+        Self {
+            props,
+            forwarded_ref,
+        }
     }
 }
 
@@ -197,6 +209,7 @@ impl<COMP: Component> Mountable for PropsWrapper<COMP> {
     fn copy(&self) -> Box<dyn Mountable> {
         let wrapper: PropsWrapper<COMP> = PropsWrapper {
             props: Rc::clone(&self.props),
+            forwarded_ref: self.forwarded_ref.clone(),
         };
         Box::new(wrapper)
     }
@@ -209,14 +222,28 @@ impl<COMP: Component> Mountable for PropsWrapper<COMP> {
         next_sibling: NodeRef,
     ) -> Box<dyn Scoped> {
         let scope: Scope<COMP> = Scope::new(Some(parent_scope.clone()));
-        scope.mount_in_place(parent, next_sibling, node_ref, self.props);
+        self.forwarded_ref
+            .as_ref()
+            .unwrap_or(&node_ref)
+            .set_scope(scope.clone().into());
+        scope.mount_in_place(
+            parent,
+            next_sibling,
+            node_ref,
+            self.forwarded_ref,
+            self.props,
+        );
 
         Box::new(scope)
     }
 
     fn reuse(self: Box<Self>, node_ref: NodeRef, scope: &dyn Scoped, next_sibling: NodeRef) {
         let scope: Scope<COMP> = scope.to_any().downcast();
-        scope.reuse(self.props, node_ref, next_sibling);
+        self.forwarded_ref
+            .as_ref()
+            .unwrap_or(&node_ref)
+            .set_scope(scope.clone().into());
+        scope.reuse(self.props, node_ref, self.forwarded_ref, next_sibling);
     }
 }
 
@@ -54,6 +54,12 @@ macro_rules! test_log {
     };
 }
 
+#[cfg(all(test, feature = "wasm_bench"))]
+thread_local! {
+    /// Number of nodes moved by keyed diffing. Used for benchmarking.
+    static MOVES: std::cell::Cell<usize> = Default::default();
+}
+
 struct ElementWriter<'s> {
     parent_scope: &'s AnyScope,
     parent: &'s Element,
@@ -127,6 +133,11 @@ impl VList {
         self.fully_keyed = self.children.iter().all(|ch| ch.has_key());
     }
 
+    /// Mutable access to the children, that does not allow changing their keys
+    pub(super) fn nodes_mut(&mut self) -> &mut [VNode] {
+        &mut self.children
+    }
+
     /// Diff and patch unkeyed child lists
     fn apply_unkeyed(
         parent_scope: &AnyScope,
@@ -167,8 +178,8 @@ impl VList {
 
     /// Diff and patch fully keyed child lists.
     ///
-    /// Optimized for node addition or removal from either end of the list and small changes in the
-    /// middle.
+    /// Optimized for node addition or removal from either end of the list. Reordered children in
+    /// the middle are moved with the minimal number of DOM operations.
     fn apply_keyed(
         parent_scope: &AnyScope,
         parent: &Element,
@@ -226,34 +237,40 @@ impl VList {
             writer = writer.patch(l, r);
         }
 
-        // Diff mismatched children in the middle
-        let mut next_right_key: Option<&Key> = None;
-        let mut rights_diff: HashMap<&Key, (VNode, Option<&Key>)> =
+        // Diff mismatched children in the middle.
+        //
+        // Children, whose relative order is the same in both lists, are found as the longest
+        // increasing subsequence of their positions in the rights list. These stay in place and
+        // only the remaining children are moved, which results in the minimal number of DOM moves.
+        let mut rights_diff: HashMap<&Key, (usize, VNode)> =
             HashMap::with_capacity(rights_to - from_start);
-        for (k, v) in rights_keys[from_start..rights_to]
+        for (i, (k, v)) in rights_keys[from_start..rights_to]
             .iter()
             .zip(rights.drain(from_start..)) // rights_to.. has been drained already
-            .rev()
+            .enumerate()
         {
-            let next_r_key = std::mem::replace(&mut next_right_key, Some(k));
-            rights_diff.insert(k, (v, next_r_key));
+            rights_diff.insert(k, (i, v));
         }
-        let mut next_left_key: Option<&Key> = None;
-        for (l_key, l) in lefts_keys[from_start..lefts_to]
+        let rights_positions: Vec<Option<usize>> = lefts_keys[from_start..lefts_to]
+            .iter()
+            .map(|k| rights_diff.get(k).map(|(i, _)| *i))
+            .collect();
+        let stable = longest_increasing_subsequence(&rights_positions);
+        for ((l_key, l), stable) in lefts_keys[from_start..lefts_to]
             .iter()
             .zip(lefts[from_start..lefts_to].iter_mut())
+            .zip(stable)
             .rev()
         {
             match rights_diff.remove(l_key) {
                 // Reorder and diff any existing children
-                Some((r, next_r_key)) => {
-                    match (next_r_key, next_left_key) {
-                        // If the next sibling was already the same, we don't need to move the node
-                        (Some(r_next), Some(l_next)) if r_next == l_next => (),
-                        _ => {
-                            test_log!("moving as next: {:?}", r);
-                            r.move_before(parent, &writer.next_sibling.get());
-                        }
+                Some((_, r)) => {
+                    // Nodes in the longest increasing subsequence already are in the right order
+                    if !stable {
+                        test_log!("moving as next: {:?}", r);
+                        #[cfg(all(test, feature = "wasm_bench"))]
+                        MOVES.with(|m| m.set(m.get() + 1));
+                        r.move_before(parent, &writer.next_sibling.get());
                     }
                     writer = writer.patch(l, r);
                 }
@@ -262,11 +279,10 @@ impl VList {
                     writer = writer.add(l);
                 }
             }
-            next_left_key = Some(l_key);
         }
 
         // Remove any extra rights
-        for (_, (mut r, _)) in rights_diff.drain() {
+        for (_, (_, mut r)) in rights_diff.drain() {
             test_log!("removing: {:?}", r);
             r.detach(parent);
         }
@@ -284,6 +300,43 @@ impl VList {
     }
 }
 
+/// Returns a mask of the members of a longest strictly increasing subsequence of the [Some]
+/// values in `seq`. [None] values are never members.
+///
+/// Runs in O(n log n) time.
+fn longest_increasing_subsequence(seq: &[Option<usize>]) -> Vec<bool> {
+    // Indices into `seq` of the smallest tail value of each increasing subsequence length found
+    let mut tails: Vec<usize> = Vec::new();
+    // Index of the previous member of the subsequence ending at each index of `seq`
+    let mut predecessors: Vec<Option<usize>> = vec![None; seq.len()];
+
+    for (i, v) in seq.iter().enumerate() {
+        let v = match v {
+            Some(v) => v,
+            None => continue,
+        };
+        let pos = tails
+            .binary_search_by(|t| seq[*t].as_ref().unwrap().cmp(v))
+            .unwrap_or_else(|pos| pos);
+        if pos > 0 {
+            predecessors[i] = Some(tails[pos - 1]);
+        }
+        if pos == tails.len() {
+            tails.push(i);
+        } else {
+            tails[pos] = i;
+        }
+    }
+
+    let mut members = vec![false; seq.len()];
+    let mut next = tails.last().copied();
+    while let Some(i) = next {
+        members[i] = true;
+        next = predecessors[i];
+    }
+    members
+}
+
 impl VDiff for VList {
     fn detach(&mut self, parent: &Element) {
         for mut child in self.children.drain(..) {
@@ -1257,3 +1310,212 @@ mod layout_tests_keys {
         diff_layouts(layouts);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::longest_increasing_subsequence;
+
+    #[test]
+    fn lis_empty() {
+        assert!(longest_increasing_subsequence(&[]).is_empty());
+        assert_eq!(
+            longest_increasing_subsequence(&[None, None]),
+            vec![false, false]
+        );
+    }
+
+    #[test]
+    fn lis_sorted() {
+        assert_eq!(
+            longest_increasing_subsequence(&[Some(0), Some(1), Some(2)]),
+            vec![true, true, true]
+        );
+    }
+
+    #[test]
+    fn lis_reversed() {
+        assert_eq!(
+            longest_increasing_subsequence(&[Some(2), Some(1), Some(0)])
+                .into_iter()
+                .filter(|b| *b)
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn lis_moved_to_front() {
+        assert_eq!(
+            longest_increasing_subsequence(&[Some(4), Some(0), Some(1), Some(2), Some(3)]),
+            vec![false, true, true, true, true]
+        );
+    }
+
+    #[test]
+    fn lis_skips_none() {
+        assert_eq!(
+            longest_increasing_subsequence(&[Some(3), None, Some(0), Some(1), None, Some(2)]),
+            vec![false, false, true, true, false, true]
+        );
+    }
+
+    #[test]
+    fn lis_mixed() {
+        let seq = [
+            Some(5),
+            Some(1),
+            Some(6),
+            Some(2),
+            Some(7),
+            Some(3),
+            Some(0),
+            Some(4),
+        ];
+        let members = longest_increasing_subsequence(&seq);
+        let picked: Vec<usize> = seq
+            .iter()
+            .zip(members)
+            .filter_map(|(v, m)| if m { *v } else { None })
+            .collect();
+        assert_eq!(picked.len(), 4);
+        assert!(picked.windows(2).all(|w| w[0] < w[1]));
+    }
+}
+
+#[cfg(all(test, feature = "wasm_bench"))]
+mod benchmarks {
+    use super::*;
+    use crate::html;
+    use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    const LEN: usize = 1000;
+
+    fn make_list(keys: &[usize]) -> VNode {
+        VList::with_children(
+            keys.iter()
+                .map(|k| html! { <li key={k.to_string()}>{ k }</li> })
+                .collect(),
+            None,
+        )
+        .into()
+    }
+
+    /// Mounts a keyed list of `old` keys, diffs it against `new` keys and returns the number of
+    /// moved nodes
+    fn apply_moves(old: &[usize], new: &[usize]) -> usize {
+        let scope = AnyScope::test();
+        let parent = gloo_utils::document().create_element("div").unwrap();
+
+        let mut ancestor = make_list(old);
+        ancestor.apply(&scope, &parent, NodeRef::default(), None);
+
+        MOVES.with(|m| m.set(0));
+        let mut node = make_list(new);
+        node.apply(&scope, &parent, NodeRef::default(), Some(ancestor));
+        MOVES.with(|m| m.get())
+    }
+
+    /// Number of nodes the previous keyed diffing algorithm would have moved.
+    ///
+    /// It only skipped moving a node, if its next sibling was the same in both lists.
+    fn legacy_moves(old: &[usize], new: &[usize]) -> usize {
+        let from_start = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+        if from_start == std::cmp::min(old.len(), new.len()) {
+            return 0;
+        }
+        let from_end = old[from_start..]
+            .iter()
+            .rev()
+            .zip(new[from_start..].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let old = &old[from_start..old.len() - from_end];
+        let new = &new[from_start..new.len() - from_end];
+
+        let next_in_old: HashMap<usize, Option<usize>> = old
+            .iter()
+            .enumerate()
+            .map(|(i, k)| (*k, old.get(i + 1).copied()))
+            .collect();
+        let mut next_in_new = None;
+        let mut moves = 0;
+        for k in new.iter().rev() {
+            if let Some(next_in_old) = next_in_old.get(k) {
+                match (next_in_old, next_in_new) {
+                    (Some(a), Some(b)) if *a == b => (),
+                    _ => moves += 1,
+                }
+            }
+            next_in_new = Some(*k);
+        }
+        moves
+    }
+
+    fn shuffled(mut keys: Vec<usize>) -> Vec<usize> {
+        // Deterministic Fisher-Yates shuffle with a linear congruential generator
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        for i in (1..keys.len()).rev() {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            keys.swap(i, (state >> 33) as usize % (i + 1));
+        }
+        keys
+    }
+
+    #[wasm_bindgen_test]
+    fn bench_keyed_reorder() {
+        let old: Vec<usize> = (0..LEN).collect();
+
+        let mut move_last_to_front = old.clone();
+        move_last_to_front.rotate_right(1);
+        let mut move_first_to_back = old.clone();
+        move_first_to_back.rotate_left(1);
+        let mut swap_ends = old.clone();
+        swap_ends.swap(0, LEN - 1);
+        let mut swap_middle = old.clone();
+        swap_middle.swap(LEN / 4, LEN * 3 / 4);
+        let reversed: Vec<usize> = old.iter().rev().copied().collect();
+        let mut interleaved = old.clone();
+        interleaved.retain(|k| k % 3 != 0);
+        interleaved.extend((LEN..LEN + LEN / 3).rev());
+
+        let cases = vec![
+            ("move last to front", move_last_to_front),
+            ("move first to back", move_first_to_back),
+            ("swap ends", swap_ends),
+            ("swap middle", swap_middle),
+            ("reverse", reversed),
+            ("remove and append", interleaved),
+            ("shuffle", shuffled(old.clone())),
+        ];
+
+        let max_name_len = cases
+            .iter()
+            .map(|(name, _)| name.len())
+            .max()
+            .unwrap_or_default();
+        let mut report = String::new();
+        for (name, new) in cases {
+            use std::fmt::Write;
+
+            let res =
+                easybench_wasm::bench_env_limit(2.0, (old.clone(), new.clone()), |(old, new)| {
+                    apply_moves(&old, &new)
+                });
+            write!(
+                &mut report,
+                "\n\t\t{:<width$}: {:>5} moves (previously {:>5}), {:>12.1} ns",
+                name,
+                apply_moves(&old, &new),
+                legacy_moves(&old, &new),
+                res.ns_per_iter,
+                width = max_name_len,
+            )
+            .unwrap();
+        }
+        wasm_bindgen_test::console_log!("keyed_reorder ({} nodes):{}", LEN, report);
+    }
+}
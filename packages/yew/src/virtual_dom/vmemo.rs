@@ -0,0 +1,275 @@
+//! This module contains the implementation of a memoized subtree `VMemo`.
+
+use super::{Key, VDiff, VNode};
+use crate::html::{AnyScope, NodeRef};
+use std::any::Any;
+use std::fmt;
+use std::rc::Rc;
+use web_sys::{Element, Node};
+
+/// Type-erased dependencies of a [VMemo]
+trait Deps {
+    fn as_any(&self) -> &dyn Any;
+    fn eq_deps(&self, other: &dyn Deps) -> bool;
+}
+
+impl<T: PartialEq + 'static> Deps for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn eq_deps(&self, other: &dyn Deps) -> bool {
+        other.as_any().downcast_ref::<T>() == Some(self)
+    }
+}
+
+/// A memoized subtree of the virtual DOM.
+///
+/// The subtree is only rendered and diffed, if the dependencies differ from the ones of the
+/// [VMemo] it replaces. Otherwise the previously rendered subtree and its DOM nodes are reused
+/// as is.
+pub struct VMemo {
+    deps: Rc<dyn Deps>,
+    render: Rc<dyn Fn() -> VNode>,
+    /// The rendered subtree. Set when rendered.
+    node: Option<Box<VNode>>,
+    /// Stable reference to the next sibling of the subtree. Linked to the current next sibling on
+    /// every apply, so components inside a reused subtree keep rendering at the right position.
+    next_sibling: NodeRef,
+    /// Reference to the first node of the subtree. Set when rendered.
+    node_ref: NodeRef,
+    pub key: Option<Key>,
+}
+
+impl Clone for VMemo {
+    fn clone(&self) -> Self {
+        Self {
+            deps: Rc::clone(&self.deps),
+            render: Rc::clone(&self.render),
+            node: None,
+            next_sibling: NodeRef::default(),
+            node_ref: NodeRef::default(),
+            key: self.key.clone(),
+        }
+    }
+}
+
+impl VMemo {
+    /// Creates a [VMemo], that calls `render` to render the subtree only if `deps` differ from the
+    /// dependencies of the previously rendered [VMemo].
+    pub fn new<D, F>(deps: D, render: F) -> Self
+    where
+        D: PartialEq + 'static,
+        F: Fn() -> VNode + 'static,
+    {
+        Self {
+            deps: Rc::new(deps),
+            render: Rc::new(render),
+            node: None,
+            next_sibling: NodeRef::default(),
+            node_ref: NodeRef::default(),
+            key: None,
+        }
+    }
+
+    /// Returns the rendered subtree, if this [VMemo] has been rendered
+    pub fn node(&self) -> Option<&VNode> {
+        self.node.as_deref()
+    }
+
+    /// Renders the subtree without diffing it
+    pub(crate) fn render_subtree(&self) -> VNode {
+        (self.render)()
+    }
+
+    pub(crate) fn first_node(&self) -> Option<Node> {
+        self.node.as_ref().and_then(|node| node.first_node())
+    }
+
+    pub(crate) fn unchecked_first_node(&self) -> Node {
+        self.node
+            .as_ref()
+            .expect("VMemo is not mounted")
+            .unchecked_first_node()
+    }
+
+    pub(crate) fn move_before(&self, parent: &Element, next_sibling: &Option<Node>) {
+        self.node
+            .as_ref()
+            .expect("VMemo is not mounted")
+            .move_before(parent, next_sibling);
+    }
+}
+
+impl VDiff for VMemo {
+    fn detach(&mut self, parent: &Element) {
+        if let Some(mut node) = self.node.take() {
+            node.detach(parent);
+        }
+        self.next_sibling.set(None);
+    }
+
+    fn apply(
+        &mut self,
+        parent_scope: &AnyScope,
+        parent: &Element,
+        next_sibling: NodeRef,
+        ancestor: Option<VNode>,
+    ) -> NodeRef {
+        let ancestor = match ancestor {
+            Some(VNode::VMemo(mut memo)) if self.key == memo.key => {
+                self.next_sibling = memo.next_sibling.clone();
+                self.next_sibling.link(next_sibling);
+
+                if self.deps.eq_deps(&*memo.deps) {
+                    if let Some(node) = memo.node.take() {
+                        // Dependencies did not change. The subtree is still mounted in place.
+                        self.node = Some(node);
+                        self.node_ref = memo.node_ref;
+                        return self.node_ref.clone();
+                    }
+                }
+                memo.node.take().map(|node| *node)
+            }
+            ancestor => {
+                self.next_sibling.link(next_sibling);
+                ancestor
+            }
+        };
+
+        let mut node = (self.render)();
+        self.node_ref = node.apply(parent_scope, parent, self.next_sibling.clone(), ancestor);
+        self.node = Some(Box::new(node));
+        self.node_ref.clone()
+    }
+}
+
+impl PartialEq for VMemo {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key && self.deps.eq_deps(&*other.deps)
+    }
+}
+
+impl fmt::Debug for VMemo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "VMemo {{ node: {:?} }}", self.node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::html;
+    use std::cell::Cell;
+
+    #[test]
+    fn compares_deps() {
+        let a = VMemo::new(1u32, || html! {});
+        let b = VMemo::new(1u32, || html! { <div/> });
+        let c = VMemo::new(2u32, || html! {});
+        let d = VMemo::new(1u64, || html! {});
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_ne!(a, d);
+    }
+
+    #[test]
+    fn does_not_render_eagerly() {
+        let renders = Rc::new(Cell::new(0));
+        let memo = {
+            let renders = renders.clone();
+            VMemo::new((), move || {
+                renders.set(renders.get() + 1);
+                html! {}
+            })
+        };
+        let _ = memo.clone();
+
+        assert_eq!(renders.get(), 0);
+        assert!(memo.node().is_none());
+    }
+}
+
+#[cfg(test)]
+mod layout_tests {
+    extern crate self as yew;
+
+    use super::*;
+    use crate::html;
+    use crate::virtual_dom::layout_tests::{diff_layouts, TestLayout};
+    use std::cell::Cell;
+
+    #[cfg(feature = "wasm_test")]
+    use wasm_bindgen_test::{wasm_bindgen_test as test, wasm_bindgen_test_configure};
+
+    #[cfg(feature = "wasm_test")]
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[test]
+    fn diff() {
+        let layout1 = TestLayout {
+            name: "1",
+            node: html! {
+                <>
+                    {"a"}
+                    {VMemo::new(1, || html! { <p>{"b"}</p> })}
+                    {"c"}
+                </>
+            },
+            expected: "a<p>b</p>c",
+        };
+
+        let layout2 = TestLayout {
+            name: "2",
+            node: html! {
+                <>
+                    {VMemo::new(1, || html! { <p>{"b"}</p> })}
+                    {"c"}
+                </>
+            },
+            expected: "<p>b</p>c",
+        };
+
+        let layout3 = TestLayout {
+            name: "3",
+            node: html! {
+                <>
+                    {VMemo::new(2, || html! { <p>{"d"}</p> })}
+                    {"c"}
+                </>
+            },
+            expected: "<p>d</p>c",
+        };
+
+        diff_layouts(vec![layout1, layout2, layout3]);
+    }
+
+    #[test]
+    fn skips_rendering_with_equal_deps() {
+        let scope = AnyScope::test();
+        let parent = gloo_utils::document().create_element("div").unwrap();
+        let renders = Rc::new(Cell::new(0));
+        let make = |deps: u32| {
+            let renders = renders.clone();
+            VNode::VMemo(VMemo::new(deps, move || {
+                renders.set(renders.get() + 1);
+                html! { <p>{ deps }</p> }
+            }))
+        };
+
+        let mut ancestor = make(1);
+        ancestor.apply(&scope, &parent, NodeRef::default(), None);
+        assert_eq!(renders.get(), 1);
+
+        let mut node = make(1);
+        node.apply(&scope, &parent, NodeRef::default(), Some(ancestor));
+        assert_eq!(renders.get(), 1);
+        assert_eq!(parent.inner_html(), "<p>1</p>");
+
+        let mut next = make(2);
+        next.apply(&scope, &parent, NodeRef::default(), Some(node));
+        assert_eq!(renders.get(), 2);
+        assert_eq!(parent.inner_html(), "<p>2</p>");
+    }
+}
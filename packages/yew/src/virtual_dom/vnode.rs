@@ -1,6 +1,6 @@
 //! This module contains the implementation of abstract virtual node.
 
-use super::{Key, VChild, VComp, VDiff, VList, VPortal, VTag, VText};
+use super::{AttrValue, Key, VChild, VComp, VDiff, VList, VMemo, VPortal, VRaw, VTag, VText};
 use crate::html::{AnyScope, Component, NodeRef};
 use gloo::console;
 use std::cmp::PartialEq;
@@ -23,19 +23,57 @@ pub enum VNode {
     VList(VList),
     /// A portal to another part of the document
     VPortal(VPortal),
+    /// A memoized subtree, that is only rendered when its dependencies change
+    VMemo(VMemo),
+    /// A string of HTML, that is rendered as it is
+    VRaw(VRaw),
     /// A holder for any `Node` (necessary for replacing node).
     VRef(Node),
 }
 
 impl VNode {
+    /// Creates a node rendering a string of HTML as it is.
+    ///
+    /// The HTML can contain any number of top-level nodes. It is not escaped or checked in any
+    /// way, so it must never contain untrusted input. Enable the `sanitize` feature and use
+    /// [`VNode::from_html_sanitized`] for HTML from untrusted sources.
+    ///
+    /// # Example
+    /// ```
+    /// # use yew::prelude::*;
+    /// # fn test() -> Html {
+    /// html! {
+    ///     <div class="markdown">
+    ///         { Html::from_html_unchecked("<h1>Title</h1><p>Paragraph</p>".into()) }
+    ///     </div>
+    /// }
+    /// # }
+    /// ```
+    pub fn from_html_unchecked(html: AttrValue) -> Self {
+        VNode::VRaw(VRaw::new(html))
+    }
+
+    /// Creates a node rendering a string of HTML, that keeps only allowed elements and attributes.
+    ///
+    /// The HTML is parsed into an inert `<template>` and everything, that is not on an allowlist,
+    /// is removed before it is inserted: `<script>`, `<style>`, `<iframe>`, `<svg>` and similar
+    /// elements with their content, other unknown elements in favour of their content, comments,
+    /// event handler and `style` attributes, and URLs with schemes other than `http`, `https`
+    /// and `mailto`.
+    #[cfg(feature = "sanitize")]
+    pub fn from_html_sanitized(html: AttrValue) -> Self {
+        VNode::VRaw(VRaw::sanitized(html))
+    }
+
     pub fn key(&self) -> Option<Key> {
         match self {
             VNode::VComp(vcomp) => vcomp.key.clone(),
             VNode::VList(vlist) => vlist.key.clone(),
-            VNode::VRef(_) => None,
+            VNode::VRef(_) | VNode::VRaw(_) => None,
             VNode::VTag(vtag) => vtag.key.clone(),
             VNode::VText(_) => None,
             VNode::VPortal(vportal) => vportal.node.key(),
+            VNode::VMemo(vmemo) => vmemo.key.clone(),
         }
     }
 
@@ -44,9 +82,10 @@ impl VNode {
         match self {
             VNode::VComp(vcomp) => vcomp.key.is_some(),
             VNode::VList(vlist) => vlist.key.is_some(),
-            VNode::VRef(_) | VNode::VText(_) => false,
+            VNode::VRef(_) | VNode::VText(_) | VNode::VRaw(_) => false,
             VNode::VTag(vtag) => vtag.key.is_some(),
             VNode::VPortal(vportal) => vportal.node.has_key(),
+            VNode::VMemo(vmemo) => vmemo.key.is_some(),
         }
     }
 
@@ -63,6 +102,8 @@ impl VNode {
             VNode::VList(vlist) => vlist.get(0).and_then(VNode::first_node),
             VNode::VRef(node) => Some(node.clone()),
             VNode::VPortal(vportal) => vportal.next_sibling(),
+            VNode::VMemo(vmemo) => vmemo.first_node(),
+            VNode::VRaw(vraw) => vraw.nodes().first().cloned(),
         }
     }
 
@@ -94,6 +135,8 @@ impl VNode {
                 .unchecked_first_node(),
             VNode::VRef(node) => node.clone(),
             VNode::VPortal(_) => panic!("portals have no first node, they are empty inside"),
+            VNode::VMemo(vmemo) => vmemo.unchecked_first_node(),
+            VNode::VRaw(vraw) => vraw.nodes().first().expect("VRaw is not mounted").clone(),
         }
     }
 
@@ -111,6 +154,8 @@ impl VNode {
                     .move_before(parent, next_sibling);
             }
             VNode::VPortal(_) => {} // no need to move portals
+            VNode::VMemo(vmemo) => vmemo.move_before(parent, next_sibling),
+            VNode::VRaw(vraw) => vraw.move_before(parent, next_sibling),
             _ => super::insert_node(&self.unchecked_first_node(), parent, next_sibling.as_ref()),
         };
     }
@@ -130,6 +175,8 @@ impl VDiff for VNode {
                 }
             }
             VNode::VPortal(ref mut vportal) => vportal.detach(parent),
+            VNode::VMemo(ref mut vmemo) => vmemo.detach(parent),
+            VNode::VRaw(ref mut vraw) => vraw.detach(parent),
         }
     }
 
@@ -166,6 +213,10 @@ impl VDiff for VNode {
             VNode::VPortal(ref mut vportal) => {
                 vportal.apply(parent_scope, parent, next_sibling, ancestor)
             }
+            VNode::VMemo(ref mut vmemo) => {
+                vmemo.apply(parent_scope, parent, next_sibling, ancestor)
+            }
+            VNode::VRaw(ref mut vraw) => vraw.apply(parent_scope, parent, next_sibling, ancestor),
         }
     }
 }
@@ -197,6 +248,13 @@ impl From<VTag> for VNode {
     }
 }
 
+impl From<VMemo> for VNode {
+    #[inline]
+    fn from(vmemo: VMemo) -> Self {
+        VNode::VMemo(vmemo)
+    }
+}
+
 impl From<VComp> for VNode {
     #[inline]
     fn from(vcomp: VComp) -> Self {
@@ -237,6 +295,8 @@ impl fmt::Debug for VNode {
             VNode::VList(ref vlist) => vlist.fmt(f),
             VNode::VRef(ref vref) => write!(f, "VRef ( \"{}\" )", crate::utils::print_node(vref)),
             VNode::VPortal(ref vportal) => vportal.fmt(f),
+            VNode::VMemo(ref vmemo) => vmemo.fmt(f),
+            VNode::VRaw(ref vraw) => vraw.fmt(f),
         }
     }
 }
@@ -248,6 +308,8 @@ impl PartialEq for VNode {
             (VNode::VText(a), VNode::VText(b)) => a == b,
             (VNode::VList(a), VNode::VList(b)) => a == b,
             (VNode::VRef(a), VNode::VRef(b)) => a == b,
+            (VNode::VRaw(a), VNode::VRaw(b)) => a == b,
+            (VNode::VMemo(a), VNode::VMemo(b)) => a == b,
             // TODO: Need to improve PartialEq for VComp before enabling.
             (VNode::VComp(_), VNode::VComp(_)) => false,
             _ => false,
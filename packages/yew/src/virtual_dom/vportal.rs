@@ -1,5 +1,6 @@
 //! This module contains the implementation of a portal `VPortal`.
 
+use super::listeners::EventRoot;
 use super::{VDiff, VNode};
 use crate::html::{AnyScope, NodeRef};
 use web_sys::{Element, Node};
@@ -14,12 +15,17 @@ pub struct VPortal {
     pub node: Box<VNode>,
     /// The next sibling after the portal. Set when rendered
     sibling_ref: NodeRef,
+    /// The delegation root handling events on the host. Set when rendered
+    event_root: Option<EventRoot>,
 }
 
 impl VDiff for VPortal {
     fn detach(&mut self, _: &Element) {
         self.node.detach(&self.host);
         self.sibling_ref.set(None);
+        if let Some(event_root) = self.event_root.take() {
+            event_root.remove_target(&self.host);
+        }
     }
 
     fn apply(
@@ -29,14 +35,22 @@ impl VDiff for VPortal {
         next_sibling: NodeRef,
         ancestor: Option<VNode>,
     ) -> NodeRef {
+        // The host may be outside of the element the app is mounted in
+        parent_scope.event_root.add_target(&self.host);
+        self.event_root = Some(parent_scope.event_root);
+
         let inner_ancestor = match ancestor {
             Some(VNode::VPortal(old_portal)) => {
                 let VPortal {
                     host: old_host,
                     next_sibling: old_sibling,
                     mut node,
+                    event_root,
                     ..
                 } = old_portal;
+                if let Some(event_root) = event_root {
+                    event_root.remove_target(&old_host);
+                }
                 if old_host != self.host {
                     // Remount the inner node somewhere else instead of diffing
                     node.detach(&old_host);
@@ -75,6 +89,7 @@ impl VPortal {
             next_sibling: NodeRef::default(),
             node: Box::new(content),
             sibling_ref: NodeRef::default(),
+            event_root: None,
         }
     }
     /// Creates a [VPortal] rendering `content` in the DOM hierarchy under `host`.
@@ -90,6 +105,7 @@ impl VPortal {
             },
             node: Box::new(content),
             sibling_ref: NodeRef::default(),
+            event_root: None,
         }
     }
     /// Returns the [Node] following this [VPortal], if this [VPortal]
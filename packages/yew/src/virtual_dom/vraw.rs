@@ -0,0 +1,410 @@
+//! This module contains the implementation of a raw HTML node `VRaw`.
+
+use super::{AttrValue, VDiff, VNode};
+use crate::html::{AnyScope, NodeRef};
+use gloo::console;
+use gloo_utils::document;
+use wasm_bindgen::JsCast;
+use web_sys::{Element, HtmlTemplateElement, Node};
+
+/// A node rendering a string of HTML as it is.
+///
+/// The HTML can contain any number of top-level nodes, which are all inserted in the place of the
+/// `VRaw`. It is parsed again only if the string changes.
+#[derive(Clone)]
+pub struct VRaw {
+    /// The HTML of the node
+    pub html: AttrValue,
+    /// Whether only allowed elements and attributes are kept from the HTML
+    sanitize: bool,
+    /// The top-level DOM nodes parsed from the HTML. Set when rendered
+    nodes: Vec<Node>,
+}
+
+impl VRaw {
+    /// Creates a node rendering `html` as it is.
+    pub fn new(html: AttrValue) -> Self {
+        Self {
+            html,
+            sanitize: false,
+            nodes: Vec::new(),
+        }
+    }
+
+    /// Creates a node rendering `html` with everything removed, that is not on the allowlist of
+    /// [`VNode::from_html_sanitized`].
+    #[cfg(feature = "sanitize")]
+    pub fn sanitized(html: AttrValue) -> Self {
+        Self {
+            html,
+            sanitize: true,
+            nodes: Vec::new(),
+        }
+    }
+
+    /// Returns the top-level DOM nodes, if the node has been rendered
+    pub(crate) fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    /// Parses the HTML into detached DOM nodes.
+    ///
+    /// An empty string is rendered as an empty text node, so that the node always has a position
+    /// in the DOM.
+    fn parse(&self) -> Vec<Node> {
+        // The content of a template is inert: scripts do not run and images are not loaded, until
+        // it is inserted into the document.
+        let template: HtmlTemplateElement = document()
+            .create_element("template")
+            .unwrap()
+            .unchecked_into();
+        template.set_inner_html(&self.html);
+        let content = template.content();
+        if self.sanitize {
+            sanitize(&content);
+        }
+
+        let mut nodes = Vec::new();
+        let mut next = content.first_child();
+        while let Some(node) = next {
+            next = node.next_sibling();
+            nodes.push(node);
+        }
+        if nodes.is_empty() {
+            nodes.push(document().create_text_node("").into());
+        }
+        nodes
+    }
+
+    pub(crate) fn move_before(&self, parent: &Element, next_sibling: &Option<Node>) {
+        for node in self.nodes.iter() {
+            super::insert_node(node, parent, next_sibling.as_ref());
+        }
+    }
+}
+
+impl std::fmt::Debug for VRaw {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "VRaw {{ html: \"{}\", sanitize: {}, nodes: {} }}",
+            self.html,
+            self.sanitize,
+            self.nodes.len()
+        )
+    }
+}
+
+impl VDiff for VRaw {
+    /// Remove the nodes of the VRaw from parent.
+    fn detach(&mut self, parent: &Element) {
+        for node in self.nodes.drain(..) {
+            if parent.remove_child(&node).is_err() {
+                console::warn!("Node not found to remove VRaw");
+            }
+        }
+    }
+
+    /// Renders the HTML, but only if it has changed.
+    fn apply(
+        &mut self,
+        _parent_scope: &AnyScope,
+        parent: &Element,
+        next_sibling: NodeRef,
+        ancestor: Option<VNode>,
+    ) -> NodeRef {
+        if let Some(mut ancestor) = ancestor {
+            if let VNode::VRaw(vraw) = &mut ancestor {
+                if *self == *vraw {
+                    self.nodes = std::mem::take(&mut vraw.nodes);
+                    return NodeRef::new(self.nodes[0].clone());
+                }
+            }
+            ancestor.detach(parent);
+        }
+
+        self.nodes = self.parse();
+        for node in self.nodes.iter() {
+            super::insert_node(node, parent, next_sibling.get().as_ref());
+        }
+        NodeRef::new(self.nodes[0].clone())
+    }
+}
+
+impl PartialEq for VRaw {
+    fn eq(&self, other: &VRaw) -> bool {
+        self.html == other.html && self.sanitize == other.sanitize
+    }
+}
+
+#[cfg(feature = "sanitize")]
+const HTML_NAMESPACE: &str = "http://www.w3.org/1999/xhtml";
+
+/// Elements, that are kept by the sanitizer
+#[cfg(feature = "sanitize")]
+const ALLOWED_TAGS: &[&str] = &[
+    "a",
+    "abbr",
+    "b",
+    "blockquote",
+    "br",
+    "caption",
+    "cite",
+    "code",
+    "col",
+    "colgroup",
+    "dd",
+    "del",
+    "details",
+    "dfn",
+    "div",
+    "dl",
+    "dt",
+    "em",
+    "figcaption",
+    "figure",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "i",
+    "img",
+    "ins",
+    "kbd",
+    "li",
+    "mark",
+    "ol",
+    "p",
+    "pre",
+    "q",
+    "s",
+    "samp",
+    "small",
+    "span",
+    "strong",
+    "sub",
+    "summary",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "time",
+    "tr",
+    "u",
+    "ul",
+];
+
+/// Elements, that are removed together with their content by the sanitizer. Other elements, that
+/// are not allowed, are replaced by their content.
+#[cfg(feature = "sanitize")]
+const DROPPED_TAGS: &[&str] = &[
+    "embed", "frame", "frameset", "iframe", "math", "noembed", "noframes", "noscript", "object",
+    "script", "select", "style", "svg", "template", "textarea", "title",
+];
+
+/// Attributes, that are kept by the sanitizer
+#[cfg(feature = "sanitize")]
+const ALLOWED_ATTRIBUTES: &[&str] = &[
+    "abbr", "alt", "cite", "class", "colspan", "datetime", "dir", "height", "href", "id", "lang",
+    "open", "reversed", "rowspan", "scope", "span", "src", "start", "title", "width",
+];
+
+/// Attributes holding a URL, which are only kept if the URL is relative or has an allowed scheme
+#[cfg(feature = "sanitize")]
+const URL_ATTRIBUTES: &[&str] = &["cite", "href", "src"];
+
+#[cfg(feature = "sanitize")]
+const ALLOWED_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+/// Removes every node from the descendants of `node`, that is not an allowed element or text, and
+/// every attribute, that is not allowed.
+#[cfg(feature = "sanitize")]
+fn sanitize(node: &Node) {
+    let mut next = node.first_child();
+    while let Some(child) = next {
+        next = child.next_sibling();
+        if child.node_type() == Node::TEXT_NODE {
+            continue;
+        }
+
+        if let Some(element) = child.dyn_ref::<Element>() {
+            let tag = element.local_name();
+            let html = element.namespace_uri().as_deref() == Some(HTML_NAMESPACE);
+            if html && is_allowed_tag(&tag) {
+                sanitize_attributes(element);
+                sanitize(&child);
+                continue;
+            }
+            if !DROPPED_TAGS.contains(&tag.as_str()) && child.has_child_nodes() {
+                // Move the content in front of the element and sanitize it on its own
+                next = child.first_child();
+                while let Some(grandchild) = child.first_child() {
+                    let _ = node.insert_before(&grandchild, Some(&child));
+                }
+            }
+        }
+        // Comments, processing instructions and elements, that are not allowed
+        let _ = node.remove_child(&child);
+    }
+}
+
+#[cfg(not(feature = "sanitize"))]
+fn sanitize(_: &Node) {}
+
+#[cfg(feature = "sanitize")]
+fn sanitize_attributes(element: &Element) {
+    for name in element.get_attribute_names().iter() {
+        let name = match name.as_string() {
+            Some(name) => name,
+            None => continue,
+        };
+        let value = element.get_attribute(&name).unwrap_or_default();
+        if !is_allowed_attribute(&name, &value) {
+            let _ = element.remove_attribute(&name);
+        }
+    }
+}
+
+#[cfg(feature = "sanitize")]
+fn is_allowed_tag(tag: &str) -> bool {
+    ALLOWED_TAGS.contains(&tag)
+}
+
+#[cfg(feature = "sanitize")]
+fn is_allowed_attribute(name: &str, value: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    if !ALLOWED_ATTRIBUTES.contains(&name.as_str()) {
+        return false;
+    }
+    !URL_ATTRIBUTES.contains(&name.as_str()) || is_allowed_url(value)
+}
+
+/// Check a URL is relative or has an allowed scheme
+#[cfg(feature = "sanitize")]
+fn is_allowed_url(url: &str) -> bool {
+    // Browsers ignore whitespace and control characters inside of the scheme
+    let url: String = url
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace() && !c.is_control())
+        .collect();
+    match url.find(&[':', '/', '?', '#'][..]) {
+        Some(end) if url[end..].starts_with(':') => ALLOWED_SCHEMES
+            .iter()
+            .any(|scheme| url[..end].eq_ignore_ascii_case(scheme)),
+        _ => true,
+    }
+}
+
+#[cfg(all(test, feature = "sanitize"))]
+mod tests {
+    use super::{is_allowed_attribute, is_allowed_tag};
+
+    #[test]
+    fn allowed_tags() {
+        assert!(is_allowed_tag("p"));
+        assert!(is_allowed_tag("a"));
+        assert!(!is_allowed_tag("script"));
+        assert!(!is_allowed_tag("iframe"));
+        assert!(!is_allowed_tag("svg"));
+    }
+
+    #[test]
+    fn allowed_attributes() {
+        assert!(is_allowed_attribute("class", "a"));
+        assert!(is_allowed_attribute("href", "https://yew.rs"));
+        assert!(is_allowed_attribute("href", "/docs?page=1#top"));
+        assert!(is_allowed_attribute("href", "docs/a:b"));
+        assert!(is_allowed_attribute("HREF", "mailto:a@b.c"));
+        assert!(!is_allowed_attribute("onclick", "alert(1)"));
+        assert!(!is_allowed_attribute("OnLoad", ""));
+        assert!(!is_allowed_attribute("style", "color: red"));
+        assert!(!is_allowed_attribute("href", "javascript:alert(1)"));
+        assert!(!is_allowed_attribute("href", " Java\tScript:alert(1)"));
+        assert!(!is_allowed_attribute("src", "data:text/html,<script>"));
+        assert!(!is_allowed_attribute("cite", "vbscript:msgbox"));
+    }
+}
+
+#[cfg(test)]
+mod layout_tests {
+    extern crate self as yew;
+
+    use crate::html;
+    use crate::html::Html;
+    use crate::virtual_dom::layout_tests::{diff_layouts, TestLayout};
+
+    #[cfg(feature = "wasm_test")]
+    use wasm_bindgen_test::{wasm_bindgen_test as test, wasm_bindgen_test_configure};
+
+    #[cfg(feature = "wasm_test")]
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[test]
+    fn diff() {
+        let layout1 = TestLayout {
+            name: "1",
+            node: Html::from_html_unchecked("<b>a</b>".into()),
+            expected: "<b>a</b>",
+        };
+
+        let layout2 = TestLayout {
+            name: "2",
+            node: html! {
+                <>
+                    {"a"}
+                    { Html::from_html_unchecked("<i>b</i>c<p>d</p>".into()) }
+                    {"e"}
+                </>
+            },
+            expected: "a<i>b</i>c<p>d</p>e",
+        };
+
+        let layout3 = TestLayout {
+            name: "3",
+            node: html! {
+                <>
+                    { Html::from_html_unchecked("".into()) }
+                    {"a"}
+                </>
+            },
+            expected: "a",
+        };
+
+        let layout4 = TestLayout {
+            name: "4",
+            node: html! {
+                <div>
+                    { Html::from_html_unchecked("<span>x</span>y".into()) }
+                </div>
+            },
+            expected: "<div><span>x</span>y</div>",
+        };
+
+        diff_layouts(vec![layout1, layout2, layout3, layout4]);
+    }
+
+    #[cfg(feature = "sanitize")]
+    #[test]
+    fn sanitized() {
+        let layout = TestLayout {
+            name: "1",
+            node: Html::from_html_sanitized(
+                "<p onclick=\"alert(1)\" class=\"a\">a<script>alert(2)</script></p>\
+                 <a href=\"javascript:alert(3)\" title=\"b\">b</a>\
+                 <img src=\"x\" onerror=\"alert(4)\">\
+                 <custom-tag><b>c</b><!-- d --></custom-tag>\
+                 <svg><script>alert(5)</script></svg>"
+                    .into(),
+            ),
+            expected: "<p class=\"a\">a</p><a title=\"b\">b</a><img src=\"x\"><b>c</b>",
+        };
+
+        diff_layouts(vec![layout]);
+    }
+}
@@ -1,6 +1,9 @@
 //! This module contains the implementation of a virtual element node [VTag].
 
-use super::{Apply, AttrValue, Attributes, Key, Listener, Listeners, VDiff, VList, VNode};
+use super::listeners::EventRoot;
+use super::{
+    template, Apply, AttrValue, Attributes, Key, Listener, Listeners, VDiff, VList, VNode,
+};
 use crate::html::{AnyScope, IntoPropValue, NodeRef};
 use gloo::console;
 use gloo_utils::document;
@@ -12,8 +15,11 @@ use std::mem::MaybeUninit;
 use std::ops::Deref;
 use std::rc::Rc;
 use std::sync::Once;
-use wasm_bindgen::JsCast;
-use web_sys::{Element, HtmlInputElement as InputElement, HtmlTextAreaElement as TextAreaElement};
+use std::thread::LocalKey;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{
+    Document, Element, HtmlInputElement as InputElement, HtmlTextAreaElement as TextAreaElement,
+};
 
 /// SVG namespace string used for creating svg elements
 pub const SVG_NAMESPACE: &str = "http://www.w3.org/2000/svg";
@@ -116,6 +122,47 @@ impl Apply for InputFields {
     }
 }
 
+/// DOM properties of an [Element], which are assigned to the element object instead of being set
+/// as attributes.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct DomProperties(Vec<(&'static str, JsValue)>);
+
+impl Apply for DomProperties {
+    type Element = Element;
+
+    fn apply(&mut self, el: &Self::Element) {
+        for (key, value) in self.0.iter() {
+            Self::set(el, key, value);
+        }
+    }
+
+    fn apply_diff(&mut self, el: &Self::Element, ancestor: Self) {
+        for (key, value) in self.0.iter() {
+            // Compare with the value of the element, as it might have been changed from elsewhere,
+            // like `indeterminate` by a click
+            let current = js_sys::Reflect::get(el, &JsValue::from_str(key));
+            if current.as_ref() != Ok(value) {
+                Self::set(el, key, value);
+            }
+        }
+        for (key, _) in ancestor.0.iter() {
+            if !self.0.iter().any(|(k, _)| k == key) {
+                Self::set(el, key, &JsValue::UNDEFINED);
+            }
+        }
+    }
+}
+
+impl DomProperties {
+    /// Sets a property of the element. Setters can throw, like the one of `valueAsNumber` for
+    /// inputs of a wrong type, which is logged instead of aborting the render.
+    fn set(el: &Element, key: &str, value: &JsValue) {
+        if let Err(error) = js_sys::Reflect::set(el, &JsValue::from_str(key), value) {
+            console::error!(format!("could not set DOM property `{}`", key), error);
+        }
+    }
+}
+
 /// [VTag] fields that are specific to different [VTag] kinds.
 /// Decreases the memory footprint of [VTag] by avoiding impossible field and value combinations.
 #[derive(Debug, Clone)]
@@ -164,7 +211,14 @@ pub struct VTag {
     /// List of attributes.
     pub attributes: Attributes,
 
+    /// DOM properties assigned to the element.
+    properties: DomProperties,
+
     pub key: Option<Key>,
+
+    /// Where the subtree was built, if the `html!` macro hoisted it out of the render, because it
+    /// only contains literals
+    hoisted: Option<&'static LocalKey<VNode>>,
 }
 
 impl Clone for VTag {
@@ -174,8 +228,10 @@ impl Clone for VTag {
             reference: None,
             listeners: self.listeners.clone(),
             attributes: self.attributes.clone(),
+            properties: self.properties.clone(),
             node_ref: self.node_ref.clone(),
             key: self.key.clone(),
+            hoisted: self.hoisted,
         }
     }
 }
@@ -290,6 +346,17 @@ impl VTag {
         )
     }
 
+    /// Clones an element subtree the `html!` macro hoisted out of a render, because it only
+    /// contains literals. Subtrees from the same call site are never diffed against each other.
+    #[doc(hidden)]
+    pub fn __new_static(key: &'static LocalKey<VNode>) -> VNode {
+        let mut node = key.with(Clone::clone);
+        if let VNode::VTag(tag) = &mut node {
+            tag.hoisted = Some(key);
+        }
+        node
+    }
+
     /// Constructs a [VTag] from [VTagInner] and fields common to all [VTag] kinds
     #[inline]
     #[allow(clippy::too_many_arguments)]
@@ -304,9 +371,11 @@ impl VTag {
             inner,
             reference: None,
             attributes,
+            properties: Default::default(),
             listeners,
             node_ref,
             key,
+            hoisted: None,
         }
     }
 
@@ -435,12 +504,46 @@ impl VTag {
             .insert(key, value.into_prop_value());
     }
 
+    /// Sets a DOM property of the [Element] to `value`.
+    ///
+    /// Unlike attributes, DOM properties can be any JS value. They are needed for properties
+    /// without a corresponding attribute, like `indeterminate` or `srcObject`, and for passing
+    /// objects to custom elements. Properties, that are no longer set, are reset to `undefined`.
+    ///
+    /// In the `html!` macro, DOM properties are set by prefixing their name with `~`:
+    /// `<input type="checkbox" ~indeterminate={true} />`.
+    pub fn set_property(&mut self, key: &'static str, value: impl Into<JsValue>) {
+        let value = value.into();
+        match self.properties.0.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, v)) => *v = value,
+            None => self.properties.0.push((key, value)),
+        }
+    }
+
+    /// Returns the value of a DOM property set with [VTag::set_property]
+    pub fn property(&self, key: &str) -> Option<&JsValue> {
+        self.properties
+            .0
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, v)| v)
+    }
+
     /// Set event listeners on the [VTag]'s  [Element]
     pub fn set_listener(&mut self, listeners: Box<[Option<Rc<dyn Listener>>]>) {
         self.listeners = Listeners::Pending(listeners);
     }
 
-    fn create_element(&self, parent: &Element) -> Element {
+    /// Creates the [Element] of this [VTag]. Returns true, if the element was cloned from a
+    /// template and already contains the static parts of the subtree.
+    fn create_element(&self, parent: &Element) -> (Element, bool) {
+        match template::clone_element(self, parent) {
+            Some(el) => (el, true),
+            None => (self.create_element_in(&document(), parent), false),
+        }
+    }
+
+    pub(super) fn create_element_in(&self, document: &Document, parent: &Element) -> Element {
         let tag = self.tag();
         if tag == "svg"
             || parent
@@ -448,15 +551,56 @@ impl VTag {
                 .map_or(false, |ns| ns == SVG_NAMESPACE)
         {
             let namespace = Some(SVG_NAMESPACE);
-            document()
+            document
                 .create_element_ns(namespace, tag)
                 .expect("can't create namespaced element for vtag")
         } else {
-            document()
+            document
                 .create_element(tag)
                 .expect("can't create element for vtag")
         }
     }
+
+    /// Returns the call site, if the `html!` macro hoisted this subtree out of the render
+    pub(super) fn hoisted(&self) -> Option<&'static LocalKey<VNode>> {
+        self.hoisted
+    }
+
+    /// Returns true, if both subtrees were hoisted from the same call site and `other` is mounted
+    fn is_hoisted_like(&self, other: &VTag) -> bool {
+        match (self.hoisted, other.hoisted) {
+            (Some(a), Some(b)) => {
+                std::ptr::eq(a, b) && self.key == other.key && other.reference.is_some()
+            }
+            _ => false,
+        }
+    }
+
+    /// Links an [Element] cloned from a template to this [VTag] and applies everything the
+    /// template does not contain.
+    pub(super) fn hydrate(&mut self, el: Element, event_root: EventRoot) {
+        // Static attributes are part of the template
+        if !matches!(self.attributes, Attributes::Static(_)) {
+            self.attributes.apply(&el);
+        }
+        self.properties.apply(&el);
+        self.listeners.apply(&el, event_root);
+
+        match &mut self.inner {
+            VTagInner::Input(f) => {
+                f.apply(el.unchecked_ref());
+            }
+            VTagInner::Textarea { value } => {
+                value.apply(el.unchecked_ref());
+            }
+            VTagInner::Other { children, .. } => {
+                template::hydrate(&el, children, event_root);
+            }
+        }
+
+        self.node_ref.set(Some(el.deref().clone()));
+        self.reference = el.into();
+    }
 }
 
 impl VDiff for VTag {
@@ -488,10 +632,23 @@ impl VDiff for VTag {
         next_sibling: NodeRef,
         ancestor: Option<VNode>,
     ) -> NodeRef {
+        // Subtrees hoisted from the same call site are identical, so the mounted one is kept as is
+        let ancestor = match ancestor {
+            Some(VNode::VTag(a)) if self.is_hoisted_like(&a) => {
+                let a = *a;
+                let el = a.reference.expect("hoisted VTag is not mounted");
+                self.inner = a.inner;
+                self.node_ref.set(Some(el.deref().clone()));
+                self.reference = Some(el);
+                return self.node_ref.clone();
+            }
+            ancestor => ancestor,
+        };
+
         // This kind of branching patching routine reduces branch predictor misses and the need to
         // unpack the enums (including `Option`s) all the time, resulting in a more streamlined
         // patching flow
-        let (ancestor_tag, el) = match ancestor {
+        let (ancestor_tag, el, cloned) = match ancestor {
             Some(mut ancestor) => {
                 // If the ancestor is a tag of the same type, don't recreate, keep the
                 // old tag and update its attributes and children.
@@ -514,29 +671,39 @@ impl VDiff for VTag {
                         VNode::VTag(mut a) => {
                             // Preserve the reference that already exists
                             let el = a.reference.take().unwrap();
-                            a.node_ref.set(None);
-                            (Some(a), el)
+                            if a.node_ref.has_same_callback(&self.node_ref) {
+                                // Don't notify the callback of the unchanged element
+                                self.node_ref.reuse(a.node_ref.clone());
+                            } else {
+                                a.node_ref.set(None);
+                            }
+                            (Some(a), el, false)
                         }
                         _ => unsafe { unreachable_unchecked() },
                     }
                 } else {
-                    let el = self.create_element(parent);
+                    let (el, cloned) = self.create_element(parent);
                     super::insert_node(&el, parent, ancestor.first_node().as_ref());
                     ancestor.detach(parent);
-                    (None, el)
+                    (None, el, cloned)
                 }
             }
-            None => (None, {
-                let el = self.create_element(parent);
+            None => {
+                let (el, cloned) = self.create_element(parent);
                 super::insert_node(&el, parent, next_sibling.get().as_ref());
-                el
-            }),
+                (None, el, cloned)
+            }
         };
 
         match ancestor_tag {
+            None if cloned => {
+                self.hydrate(el, parent_scope.event_root);
+                return self.node_ref.clone();
+            }
             None => {
                 self.attributes.apply(&el);
-                self.listeners.apply(&el);
+                self.properties.apply(&el);
+                self.listeners.apply(&el, parent_scope.event_root);
 
                 match &mut self.inner {
                     VTagInner::Input(f) => {
@@ -554,7 +721,9 @@ impl VDiff for VTag {
             }
             Some(ancestor) => {
                 self.attributes.apply_diff(&el, ancestor.attributes);
-                self.listeners.apply_diff(&el, ancestor.listeners);
+                self.properties.apply_diff(&el, ancestor.properties);
+                self.listeners
+                    .apply_diff(&el, parent_scope.event_root, ancestor.listeners);
 
                 match (&mut self.inner, ancestor.inner) {
                     (VTagInner::Input(new), VTagInner::Input(old)) => {
@@ -601,6 +770,7 @@ impl PartialEq for VTag {
             _ => false,
         }) && self.listeners.eq(&other.listeners)
             && self.attributes == other.attributes
+            && self.properties == other.properties
             // Diff children last, as recursion is the most expensive
             && match (&self.inner, &other.inner) {
                 (Other { children: ch_l, .. }, Other { children: ch_r, .. }) => ch_l == ch_r,
@@ -612,7 +782,8 @@ impl PartialEq for VTag {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{html, Html};
+    use crate::{html, Callback, Html};
+    use std::cell::RefCell;
 
     #[cfg(feature = "wasm_test")]
     use wasm_bindgen_test::{wasm_bindgen_test as test, wasm_bindgen_test_configure};
@@ -1130,6 +1301,125 @@ mod tests {
             "node_ref_a should have been reset when the element was reused."
         );
     }
+
+    #[test]
+    fn callback_ref() {
+        let scope = test_scope();
+        let parent = document().create_element("div").unwrap();
+        document().body().unwrap().append_child(&parent).unwrap();
+
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let onref = {
+            let calls = calls.clone();
+            Callback::from(move |el: Option<Element>| calls.borrow_mut().push(el))
+        };
+
+        let mut elem_a = html! { <div ref={onref.clone()} /> };
+        elem_a.apply(&scope, &parent, NodeRef::default(), None);
+        let el = parent.first_element_child();
+        assert_eq!(*calls.borrow(), vec![el.clone()]);
+
+        // The same callback is not notified of the reused element
+        let mut elem_b = html! { <div ref={onref} /> };
+        elem_b.apply(&scope, &parent, NodeRef::default(), Some(elem_a));
+        assert_eq!(*calls.borrow(), vec![el.clone()]);
+
+        elem_b.detach(&parent);
+        assert_eq!(*calls.borrow(), vec![el, None]);
+    }
+
+    #[test]
+    fn hoists_static_subtrees() {
+        let render = |n: u32| html! { <div><p class="a">{"static"}</p>{ n }</div> };
+        let static_child = |node: &VNode| match &assert_vtag(node).children()[0] {
+            VNode::VTag(tag) => tag.hoisted,
+            other => panic!("expected tag, got {:?}", other),
+        };
+
+        let (a, b) = (render(1), render(2));
+        assert!(static_child(&a).is_some());
+        assert!(std::ptr::eq(
+            static_child(&a).unwrap(),
+            static_child(&b).unwrap()
+        ));
+        assert_ne!(a, b);
+        assert_eq!(
+            render(1),
+            html! { <div>{ html! { <p class="a">{"static"}</p> } }{ 1 }</div> }
+        );
+    }
+
+    #[test]
+    fn reuses_hoisted_subtrees() {
+        let scope = test_scope();
+        let parent = document().create_element("div").unwrap();
+        let render = || html! { <p class="a"><b>{"static"}</b></p> };
+
+        let mut elem_a = render();
+        elem_a.apply(&scope, &parent, NodeRef::default(), None);
+        let el = assert_vtag(&elem_a).reference().cloned();
+        let child = assert_vtag(&elem_a).children()[0].first_node();
+
+        let mut elem_b = render();
+        elem_b.apply(&scope, &parent, NodeRef::default(), Some(elem_a));
+        assert_eq!(assert_vtag(&elem_b).reference().cloned(), el);
+        assert_eq!(assert_vtag(&elem_b).children()[0].first_node(), child);
+        assert_eq!(parent.inner_html(), r#"<p class="a"><b>static</b></p>"#);
+    }
+
+    #[test]
+    fn dom_properties() {
+        use js_sys::Reflect;
+
+        let scope = test_scope();
+        let parent = document().create_element("div").unwrap();
+        document().body().unwrap().append_child(&parent).unwrap();
+        let get = |el: &Element, key: &str| Reflect::get(el, &JsValue::from_str(key)).unwrap();
+
+        let mut elem_a = html! { <input type="checkbox" ~indeterminate={true} ~data={42} /> };
+        elem_a.apply(&scope, &parent, NodeRef::default(), None);
+        let el = parent.first_element_child().unwrap();
+        assert_eq!(get(&el, "indeterminate"), JsValue::TRUE);
+        assert_eq!(get(&el, "data"), JsValue::from(42));
+        assert!(!el.has_attribute("indeterminate"));
+
+        // Properties changed from elsewhere are restored and removed properties are reset
+        Reflect::set(&el, &JsValue::from_str("indeterminate"), &JsValue::FALSE).unwrap();
+        let mut elem_b = html! { <input type="checkbox" ~indeterminate={true} /> };
+        elem_b.apply(&scope, &parent, NodeRef::default(), Some(elem_a));
+        assert_eq!(get(&el, "indeterminate"), JsValue::TRUE);
+        assert_eq!(get(&el, "data"), JsValue::UNDEFINED);
+    }
+
+    #[test]
+    fn style_properties_diffed() {
+        use web_sys::HtmlElement;
+
+        let scope = test_scope();
+        let parent = document().create_element("div").unwrap();
+        document().body().unwrap().append_child(&parent).unwrap();
+
+        let style = |color: &'static str| {
+            html! { <div style={crate::style!("color" => color, "width" => "1px")} /> }
+        };
+
+        let mut elem_a = style("red");
+        elem_a.apply(&scope, &parent, NodeRef::default(), None);
+        let el: HtmlElement = parent.first_element_child().unwrap().dyn_into().unwrap();
+        el.style().set_property("opacity", "0.5").unwrap();
+
+        // Properties set from elsewhere are kept
+        let mut elem_b = style("blue");
+        elem_b.apply(&scope, &parent, NodeRef::default(), Some(elem_a));
+        assert_eq!(el.style().get_property_value("color").unwrap(), "blue");
+        assert_eq!(el.style().get_property_value("width").unwrap(), "1px");
+        assert_eq!(el.style().get_property_value("opacity").unwrap(), "0.5");
+
+        // Removing a property rewrites the attribute
+        let mut elem_c = html! { <div style={crate::style!("width" => "1px")} /> };
+        elem_c.apply(&scope, &parent, NodeRef::default(), Some(elem_b));
+        assert_eq!(el.get_attribute("style").as_deref(), Some("width: 1px;"));
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,453 @@
+//! This module defines the `VirtualList` component.
+
+use crate::html::{Classes, NodeRef};
+use crate::virtual_dom::{AttrValue, Key};
+use crate::{html, Component, Context, Html, Properties};
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::Range;
+use std::rc::Rc;
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::{Element, Event, HtmlElement, ResizeObserver};
+
+/// Renders the item at an index of a [`VirtualList`].
+///
+/// Rendered items should not have keys of their own. The list keys rows by their index.
+#[derive(Clone)]
+pub struct ItemRenderer(Rc<dyn Fn(usize) -> Html>);
+
+impl ItemRenderer {
+    /// Render the item at `index`
+    pub fn render(&self, index: usize) -> Html {
+        (self.0)(index)
+    }
+}
+
+impl<F: Fn(usize) -> Html + 'static> From<F> for ItemRenderer {
+    fn from(func: F) -> Self {
+        Self(Rc::new(func))
+    }
+}
+
+#[allow(ambiguous_wide_pointer_comparisons)]
+impl PartialEq for ItemRenderer {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl fmt::Debug for ItemRenderer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ItemRenderer<_>")
+    }
+}
+
+/// Height of the items of a [`VirtualList`] in pixels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ItemHeight {
+    /// All items have the same fixed height
+    Fixed(f64),
+
+    /// Items can have differing heights, which are measured after they have been rendered.
+    /// `estimate` is used for items, that have not been measured yet.
+    Dynamic {
+        /// Estimated height of items, that have not been measured yet
+        estimate: f64,
+    },
+}
+
+/// Props for [`VirtualList`]
+#[derive(Debug, Clone, PartialEq, Properties)]
+pub struct VirtualListProps {
+    /// Total number of items in the list
+    pub item_count: usize,
+    /// Renders the item at an index
+    pub render_item: ItemRenderer,
+    /// Height of the items
+    pub item_height: ItemHeight,
+    /// CSS height of the scrolling viewport. For example `"400px"`.
+    pub height: AttrValue,
+    /// Number of items to render beyond either edge of the viewport
+    #[prop_or(3)]
+    pub overscan: usize,
+    /// Index of an item to scroll to, when this changes
+    #[prop_or_default]
+    pub scroll_to: Option<usize>,
+    /// Classes of the scrolling viewport
+    #[prop_or_default]
+    pub class: Classes,
+}
+
+/// Messages of the [`VirtualList`] component
+#[derive(Debug)]
+pub enum VirtualListMsg {
+    /// The viewport has been scrolled
+    Scrolled,
+    /// The viewport has been resized
+    Resized,
+    /// Scroll the item at the given index to the top of the viewport
+    ScrollTo(usize),
+    /// Item heights or the viewport have been measured
+    Measured,
+}
+
+/// Offsets of the items from the start of the list
+#[derive(Debug)]
+enum Offsets {
+    /// Items of the same height
+    Fixed { height: f64, count: usize },
+
+    /// Offset of each item. Contains an additional entry for the end of the list.
+    Dynamic(Vec<f64>),
+}
+
+impl Offsets {
+    fn new(height: ItemHeight, count: usize, measured: &[Option<f64>]) -> Self {
+        match height {
+            ItemHeight::Fixed(height) => Self::Fixed { height, count },
+            ItemHeight::Dynamic { estimate } => {
+                let mut offsets = Vec::with_capacity(count + 1);
+                let mut offset = 0.;
+                offsets.push(offset);
+                for i in 0..count {
+                    offset += measured.get(i).copied().flatten().unwrap_or(estimate);
+                    offsets.push(offset);
+                }
+                Self::Dynamic(offsets)
+            }
+        }
+    }
+
+    fn count(&self) -> usize {
+        match self {
+            Self::Fixed { count, .. } => *count,
+            Self::Dynamic(offsets) => offsets.len() - 1,
+        }
+    }
+
+    /// Offset of the item at `index`. Indices past the end return the total height of the list.
+    fn offset(&self, index: usize) -> f64 {
+        let index = std::cmp::min(index, self.count());
+        match self {
+            Self::Fixed { height, .. } => index as f64 * height,
+            Self::Dynamic(offsets) => offsets[index],
+        }
+    }
+
+    /// Total height of the list
+    fn total(&self) -> f64 {
+        self.offset(self.count())
+    }
+
+    /// Index of the item at offset `y`
+    fn index_at(&self, y: f64) -> usize {
+        let last = self.count().saturating_sub(1);
+        if y <= 0. {
+            return 0;
+        }
+        let index = match self {
+            Self::Fixed { height, .. } if *height > 0. => (y / height) as usize,
+            Self::Fixed { .. } => 0,
+            Self::Dynamic(offsets) => {
+                match offsets.binary_search_by(|o| o.partial_cmp(&y).unwrap_or(Ordering::Less)) {
+                    Ok(i) => i,
+                    Err(i) => i - 1,
+                }
+            }
+        };
+        std::cmp::min(index, last)
+    }
+
+    /// Range of items visible in a viewport of `height` scrolled to `scroll_top` with `overscan`
+    /// additional items on either side
+    fn visible(&self, scroll_top: f64, height: f64, overscan: usize) -> Range<usize> {
+        if self.count() == 0 {
+            return 0..0;
+        }
+        let start = self.index_at(scroll_top).saturating_sub(overscan);
+        let end = self.index_at(scroll_top + height) + 1 + overscan;
+        start..std::cmp::min(end, self.count())
+    }
+}
+
+/// A list component, that only renders the items visible in its scrolling viewport.
+///
+/// Rendering a [`VNode`](crate::virtual_dom::VNode) for every item of very large collections is
+/// slow. `VirtualList` renders only the items in the viewport plus a number of `overscan` items on
+/// either side and reuses rendered rows through keyed diffing, when the viewport is scrolled.
+///
+/// # Example
+///
+/// ```
+/// # use yew::prelude::*;
+/// use yew::virtual_list::{ItemHeight, ItemRenderer, VirtualList};
+///
+/// # fn test() -> Html {
+/// html! {
+///     <VirtualList
+///         item_count={100_000}
+///         item_height={ItemHeight::Fixed(24.)}
+///         height="400px"
+///         render_item={ItemRenderer::from(|i| html! { <p>{ format!("Row {}", i) }</p> })}
+///     />
+/// }
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct VirtualList {
+    offsets: Offsets,
+    /// Measured heights of items with [`ItemHeight::Dynamic`] heights
+    measured: Vec<Option<f64>>,
+    viewport_ref: NodeRef,
+    scroll_top: f64,
+    viewport_height: f64,
+    /// Last value of the `scroll_to` prop
+    scroll_to: Option<usize>,
+    /// Item to scroll to after the next render
+    pending_scroll: Option<usize>,
+    /// References to the rendered rows by item index
+    rows: RefCell<Vec<(usize, NodeRef)>>,
+    /// Observes resizes of the viewport, while it is mounted
+    resize_observer: Option<(ResizeObserver, Closure<dyn Fn()>)>,
+}
+
+impl VirtualList {
+    fn viewport(&self) -> Option<Element> {
+        self.viewport_ref.cast::<Element>()
+    }
+
+    /// Scroll the viewport, so the item at `index` is at its top. Returns, if the viewport is
+    /// mounted.
+    fn scroll_to(&mut self, index: usize) -> bool {
+        match self.viewport() {
+            Some(viewport) => {
+                self.scroll_top = self.offsets.offset(index);
+                viewport.set_scroll_top(self.scroll_top as i32);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Observe the viewport, so it is measured again, when it or the page is resized
+    fn observe_resize(&mut self, ctx: &Context<Self>) {
+        let viewport = match self.viewport() {
+            Some(viewport) => viewport,
+            None => return,
+        };
+        let link = ctx.link().clone();
+        let closure = Closure::wrap(
+            Box::new(move || link.send_message(VirtualListMsg::Resized)) as Box<dyn Fn()>
+        );
+        let observer = ResizeObserver::new(closure.as_ref().unchecked_ref())
+            .expect("failed to create resize observer");
+        observer.observe(&viewport);
+        self.resize_observer = Some((observer, closure));
+    }
+
+    /// Update the stored viewport dimensions. Returns, if they have changed.
+    fn measure_viewport(&mut self) -> bool {
+        match self.viewport() {
+            Some(viewport) => {
+                let scroll_top = viewport.scroll_top() as f64;
+                let height = viewport.client_height() as f64;
+                let changed = scroll_top != self.scroll_top || height != self.viewport_height;
+                self.scroll_top = scroll_top;
+                self.viewport_height = height;
+                changed
+            }
+            None => false,
+        }
+    }
+
+    /// Measure the heights of rendered rows. Returns, if any heights have changed.
+    fn measure_rows(&mut self, ctx: &Context<Self>) -> bool {
+        if let ItemHeight::Fixed(_) = ctx.props().item_height {
+            return false;
+        }
+
+        let mut changed = false;
+        for (index, row) in self.rows.borrow().iter() {
+            if let (Some(el), Some(measured)) =
+                (row.cast::<HtmlElement>(), self.measured.get_mut(*index))
+            {
+                let height = Some(el.offset_height() as f64);
+                if *measured != height {
+                    *measured = height;
+                    changed = true;
+                }
+            }
+        }
+        if changed {
+            self.update_offsets(ctx.props());
+        }
+        changed
+    }
+
+    fn update_offsets(&mut self, props: &VirtualListProps) {
+        match props.item_height {
+            ItemHeight::Fixed(_) => self.measured.clear(),
+            ItemHeight::Dynamic { .. } => self.measured.resize(props.item_count, None),
+        }
+        self.offsets = Offsets::new(props.item_height, props.item_count, &self.measured);
+    }
+}
+
+impl Component for VirtualList {
+    type Message = VirtualListMsg;
+    type Properties = VirtualListProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let props = ctx.props();
+        let mut list = Self {
+            offsets: Offsets::Fixed {
+                height: 0.,
+                count: 0,
+            },
+            measured: Vec::new(),
+            viewport_ref: NodeRef::default(),
+            scroll_top: 0.,
+            viewport_height: 0.,
+            scroll_to: props.scroll_to,
+            pending_scroll: props.scroll_to,
+            rows: Default::default(),
+            resize_observer: None,
+        };
+        list.update_offsets(props);
+        list
+    }
+
+    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            VirtualListMsg::Scrolled => self.measure_viewport(),
+            // Rows may wrap differently at another width, so they are measured again as well
+            VirtualListMsg::Resized => {
+                self.measure_viewport();
+                true
+            }
+            VirtualListMsg::ScrollTo(index) => {
+                if !self.scroll_to(index) {
+                    self.pending_scroll = Some(index);
+                }
+                true
+            }
+            VirtualListMsg::Measured => true,
+        }
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>) -> bool {
+        let props = ctx.props();
+        self.update_offsets(props);
+        if props.scroll_to != self.scroll_to {
+            self.scroll_to = props.scroll_to;
+            if let Some(index) = props.scroll_to {
+                if !self.scroll_to(index) {
+                    self.pending_scroll = Some(index);
+                }
+            }
+        }
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let visible = self
+            .offsets
+            .visible(self.scroll_top, self.viewport_height, props.overscan);
+        let top = self.offsets.offset(visible.start);
+
+        let mut rows = self.rows.borrow_mut();
+        rows.clear();
+        let items = visible.map(|index| {
+            let row_ref = NodeRef::default();
+            rows.push((index, row_ref.clone()));
+            html! {
+                <div key={Key::from(index)} ref={row_ref}>
+                    { props.render_item.render(index) }
+                </div>
+            }
+        });
+
+        let onscroll = ctx.link().callback(|_: Event| VirtualListMsg::Scrolled);
+        html! {
+            <div
+                class={props.class.clone()}
+                style={format!("overflow-y: auto; position: relative; height: {};", props.height)}
+                ref={self.viewport_ref.clone()}
+                {onscroll}
+            >
+                <div style={format!("position: relative; height: {}px;", self.offsets.total())}>
+                    <div style={format!("position: absolute; top: {}px; left: 0; right: 0;", top)}>
+                        { for items }
+                    </div>
+                </div>
+            </div>
+        }
+    }
+
+    fn rendered(&mut self, ctx: &Context<Self>, first_render: bool) {
+        let mut rerender = false;
+        if first_render {
+            rerender |= self.measure_viewport();
+            self.observe_resize(ctx);
+        }
+        if let Some(index) = self.pending_scroll.take() {
+            rerender |= self.scroll_to(index);
+        }
+        if self.measure_rows(ctx) || rerender {
+            ctx.link().send_message(VirtualListMsg::Measured);
+        }
+    }
+
+    fn destroy(&mut self, _ctx: &Context<Self>) {
+        if let Some((observer, _)) = self.resize_observer.take() {
+            observer.disconnect();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_offsets() {
+        let offsets = Offsets::new(ItemHeight::Fixed(10.), 100, &[]);
+        assert_eq!(offsets.total(), 1000.);
+        assert_eq!(offsets.offset(5), 50.);
+        assert_eq!(offsets.index_at(55.), 5);
+        assert_eq!(offsets.index_at(5000.), 99);
+        assert_eq!(offsets.visible(0., 30., 0), 0..4);
+        assert_eq!(offsets.visible(100., 30., 2), 8..16);
+        assert_eq!(offsets.visible(990., 30., 2), 97..100);
+    }
+
+    #[test]
+    fn dynamic_offsets() {
+        let offsets = Offsets::new(
+            ItemHeight::Dynamic { estimate: 10. },
+            5,
+            &[Some(20.), None, Some(5.), None, None],
+        );
+        assert_eq!(offsets.total(), 55.);
+        assert_eq!(offsets.offset(2), 30.);
+        assert_eq!(offsets.offset(3), 35.);
+        assert_eq!(offsets.index_at(19.), 0);
+        assert_eq!(offsets.index_at(20.), 1);
+        assert_eq!(offsets.index_at(32.), 2);
+        assert_eq!(offsets.visible(20., 15., 0), 1..4);
+        assert_eq!(offsets.visible(20., 15., 1), 0..5);
+    }
+
+    #[test]
+    fn empty_offsets() {
+        let fixed = Offsets::new(ItemHeight::Fixed(10.), 0, &[]);
+        assert_eq!(fixed.visible(0., 100., 3), 0..0);
+        assert_eq!(fixed.total(), 0.);
+
+        let dynamic = Offsets::new(ItemHeight::Dynamic { estimate: 10. }, 0, &[]);
+        assert_eq!(dynamic.visible(0., 100., 3), 0..0);
+        assert_eq!(dynamic.total(), 0.);
+    }
+}
@@ -0,0 +1,500 @@
+//! This module contains the registration of components as
+//! [custom elements](https://developer.mozilla.org/en-US/docs/Web/Web_Components/Using_custom_elements).
+//!
+//! A registered component is mounted into the shadow root of every connected element with the
+//! registered tag name, so it can be used from plain HTML or any other framework. The properties of
+//! the component are mapped from the attributes and JS properties of the element with
+//! [CustomElementProperties].
+//!
+//! ```
+//! # use yew::prelude::*;
+//! use yew::web_component::{CustomElementHost, CustomElementProperties};
+//!
+//! #[derive(Clone, PartialEq, Properties)]
+//! pub struct CounterProps {
+//!     pub label: String,
+//!     pub onincrement: Callback<u32>,
+//! }
+//!
+//! impl CustomElementProperties for CounterProps {
+//!     fn observed_attributes() -> &'static [&'static str] {
+//!         &["label"]
+//!     }
+//!
+//!     fn create(host: &CustomElementHost) -> Self {
+//!         Self {
+//!             label: String::new(),
+//!             // Dispatches an `increment` event with the count as detail on the element
+//!             onincrement: host.callback("increment"),
+//!         }
+//!     }
+//!
+//!     fn attribute_changed(&mut self, name: &str, value: Option<String>) {
+//!         if name == "label" {
+//!             self.label = value.unwrap_or_default();
+//!         }
+//!     }
+//! }
+//! # struct Counter;
+//! # impl Component for Counter {
+//! #     type Message = ();
+//! #     type Properties = CounterProps;
+//! #     fn create(_: &Context<Self>) -> Self { Self }
+//! #     fn view(&self, _: &Context<Self>) -> Html { html! {} }
+//! # }
+//!
+//! fn define_elements() {
+//!     // <my-counter label="Clicks"></my-counter>
+//!     yew::web_component::register::<Counter>("my-counter");
+//! }
+//! # fn main() {}
+//! ```
+
+use crate::callback::Callback;
+use crate::html::{Component, NodeRef, Properties};
+use crate::AppHandle;
+use gloo_utils::document;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use wasm_bindgen::{prelude::*, JsCast};
+use web_sys::{CustomEvent, CustomEventInit, Element, HtmlElement};
+
+thread_local! {
+    /// Key used to store the instance id on a custom element
+    static INSTANCE_ID_PROP: JsValue = "__yew_instance_id".into();
+}
+
+#[wasm_bindgen(inline_js = r#"
+export function createCustomElementClass(attributes, properties, callbacks) {
+    const cls = class extends HTMLElement {
+        static get observedAttributes() {
+            return attributes;
+        }
+
+        constructor() {
+            super();
+            this.attachShadow({ mode: "open" });
+            this.__yew_properties = {};
+            // Properties set before the element was upgraded shadow the accessors of the class
+            for (const name of properties) {
+                if (Object.prototype.hasOwnProperty.call(this, name)) {
+                    this.__yew_properties[name] = this[name];
+                    delete this[name];
+                }
+            }
+        }
+
+        connectedCallback() {
+            callbacks.connected(this, this.__yew_properties);
+        }
+
+        disconnectedCallback() {
+            callbacks.disconnected(this);
+        }
+
+        attributeChangedCallback(name, oldValue, newValue) {
+            callbacks.attributeChanged(this, name, newValue);
+        }
+    };
+    for (const name of properties) {
+        Object.defineProperty(cls.prototype, name, {
+            get() {
+                return this.__yew_properties[name];
+            },
+            set(value) {
+                this.__yew_properties[name] = value;
+                callbacks.propertyChanged(this, name, value);
+            },
+        });
+    }
+    return cls;
+}
+"#)]
+extern "C" {
+    /// Creates the class of a custom element from the observed `attributes` and `properties` and
+    /// the `callbacks` into Rust
+    #[wasm_bindgen(js_name = createCustomElementClass)]
+    fn create_class(
+        attributes: &js_sys::Array,
+        properties: &js_sys::Array,
+        callbacks: &js_sys::Object,
+    ) -> js_sys::Function;
+}
+
+/// Properties of a component registered as custom element.
+///
+/// The properties are created, when the element is connected to the document, and updated from
+/// the attributes and JS properties of the element afterwards.
+pub trait CustomElementProperties: Properties + Clone {
+    /// Attributes of the element, that are passed to
+    /// [attribute_changed](Self::attribute_changed)
+    fn observed_attributes() -> &'static [&'static str] {
+        &[]
+    }
+
+    /// JS properties defined on the element, that are passed to
+    /// [property_changed](Self::property_changed)
+    fn observed_properties() -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Creates the properties for a connected element.
+    ///
+    /// Use [CustomElementHost::callback] for callbacks that should be observable from outside of
+    /// the element.
+    fn create(host: &CustomElementHost) -> Self;
+
+    /// Called with the value of an observed attribute, when it is set on connection or changes
+    fn attribute_changed(&mut self, _name: &str, _value: Option<String>) {}
+
+    /// Called with the value of an observed JS property, when it is set on connection or changes
+    fn property_changed(&mut self, _name: &str, _value: JsValue) {}
+}
+
+impl CustomElementProperties for () {
+    fn create(_: &CustomElementHost) -> Self {}
+}
+
+/// The custom element a component is mounted in
+#[derive(Debug, Clone)]
+pub struct CustomElementHost {
+    element: HtmlElement,
+}
+
+impl CustomElementHost {
+    /// Returns the custom element
+    pub fn element(&self) -> &HtmlElement {
+        &self.element
+    }
+
+    /// Creates a [Callback], that dispatches a
+    /// [CustomEvent](https://developer.mozilla.org/en-US/docs/Web/API/CustomEvent) named `event`
+    /// on the element with the emitted value as `detail`.
+    ///
+    /// The event bubbles and crosses the shadow boundary of the element.
+    pub fn callback<T>(&self, event: &str) -> Callback<T>
+    where
+        T: Into<JsValue>,
+    {
+        let element = self.element.clone();
+        let event = event.to_owned();
+        Callback::from(move |detail: T| {
+            let init = CustomEventInit::new();
+            init.set_bubbles(true);
+            init.set_composed(true);
+            init.set_detail(&detail.into());
+            let event = CustomEvent::new_with_event_init_dict(&event, &init)
+                .expect("can't create custom event");
+            element
+                .dispatch_event(&event)
+                .expect("can't dispatch custom event");
+        })
+    }
+}
+
+/// A component mounted in a connected custom element
+struct Instance<COMP: Component> {
+    props: COMP::Properties,
+    app: AppHandle<COMP>,
+    container: Element,
+}
+
+/// Mounted instances of a custom element by ID
+struct Instances<COMP: Component> {
+    /// Counter for assigning new IDs
+    id_counter: u32,
+
+    by_id: HashMap<u32, Instance<COMP>>,
+}
+
+// The instances are never borrowed while a component renders, because the view of a component
+// may connect, change or disconnect other elements of the same custom element.
+impl<COMP> Instances<COMP>
+where
+    COMP: Component,
+    COMP::Properties: CustomElementProperties,
+{
+    fn connected(this: &RefCell<Self>, element: HtmlElement, properties: JsValue) {
+        let host = CustomElementHost {
+            element: element.clone(),
+        };
+        let mut props = COMP::Properties::create(&host);
+        for name in COMP::Properties::observed_attributes() {
+            if let Some(value) = element.get_attribute(name) {
+                props.attribute_changed(name, Some(value));
+            }
+        }
+        for name in COMP::Properties::observed_properties() {
+            let value = js_sys::Reflect::get(&properties, &JsValue::from_str(name))
+                .unwrap_or(JsValue::UNDEFINED);
+            if !value.is_undefined() {
+                props.property_changed(name, value);
+            }
+        }
+
+        // Apps are mounted in an element, but the shadow root is a document fragment
+        let container = document()
+            .create_element("div")
+            .expect("can't create container element");
+        element
+            .shadow_root()
+            .expect("custom element has no shadow root")
+            .append_child(&container)
+            .expect("can't append container element");
+        let app = AppHandle::mount_with_props(container.clone(), Rc::new(props.clone()));
+
+        let mut this = this.borrow_mut();
+        let id = this.id_counter;
+        this.id_counter += 1;
+        INSTANCE_ID_PROP.with(|prop| {
+            if !js_sys::Reflect::set(&element, prop, &js_sys::Number::from(id)).unwrap() {
+                panic!("failed to set instance ID property");
+            }
+        });
+        this.by_id.insert(
+            id,
+            Instance {
+                props,
+                app,
+                container,
+            },
+        );
+    }
+
+    fn disconnected(this: &RefCell<Self>, element: &HtmlElement) {
+        // The component is mounted again with the current attributes and properties, if the
+        // element is reconnected
+        let instance = match Self::instance_id(element) {
+            Some(id) => this.borrow_mut().by_id.remove(&id),
+            None => None,
+        };
+        if let Some(instance) = instance {
+            instance.app.destroy();
+            instance.container.remove();
+        }
+    }
+
+    /// Updates the properties of the component mounted in `element` with `f`.
+    ///
+    /// Changes to disconnected elements are read, when they are connected.
+    fn changed(this: &RefCell<Self>, element: &HtmlElement, f: impl FnOnce(&mut COMP::Properties)) {
+        let id = match Self::instance_id(element) {
+            Some(id) => id,
+            None => return,
+        };
+        let (scope, props) = match this.borrow_mut().by_id.get_mut(&id) {
+            Some(instance) => {
+                f(&mut instance.props);
+                (instance.app.scope.clone(), instance.props.clone())
+            }
+            None => return,
+        };
+        scope.reuse(Rc::new(props), NodeRef::default(), None, NodeRef::default());
+    }
+
+    fn instance_id(element: &HtmlElement) -> Option<u32> {
+        INSTANCE_ID_PROP
+            .with(|prop| js_sys::Reflect::get(element, prop).ok())
+            .and_then(|id| id.as_f64())
+            .map(|id| id as u32)
+    }
+}
+
+/// Defines a custom element named `name`, that mounts `COMP` into its shadow root.
+///
+/// The component is mounted, when the element is connected to the document, and destroyed, when
+/// it is disconnected. Changes to the observed attributes and JS properties of the element update
+/// the properties of the component as described by [CustomElementProperties].
+///
+/// # Panics
+///
+/// Panics, if `name` is not a valid custom element name or is already defined.
+pub fn register<COMP>(name: &str)
+where
+    COMP: Component,
+    COMP::Properties: CustomElementProperties,
+{
+    crate::set_default_panic_hook();
+
+    let instances = Rc::new(RefCell::new(Instances::<COMP> {
+        id_counter: 0,
+        by_id: HashMap::new(),
+    }));
+
+    let connected = {
+        let instances = instances.clone();
+        Closure::wrap(Box::new(move |element: HtmlElement, properties: JsValue| {
+            Instances::connected(&instances, element, properties);
+        }) as Box<dyn Fn(HtmlElement, JsValue)>)
+    };
+    let disconnected = {
+        let instances = instances.clone();
+        Closure::wrap(Box::new(move |element: HtmlElement| {
+            Instances::disconnected(&instances, &element);
+        }) as Box<dyn Fn(HtmlElement)>)
+    };
+    let attribute_changed = {
+        let instances = instances.clone();
+        Closure::wrap(Box::new(
+            move |element: HtmlElement, name: String, value: Option<String>| {
+                Instances::changed(&instances, &element, |props| {
+                    props.attribute_changed(&name, value)
+                });
+            },
+        )
+            as Box<dyn Fn(HtmlElement, String, Option<String>)>)
+    };
+    let property_changed = Closure::wrap(Box::new(
+        move |element: HtmlElement, name: String, value: JsValue| {
+            Instances::changed(&instances, &element, |props| {
+                props.property_changed(&name, value)
+            });
+        },
+    ) as Box<dyn Fn(HtmlElement, String, JsValue)>);
+
+    let callbacks = js_sys::Object::new();
+    for (key, closure) in [
+        ("connected", connected.as_ref()),
+        ("disconnected", disconnected.as_ref()),
+        ("attributeChanged", attribute_changed.as_ref()),
+        ("propertyChanged", property_changed.as_ref()),
+    ]
+    .iter()
+    {
+        js_sys::Reflect::set(&callbacks, &JsValue::from_str(key), closure).unwrap();
+    }
+    // Custom elements can not be undefined, so their callbacks are never dropped
+    connected.forget();
+    disconnected.forget();
+    attribute_changed.forget();
+    property_changed.forget();
+
+    let to_array = |names: &[&str]| {
+        names
+            .iter()
+            .map(|name| JsValue::from_str(name))
+            .collect::<js_sys::Array>()
+    };
+    let class = create_class(
+        &to_array(COMP::Properties::observed_attributes()),
+        &to_array(COMP::Properties::observed_properties()),
+        &callbacks,
+    );
+
+    gloo_utils::window()
+        .custom_elements()
+        .define(name, class.unchecked_ref())
+        .unwrap_or_else(|e| panic!("can't define custom element `{}`: {:?}", name, e));
+}
+
+#[cfg(all(test, feature = "wasm_test"))]
+mod tests {
+    use super::*;
+    use crate::{html, Context, Html};
+    use wasm_bindgen_test::{wasm_bindgen_test as test, wasm_bindgen_test_configure};
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[derive(Clone, PartialEq, crate::Properties)]
+    struct Props {
+        label: String,
+        count: u32,
+        onclick: Callback<u32>,
+    }
+
+    impl CustomElementProperties for Props {
+        fn observed_attributes() -> &'static [&'static str] {
+            &["label"]
+        }
+
+        fn observed_properties() -> &'static [&'static str] {
+            &["count"]
+        }
+
+        fn create(host: &CustomElementHost) -> Self {
+            Self {
+                label: String::new(),
+                count: 0,
+                onclick: host.callback("clicked"),
+            }
+        }
+
+        fn attribute_changed(&mut self, name: &str, value: Option<String>) {
+            if name == "label" {
+                self.label = value.unwrap_or_default();
+            }
+        }
+
+        fn property_changed(&mut self, name: &str, value: JsValue) {
+            if name == "count" {
+                self.count = value.as_f64().unwrap_or_default() as u32;
+            }
+        }
+    }
+
+    struct Comp;
+
+    impl Component for Comp {
+        type Message = ();
+        type Properties = Props;
+
+        fn create(_: &Context<Self>) -> Self {
+            Self
+        }
+
+        fn view(&self, ctx: &Context<Self>) -> Html {
+            let Props {
+                label,
+                count,
+                onclick,
+            } = ctx.props().clone();
+            html! {
+                <button onclick={move |_| onclick.emit(count)}>
+                    { format!("{}: {}", label, count) }
+                </button>
+            }
+        }
+    }
+
+    #[test]
+    fn maps_attributes_properties_and_events() {
+        register::<Comp>("yew-test-counter");
+
+        let element: HtmlElement = document()
+            .create_element("yew-test-counter")
+            .unwrap()
+            .unchecked_into();
+        element.set_attribute("label", "clicks").unwrap();
+        js_sys::Reflect::set(&element, &"count".into(), &1.into()).unwrap();
+        document().body().unwrap().append_child(&element).unwrap();
+
+        let shadow_root = element.shadow_root().unwrap();
+        let button: HtmlElement = shadow_root
+            .query_selector("button")
+            .unwrap()
+            .unwrap()
+            .unchecked_into();
+        assert_eq!(button.text_content().unwrap(), "clicks: 1");
+
+        element.set_attribute("label", "taps").unwrap();
+        js_sys::Reflect::set(&element, &"count".into(), &2.into()).unwrap();
+        assert_eq!(button.text_content().unwrap(), "taps: 2");
+
+        let detail = Rc::new(RefCell::new(JsValue::UNDEFINED));
+        let listener = {
+            let detail = detail.clone();
+            Closure::wrap(Box::new(move |e: CustomEvent| {
+                *detail.borrow_mut() = e.detail();
+            }) as Box<dyn Fn(CustomEvent)>)
+        };
+        element
+            .add_event_listener_with_callback("clicked", listener.as_ref().unchecked_ref())
+            .unwrap();
+        button.click();
+        assert_eq!(detail.borrow().as_f64(), Some(2.0));
+
+        element.remove();
+        assert!(shadow_root.query_selector("button").unwrap().is_none());
+    }
+}
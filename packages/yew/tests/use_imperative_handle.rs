@@ -0,0 +1,58 @@
+mod common;
+
+use common::obtain_result;
+use wasm_bindgen_test::*;
+use yew::functional::{use_imperative_handle, use_state, FunctionComponent, FunctionProvider};
+use yew::html::ComponentRef;
+use yew::{html, Html};
+
+wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+#[wasm_bindgen_test]
+fn use_imperative_handle_works() {
+    struct Handle {
+        name: &'static str,
+    }
+
+    struct ChildFunction {}
+    impl FunctionProvider for ChildFunction {
+        type TProps = ();
+
+        fn run(_: &Self::TProps) -> Html {
+            use_imperative_handle(Handle { name: "child" });
+            return html! {};
+        }
+    }
+    type ChildComponent = FunctionComponent<ChildFunction>;
+
+    struct ParentFunction {}
+    impl FunctionProvider for ParentFunction {
+        type TProps = ();
+
+        fn run(_: &Self::TProps) -> Html {
+            let child_ref = use_state(ComponentRef::<ChildComponent>::default);
+            let rendered = use_state(|| false);
+            if !*rendered {
+                rendered.set(true);
+            }
+            let name = child_ref
+                .handle::<Handle>()
+                .map(|handle| handle.name)
+                .unwrap_or_default();
+            return html! {
+                <div>
+                    <ChildComponent ref={(*child_ref).clone()} />
+                    <div id="result">{ name }</div>
+                </div>
+            };
+        }
+    }
+    type ParentComponent = FunctionComponent<ParentFunction>;
+
+    yew::start_app_in_element::<ParentComponent>(
+        gloo_utils::document().get_element_by_id("output").unwrap(),
+    );
+
+    let result = obtain_result();
+    assert_eq!(result.as_str(), "child");
+}
@@ -3,7 +3,9 @@ mod common;
 use common::obtain_result;
 use std::ops::DerefMut;
 use wasm_bindgen_test::*;
-use yew::functional::{use_mut_ref, use_state, FunctionComponent, FunctionProvider};
+use yew::functional::{
+    use_forwarded_ref, use_mut_ref, use_node_ref, use_state, FunctionComponent, FunctionProvider,
+};
 use yew::{html, Html};
 
 wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
@@ -38,3 +40,54 @@ fn use_ref_works() {
     let result = obtain_result();
     assert_eq!(result.as_str(), "true");
 }
+
+#[wasm_bindgen_test]
+fn use_forwarded_ref_works() {
+    struct InputFunction {}
+    impl FunctionProvider for InputFunction {
+        type TProps = ();
+
+        const FORWARD_REF: bool = true;
+
+        fn run(_: &Self::TProps) -> Html {
+            let node_ref = use_forwarded_ref();
+            return html! {
+                <label>
+                    <input id="forwarded" ref={node_ref} />
+                </label>
+            };
+        }
+    }
+    type InputComponent = FunctionComponent<InputFunction>;
+
+    struct FormFunction {}
+    impl FunctionProvider for FormFunction {
+        type TProps = ();
+
+        fn run(_: &Self::TProps) -> Html {
+            let input_ref = use_node_ref();
+            let rendered = use_state(|| false);
+            if !*rendered {
+                rendered.set(true);
+            }
+            let id = input_ref
+                .cast::<web_sys::Element>()
+                .map(|el| el.id())
+                .unwrap_or_default();
+            return html! {
+                <div>
+                    <InputComponent ref={input_ref} />
+                    <div id="result">{ id }</div>
+                </div>
+            };
+        }
+    }
+    type FormComponent = FunctionComponent<FormFunction>;
+
+    yew::start_app_in_element::<FormComponent>(
+        gloo_utils::document().get_element_by_id("output").unwrap(),
+    );
+
+    let result = obtain_result();
+    assert_eq!(result.as_str(), "forwarded");
+}
@@ -0,0 +1,72 @@
+use wasm_bindgen::JsCast;
+use wasm_bindgen_test::*;
+use yew::functional::{use_title, FunctionComponent, FunctionProvider};
+use yew::head::Head;
+use yew::{html, Html};
+
+wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+fn descriptions() -> Vec<String> {
+    let list = gloo_utils::document()
+        .head()
+        .unwrap()
+        .query_selector_all(r#"meta[name="description"]"#)
+        .unwrap();
+    (0..list.length())
+        .map(|i| {
+            list.item(i)
+                .unwrap()
+                .dyn_into::<web_sys::Element>()
+                .unwrap()
+                .get_attribute("content")
+                .unwrap()
+        })
+        .collect()
+}
+
+#[wasm_bindgen_test]
+fn innermost_head_contribution_wins() {
+    struct OuterFunction {}
+    impl FunctionProvider for OuterFunction {
+        type TProps = ();
+
+        fn run(_props: &Self::TProps) -> Html {
+            use_title("outer");
+            return html! {
+                <div>
+                    <Head>
+                        <meta name="description" content="outer" />
+                    </Head>
+                    <InnerComponent />
+                </div>
+            };
+        }
+    }
+    struct InnerFunction {}
+    impl FunctionProvider for InnerFunction {
+        type TProps = ();
+
+        fn run(_props: &Self::TProps) -> Html {
+            use_title("inner");
+            return html! {
+                <Head>
+                    <meta name="description" content="inner" />
+                </Head>
+            };
+        }
+    }
+    type OuterComponent = FunctionComponent<OuterFunction>;
+    type InnerComponent = FunctionComponent<InnerFunction>;
+
+    let document = gloo_utils::document();
+    let title = document.title();
+
+    let app =
+        yew::start_app_in_element::<OuterComponent>(document.get_element_by_id("output").unwrap());
+    assert_eq!(document.title(), "inner");
+    assert_eq!(descriptions(), vec!["inner".to_owned()]);
+
+    app.destroy();
+    assert_eq!(document.title(), title);
+    assert!(descriptions().is_empty());
+}